@@ -0,0 +1,229 @@
+//! Fills a database with a large synthetic set of feeds and items and checks
+//! that the queries behind the main lists use an index and stay within a
+//! latency budget.
+//!
+//! Run with `cargo bench --bench list_queries`. `LIST_QUERIES_FEEDS` and
+//! `LIST_QUERIES_ITEMS_PER_FEED` change the size of the database.
+
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+
+use diesel::{
+    connection::SimpleConnection,
+    prelude::*,
+    sql_query,
+    sql_types::{Integer, Text},
+};
+use diesel_migrations::MigrationHarness;
+use rss_reader_core::database::MIGRATIONS;
+
+const DEFAULT_FEEDS: u32 = 500;
+const DEFAULT_ITEMS_PER_FEED: u32 = 200;
+/// The slowest a list query may be, as the median of [`RUNS`] runs.
+const BUDGET: Duration = Duration::from_millis(50);
+const RUNS: usize = 21;
+const PAGE_SIZE: u32 = 100;
+const TAGS: u32 = 20;
+
+struct ListQuery {
+    name: &'static str,
+    sql: String,
+    /// Sorting a handful of rows in memory is fine, it's sorting the whole
+    /// table that has to be avoided.
+    allow_sort: bool,
+}
+
+#[derive(QueryableByName)]
+struct PlanStep {
+    #[diesel(sql_type = Text)]
+    detail: String,
+}
+
+#[derive(QueryableByName)]
+struct PageStart {
+    #[diesel(sql_type = Integer)]
+    priority: i32,
+    #[diesel(sql_type = Text)]
+    published_at: String,
+    #[diesel(sql_type = Integer)]
+    id: i32,
+}
+
+fn size_from_env(name: &str, default: u32) -> u32 {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Items get a date a few minutes apart, every tenth one has a higher
+/// priority, every twentieth is starred and every fifth has one of [`TAGS`]
+/// tags.
+fn populate(conn: &mut SqliteConnection, feeds: u32, items_per_feed: u32) -> QueryResult<()> {
+    conn.batch_execute(&format!(
+        "BEGIN;
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < {feeds})
+         INSERT INTO feeds (url, feed_url, name, create_date, update_date)
+         SELECT 'https://site' || i || '.example', 'https://site' || i || '.example/feed',
+                'Feed ' || i, '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z'
+         FROM n;
+
+         WITH RECURSIVE n(i) AS (SELECT 0 UNION ALL SELECT i + 1 FROM n WHERE i < {items} - 1)
+         INSERT INTO feed_items (channel_id, title, url, description, pub_date, published_at,
+                                 create_date, update_date, is_read, is_starred, priority)
+         SELECT i % {feeds} + 1, 'Item ' || i, 'https://example.com/items/' || i,
+                'Description of item ' || i, NULL,
+                strftime('%Y-%m-%dT%H:%M:%SZ', '2025-01-01', '+' || (i * 7) || ' minutes'),
+                '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z',
+                i % 3 = 0, i % 20 = 0, CASE WHEN i % 10 = 0 THEN 1 ELSE 0 END
+         FROM n;
+
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < {TAGS})
+         INSERT INTO tags (name, create_date, update_date)
+         SELECT 'tag ' || i, '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z' FROM n;
+         INSERT INTO item_tags (item_id, tag_id, source, create_date)
+         SELECT id, id / 5 % {TAGS} + 1, 'user', '2025-01-01T00:00:00Z'
+         FROM feed_items WHERE id % 5 = 0;
+         COMMIT;
+         ANALYZE;",
+        items = feeds * items_per_feed,
+    ))
+}
+
+fn list_queries(conn: &mut SqliteConnection, feeds: u32) -> QueryResult<Vec<ListQuery>> {
+    let order =
+        "ORDER BY feed_items.priority DESC, feed_items.published_at DESC, feed_items.id DESC";
+    let columns = "feed_items.*, feeds.feed_url, feeds.name";
+
+    // somewhere in the middle of All Articles, for the keyset page query
+    let (priority, published_at, id) = sql_query(format!(
+        "SELECT priority, published_at, id FROM feed_items WHERE is_hidden = 0 {order} \
+         LIMIT 1 OFFSET (SELECT COUNT(*) / 2 FROM feed_items)"
+    ))
+    .load::<PageStart>(conn)?
+    .pop()
+    .map(|start| (start.priority, start.published_at, start.id))
+    .unwrap_or_default();
+
+    let middle_feed = feeds / 2 + 1;
+    Ok(vec![
+        ListQuery {
+            name: "all articles, first page",
+            sql: format!(
+                "SELECT {columns} FROM feed_items LEFT JOIN feeds ON feeds.id = feed_items.channel_id \
+                 WHERE feed_items.is_hidden = 0 {order} LIMIT {PAGE_SIZE}"
+            ),
+            allow_sort: false,
+        },
+        ListQuery {
+            name: "all articles, middle page",
+            sql: format!(
+                "SELECT {columns} FROM feed_items LEFT JOIN feeds ON feeds.id = feed_items.channel_id \
+                 WHERE feed_items.is_hidden = 0 AND (feed_items.priority < {priority} \
+                 OR (feed_items.priority = {priority} AND (feed_items.published_at < '{published_at}' \
+                 OR feed_items.published_at IS NULL \
+                 OR (feed_items.published_at = '{published_at}' AND feed_items.id < {id})))) \
+                 {order} LIMIT {PAGE_SIZE}"
+            ),
+            allow_sort: false,
+        },
+        ListQuery {
+            name: "single feed",
+            sql: format!(
+                "SELECT feed_items.* FROM feed_items \
+                 WHERE feed_items.channel_id = {middle_feed} AND feed_items.is_hidden = 0 {order}"
+            ),
+            allow_sort: false,
+        },
+        ListQuery {
+            name: "unread count of a feed",
+            sql: format!(
+                "SELECT COUNT(*) FROM feed_items WHERE channel_id = {middle_feed} AND is_read = 0"
+            ),
+            allow_sort: false,
+        },
+        ListQuery {
+            name: "starred",
+            sql: "SELECT feed_items.* FROM feed_items WHERE is_starred = 1 \
+                  ORDER BY published_at DESC"
+                .to_string(),
+            allow_sort: false,
+        },
+        ListQuery {
+            name: "tagged",
+            sql: format!(
+                "SELECT {columns} FROM feed_items \
+                 INNER JOIN item_tags ON item_tags.item_id = feed_items.id \
+                 LEFT JOIN feeds ON feeds.id = feed_items.channel_id \
+                 WHERE item_tags.tag_id = 1 {order}"
+            ),
+            allow_sort: true,
+        },
+    ])
+}
+
+/// Problems with the plan of `query`, like a full scan of a table.
+fn check_plan(conn: &mut SqliteConnection, query: &ListQuery) -> QueryResult<Vec<String>> {
+    let steps = sql_query(format!("EXPLAIN QUERY PLAN {}", query.sql)).load::<PlanStep>(conn)?;
+    Ok(steps
+        .into_iter()
+        .map(|step| step.detail)
+        .filter(|detail| {
+            detail.starts_with("SCAN ")
+                || (!query.allow_sort && detail.starts_with("USE TEMP B-TREE FOR ORDER BY"))
+        })
+        .collect())
+}
+
+fn median_latency(conn: &mut SqliteConnection, query: &ListQuery) -> QueryResult<Duration> {
+    let mut timings = Vec::with_capacity(RUNS);
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        conn.batch_execute(&query.sql)?;
+        timings.push(start.elapsed());
+    }
+    timings.sort();
+    Ok(timings[RUNS / 2])
+}
+
+fn main() {
+    let feeds = size_from_env("LIST_QUERIES_FEEDS", DEFAULT_FEEDS);
+    let items_per_feed = size_from_env("LIST_QUERIES_ITEMS_PER_FEED", DEFAULT_ITEMS_PER_FEED);
+
+    let directory = env::temp_dir().join(format!("rss_reader_bench_{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let database_path = directory.join("feeds.db");
+    let mut conn = SqliteConnection::establish(&database_path.to_string_lossy()).unwrap();
+    conn.run_pending_migrations(MIGRATIONS).unwrap();
+
+    let start = Instant::now();
+    populate(&mut conn, feeds, items_per_feed).unwrap();
+    println!(
+        "populated {feeds} feeds with {} items in {:.1?}",
+        feeds * items_per_feed,
+        start.elapsed()
+    );
+
+    let mut failures = Vec::new();
+    for query in list_queries(&mut conn, feeds).unwrap() {
+        let problems = check_plan(&mut conn, &query).unwrap();
+        let latency = median_latency(&mut conn, &query).unwrap();
+        println!("{:<28} {latency:>10.2?}", query.name);
+
+        if !problems.is_empty() {
+            failures.push(format!("{}: {}", query.name, problems.join(", ")));
+        }
+        if latency > BUDGET {
+            failures.push(format!("{}: {latency:.2?} is over {BUDGET:?}", query.name));
+        }
+    }
+
+    drop(conn);
+    let _ = std::fs::remove_dir_all(&directory);
+    if !failures.is_empty() {
+        eprintln!("\n{}", failures.join("\n"));
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE feed_items DROP COLUMN thumbnail_url;
+
+ALTER TABLE feed_items DROP COLUMN content;
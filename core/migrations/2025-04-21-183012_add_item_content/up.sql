@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE feed_items ADD COLUMN content TEXT;
+
+ALTER TABLE feed_items ADD COLUMN thumbnail_url TEXT;
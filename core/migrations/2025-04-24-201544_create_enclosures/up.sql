@@ -0,0 +1,19 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS enclosures (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    item_id INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    mime_type TEXT NOT NULL,
+    -- size in bytes as advertised by the feed
+    length BIGINT,
+    -- seconds, parsed from itunes:duration
+    duration INTEGER,
+    -- seconds into the episode the user stopped listening
+    playback_position DOUBLE NOT NULL DEFAULT 0,
+    -- where the episode was cached on disk, if it has been downloaded
+    local_path TEXT,
+    create_date TEXT NOT NULL,
+    update_date TEXT NOT NULL,
+    UNIQUE(url, item_id),
+    FOREIGN KEY(item_id) REFERENCES feed_items(id) ON DELETE CASCADE
+)
@@ -0,0 +1,19 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE feed_items DROP COLUMN transcript_url;
+
+ALTER TABLE feed_items DROP COLUMN chapters_url;
+
+ALTER TABLE feed_items DROP COLUMN season;
+
+ALTER TABLE feed_items DROP COLUMN episode;
+
+ALTER TABLE feed_items DROP COLUMN explicit;
+
+ALTER TABLE feed_items DROP COLUMN itunes_author;
+
+ALTER TABLE feeds DROP COLUMN explicit;
+
+ALTER TABLE feeds DROP COLUMN itunes_author;
+
+ALTER TABLE feeds DROP COLUMN artwork_url;
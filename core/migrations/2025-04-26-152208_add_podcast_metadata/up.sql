@@ -0,0 +1,18 @@
+-- Your SQL goes here
+ALTER TABLE feeds ADD COLUMN artwork_url TEXT;
+
+ALTER TABLE feeds ADD COLUMN itunes_author TEXT;
+
+ALTER TABLE feeds ADD COLUMN explicit BOOLEAN;
+
+ALTER TABLE feed_items ADD COLUMN itunes_author TEXT;
+
+ALTER TABLE feed_items ADD COLUMN explicit BOOLEAN;
+
+ALTER TABLE feed_items ADD COLUMN episode INTEGER;
+
+ALTER TABLE feed_items ADD COLUMN season INTEGER;
+
+ALTER TABLE feed_items ADD COLUMN chapters_url TEXT;
+
+ALTER TABLE feed_items ADD COLUMN transcript_url TEXT;
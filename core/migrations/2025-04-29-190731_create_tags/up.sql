@@ -0,0 +1,18 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS tags (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    name TEXT UNIQUE NOT NULL COLLATE NOCASE,
+    create_date TEXT NOT NULL,
+    update_date TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS item_tags (
+    item_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    -- 'feed' for categories that came with the item, 'user' for ones added in the app
+    source TEXT NOT NULL,
+    create_date TEXT NOT NULL,
+    PRIMARY KEY(item_id, tag_id),
+    FOREIGN KEY(item_id) REFERENCES feed_items(id) ON DELETE CASCADE,
+    FOREIGN KEY(tag_id) REFERENCES tags(id) ON DELETE CASCADE
+)
@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+
+DROP TRIGGER feed_items_fts_update;
+
+DROP TRIGGER feed_items_fts_delete;
+
+DROP TRIGGER feed_items_fts_insert;
+
+DROP TABLE feed_items_fts;
+
+ALTER TABLE feed_items DROP COLUMN is_read;
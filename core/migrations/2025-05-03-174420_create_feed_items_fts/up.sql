@@ -0,0 +1,32 @@
+-- Your SQL goes here
+ALTER TABLE feed_items ADD COLUMN is_read BOOLEAN NOT NULL DEFAULT 0;
+
+-- external content table, the text itself stays in feed_items
+CREATE VIRTUAL TABLE feed_items_fts USING fts5(
+    title,
+    description,
+    author,
+    content,
+    content='feed_items',
+    content_rowid='id'
+);
+
+CREATE TRIGGER feed_items_fts_insert AFTER INSERT ON feed_items BEGIN
+    INSERT INTO feed_items_fts(rowid, title, description, author, content)
+    VALUES (new.id, new.title, new.description, new.author, new.content);
+END;
+
+CREATE TRIGGER feed_items_fts_delete AFTER DELETE ON feed_items BEGIN
+    INSERT INTO feed_items_fts(feed_items_fts, rowid, title, description, author, content)
+    VALUES ('delete', old.id, old.title, old.description, old.author, old.content);
+END;
+
+CREATE TRIGGER feed_items_fts_update AFTER UPDATE OF title, description, author, content ON feed_items BEGIN
+    INSERT INTO feed_items_fts(feed_items_fts, rowid, title, description, author, content)
+    VALUES ('delete', old.id, old.title, old.description, old.author, old.content);
+    INSERT INTO feed_items_fts(rowid, title, description, author, content)
+    VALUES (new.id, new.title, new.description, new.author, new.content);
+END;
+
+-- index everything that was stored before search existed
+INSERT INTO feed_items_fts(feed_items_fts) VALUES ('rebuild');
@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE rules;
+
+ALTER TABLE feed_items DROP COLUMN priority;
+
+ALTER TABLE feed_items DROP COLUMN is_hidden;
+
+ALTER TABLE feed_items DROP COLUMN is_starred;
@@ -0,0 +1,25 @@
+-- Your SQL goes here
+ALTER TABLE feed_items ADD COLUMN is_starred BOOLEAN NOT NULL DEFAULT 0;
+
+ALTER TABLE feed_items ADD COLUMN is_hidden BOOLEAN NOT NULL DEFAULT 0;
+
+ALTER TABLE feed_items ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS rules (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    -- conditions, a rule matches when every condition that is set matches
+    channel_id INTEGER,
+    title_pattern TEXT,
+    description_pattern TEXT,
+    author TEXT,
+    tag TEXT,
+    -- one of 'mark_read', 'star', 'tag', 'hide' or 'priority'
+    action TEXT NOT NULL,
+    -- the tag name for 'tag', the new priority for 'priority'
+    action_value TEXT,
+    create_date TEXT NOT NULL,
+    update_date TEXT NOT NULL,
+    FOREIGN KEY(channel_id) REFERENCES feeds(id) ON DELETE CASCADE
+)
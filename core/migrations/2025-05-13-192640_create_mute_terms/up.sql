@@ -0,0 +1,11 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS mute_terms (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    -- NULL mutes the term in every feed
+    channel_id INTEGER,
+    term TEXT NOT NULL,
+    is_regex BOOLEAN NOT NULL DEFAULT 0,
+    create_date TEXT NOT NULL,
+    update_date TEXT NOT NULL,
+    FOREIGN KEY(channel_id) REFERENCES feeds(id) ON DELETE CASCADE
+)
@@ -0,0 +1,6 @@
+-- Your SQL goes here
+-- the link exactly as the feed published it, before tracking parameters and
+-- redirect wrappers are removed from `url`
+ALTER TABLE feed_items ADD COLUMN original_url TEXT;
+
+UPDATE feed_items SET original_url = url;
@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE purged_items;
+
+ALTER TABLE feeds DROP COLUMN retain_days;
+
+ALTER TABLE feeds DROP COLUMN retain_count;
+
+DROP TABLE settings;
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT NOT NULL PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+-- NULL falls back to the global retention settings
+ALTER TABLE feeds ADD COLUMN retain_count INTEGER;
+
+ALTER TABLE feeds ADD COLUMN retain_days INTEGER;
+
+-- links of purged items, so refreshing a feed that still lists them doesn't
+-- bring them back
+CREATE TABLE IF NOT EXISTS purged_items (
+    channel_id INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    purge_date TEXT NOT NULL,
+    PRIMARY KEY(channel_id, url),
+    FOREIGN KEY(channel_id) REFERENCES feeds(id) ON DELETE CASCADE
+);
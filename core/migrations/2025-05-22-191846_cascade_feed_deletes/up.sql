@@ -0,0 +1,68 @@
+-- Your SQL goes here
+-- feeds that were deleted but still hold starred articles, hidden from the
+-- sidebar and never refreshed
+ALTER TABLE feeds ADD COLUMN archived BOOLEAN NOT NULL DEFAULT 0;
+
+-- SQLite can't change a foreign key in place, so feed_items is rebuilt with
+-- ON DELETE CASCADE
+CREATE TABLE feed_items_new (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    channel_id INTEGER NOT NULL,
+    title TEXT,
+    url TEXT,
+    description TEXT,
+    author TEXT,
+    pub_date TEXT,
+    create_date TEXT NOT NULL,
+    update_date TEXT NOT NULL,
+    content TEXT,
+    thumbnail_url TEXT,
+    itunes_author TEXT,
+    explicit BOOLEAN,
+    episode INTEGER,
+    season INTEGER,
+    chapters_url TEXT,
+    transcript_url TEXT,
+    is_read BOOLEAN NOT NULL DEFAULT 0,
+    is_starred BOOLEAN NOT NULL DEFAULT 0,
+    is_hidden BOOLEAN NOT NULL DEFAULT 0,
+    priority INTEGER NOT NULL DEFAULT 0,
+    original_url TEXT,
+    UNIQUE(url, channel_id),
+    FOREIGN KEY(channel_id) REFERENCES feeds(id) ON DELETE CASCADE
+);
+
+INSERT INTO feed_items_new (
+    id, channel_id, title, url, description, author, pub_date, create_date,
+    update_date, content, thumbnail_url, itunes_author, explicit, episode,
+    season, chapters_url, transcript_url, is_read, is_starred, is_hidden,
+    priority, original_url
+)
+SELECT
+    id, channel_id, title, url, description, author, pub_date, create_date,
+    update_date, content, thumbnail_url, itunes_author, explicit, episode,
+    season, chapters_url, transcript_url, is_read, is_starred, is_hidden,
+    priority, original_url
+FROM feed_items;
+
+-- dropping the table also drops the search triggers
+DROP TABLE feed_items;
+
+ALTER TABLE feed_items_new RENAME TO feed_items;
+
+CREATE TRIGGER feed_items_fts_insert AFTER INSERT ON feed_items BEGIN
+    INSERT INTO feed_items_fts(rowid, title, description, author, content)
+    VALUES (new.id, new.title, new.description, new.author, new.content);
+END;
+
+CREATE TRIGGER feed_items_fts_delete AFTER DELETE ON feed_items BEGIN
+    INSERT INTO feed_items_fts(feed_items_fts, rowid, title, description, author, content)
+    VALUES ('delete', old.id, old.title, old.description, old.author, old.content);
+END;
+
+CREATE TRIGGER feed_items_fts_update AFTER UPDATE OF title, description, author, content ON feed_items BEGIN
+    INSERT INTO feed_items_fts(feed_items_fts, rowid, title, description, author, content)
+    VALUES ('delete', old.id, old.title, old.description, old.author, old.content);
+    INSERT INTO feed_items_fts(rowid, title, description, author, content)
+    VALUES (new.id, new.title, new.description, new.author, new.content);
+END;
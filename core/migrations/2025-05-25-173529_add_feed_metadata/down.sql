@@ -0,0 +1,15 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE feeds DROP COLUMN icon_mime_type;
+
+ALTER TABLE feeds DROP COLUMN icon;
+
+ALTER TABLE feeds DROP COLUMN copyright;
+
+ALTER TABLE feeds DROP COLUMN generator;
+
+ALTER TABLE feeds DROP COLUMN language;
+
+ALTER TABLE feeds DROP COLUMN image_url;
+
+ALTER TABLE feeds DROP COLUMN description;
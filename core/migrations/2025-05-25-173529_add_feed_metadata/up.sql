@@ -0,0 +1,15 @@
+-- Your SQL goes here
+ALTER TABLE feeds ADD COLUMN description TEXT;
+
+ALTER TABLE feeds ADD COLUMN image_url TEXT;
+
+ALTER TABLE feeds ADD COLUMN language TEXT;
+
+ALTER TABLE feeds ADD COLUMN generator TEXT;
+
+ALTER TABLE feeds ADD COLUMN copyright TEXT;
+
+-- the site's favicon, cached so the sidebar doesn't hit the network
+ALTER TABLE feeds ADD COLUMN icon BLOB;
+
+ALTER TABLE feeds ADD COLUMN icon_mime_type TEXT;
@@ -0,0 +1,13 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE feeds DROP COLUMN last_modified;
+
+ALTER TABLE feeds DROP COLUMN etag;
+
+ALTER TABLE feeds DROP COLUMN format;
+
+ALTER TABLE feeds DROP COLUMN last_error_date;
+
+ALTER TABLE feeds DROP COLUMN last_error;
+
+ALTER TABLE feeds DROP COLUMN last_refresh_date;
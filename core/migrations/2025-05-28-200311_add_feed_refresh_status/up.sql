@@ -0,0 +1,14 @@
+-- Your SQL goes here
+ALTER TABLE feeds ADD COLUMN last_refresh_date TEXT;
+
+ALTER TABLE feeds ADD COLUMN last_error TEXT;
+
+ALTER TABLE feeds ADD COLUMN last_error_date TEXT;
+
+-- e.g. "RSS 2.0", detected from the document root
+ALTER TABLE feeds ADD COLUMN format TEXT;
+
+-- HTTP caching headers from the last successful fetch
+ALTER TABLE feeds ADD COLUMN etag TEXT;
+
+ALTER TABLE feeds ADD COLUMN last_modified TEXT;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- pub_date parsed into RFC 3339 UTC so items can be sorted and filtered by
+-- date in SQL, pub_date keeps the feed's own text. Existing rows, and the
+-- RFC 2822 create/update dates, are converted by the app when it starts.
+ALTER TABLE feed_items ADD COLUMN published_at TEXT;
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX enclosures_item;
+DROP INDEX item_tags_tag;
+DROP INDEX feed_items_starred;
+DROP INDEX feed_items_channel_unread;
+DROP INDEX feed_items_channel_order;
+DROP INDEX feed_items_order;
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+-- All Articles: visible items in display order, priority then newest first
+CREATE INDEX feed_items_order ON feed_items (is_hidden, priority DESC, published_at DESC, id DESC);
+-- a single feed in the same order
+CREATE INDEX feed_items_channel_order ON feed_items (channel_id, is_hidden, priority DESC, published_at DESC, id DESC);
+-- unread counts per feed
+CREATE INDEX feed_items_channel_unread ON feed_items (channel_id, is_read);
+-- starred items by date, also what retention and unsubscribing keep
+CREATE INDEX feed_items_starred ON feed_items (is_starred, published_at DESC);
+-- the items of a tag, item_tags' primary key starts with item_id
+CREATE INDEX item_tags_tag ON item_tags (tag_id);
+-- the enclosures shown on every card, and cascading deletes
+CREATE INDEX enclosures_item ON enclosures (item_id);
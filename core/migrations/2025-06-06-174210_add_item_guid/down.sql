@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX feed_items_channel_guid;
+ALTER TABLE feed_items DROP COLUMN guid;
@@ -0,0 +1,6 @@
+-- Your SQL goes here
+-- the item's RSS guid or Atom id, which identifies it even without a link
+ALTER TABLE feed_items ADD COLUMN guid TEXT;
+
+-- finding the stored copy of a fetched item
+CREATE INDEX feed_items_channel_guid ON feed_items (channel_id, guid);
@@ -0,0 +1,103 @@
+use rss::Item;
+use ureq::ResponseExt;
+use url::Url;
+
+use crate::fetcher::feed_agent;
+
+/// Query parameters added by newsletters, ad networks and social sites to
+/// track where a click came from. `utm_*` parameters are matched by prefix.
+const TRACKING_PARAMS: [&str; 12] = [
+    "fbclid", "gclid", "dclid", "msclkid", "mc_cid", "mc_eid", "igshid", "yclid", "_hsenc",
+    "_hsmi", "mkt_tok", "ref_src",
+];
+
+/// Hosts that wrap article links in a redirect to count clicks.
+const REDIRECT_HOSTS: [&str; 3] = [
+    "feedproxy.google.com",
+    "feeds.feedburner.com",
+    "feedburner.google.com",
+];
+
+fn is_tracking_param(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    key.starts_with("utm_") || TRACKING_PARAMS.contains(&key.as_str())
+}
+
+/// Normalizes an article link so the same story linked in slightly different
+/// ways compares equal: tracking parameters, fragments, default ports and
+/// trailing slashes are removed and the host is lower-cased.
+pub fn canonical_url(link: &str) -> Option<String> {
+    // parsing lower-cases the host and drops ports that match the scheme
+    let mut url = Url::parse(link.trim()).ok()?;
+    url.set_fragment(None);
+
+    let kept_pairs = url
+        .query_pairs()
+        .into_owned()
+        .filter(|(key, _)| !is_tracking_param(key))
+        .collect::<Vec<_>>();
+    if kept_pairs.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(kept_pairs);
+    }
+
+    let path = url.path().trim_end_matches('/').to_string();
+    url.set_path(&path);
+
+    let mut canonical = url.to_string();
+    if canonical.ends_with('/') && url.query().is_none() {
+        canonical.pop();
+    }
+    Some(canonical)
+}
+
+/// Key used to decide whether two links point at the same page, ignoring the
+/// scheme and a leading `www.`.
+pub fn url_key(link: &str) -> Option<String> {
+    let canonical = canonical_url(link)?;
+    let without_scheme = canonical
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(&canonical);
+    Some(
+        without_scheme
+            .strip_prefix("www.")
+            .unwrap_or(without_scheme)
+            .to_string(),
+    )
+}
+
+/// Whether `link` goes through a click-tracking redirect, see
+/// [`resolve_redirect`].
+pub fn is_redirect(link: &str) -> bool {
+    Url::parse(link)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .is_some_and(|host| REDIRECT_HOSTS.contains(&host.as_str()))
+}
+
+/// Follows a click-tracking redirect to the page it points at, giving its
+/// canonical link.
+pub fn resolve_redirect(link: &str) -> Option<String> {
+    let response = feed_agent().head(link).call().ok()?;
+    let resolved = response.get_uri().to_string();
+    Some(canonical_url(&resolved).unwrap_or(resolved))
+}
+
+/// The link to store for an item, before any redirect wrapper is resolved.
+/// FeedBurner feeds carry the real address in `feedburner:origLink`. Links
+/// that can't be parsed are kept as they are.
+pub fn item_link(item: &Item) -> Option<String> {
+    let original = item.link.as_deref()?.trim();
+
+    let orig_link = item
+        .extensions
+        .get("feedburner")
+        .and_then(|elements| elements.get("origLink"))
+        .and_then(|links| links.first())
+        .and_then(|link| link.value.clone())
+        .unwrap_or_else(|| original.to_string());
+
+    Some(canonical_url(&orig_link).unwrap_or(orig_link))
+}
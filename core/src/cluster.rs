@@ -0,0 +1,145 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Duration;
+
+use crate::{canonical::url_key, Article};
+
+/// Titles sharing at least this fraction of their shingles are the same story.
+const TITLE_SIMILARITY: f64 = 0.5;
+/// Only articles published this close together are compared by title, so
+/// recurring titles like "Weekly links" don't all collapse into one card.
+const TITLE_WINDOW_HOURS: i64 = 72;
+/// Shingles shared by more articles than this ("how to", "of the") say nothing
+/// about whether two titles are the same story and aren't used to find
+/// candidates.
+const COMMON_SHINGLE_LIMIT: usize = 100;
+
+/// The same story as published by one or more feeds. The first article is the
+/// one shown on the card.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArticleCluster {
+    pub articles: Vec<Article>,
+}
+
+impl ArticleCluster {
+    pub fn primary(&self) -> &Article {
+        &self.articles[0]
+    }
+}
+
+/// Word pairs of the lower-cased title, or single words for one word titles.
+fn shingles(title: &str) -> HashSet<String> {
+    let words = title
+        .split(|character: char| !character.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+
+    if words.len() < 2 {
+        return words.into_iter().collect();
+    }
+    words
+        .windows(2)
+        .map(|pair| format!("{} {}", pair[0], pair[1]))
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+fn find(parents: &mut [usize], index: usize) -> usize {
+    let mut root = index;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    // path compression
+    let mut current = index;
+    while parents[current] != root {
+        let next = parents[current];
+        parents[current] = root;
+        current = next;
+    }
+    root
+}
+
+fn union(parents: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parents, a), find(parents, b));
+    if a != b {
+        // keep the earlier article as the root so clusters stay in order
+        parents[a.max(b)] = a.min(b);
+    }
+}
+
+/// Groups articles that link to the same page, or whose titles are nearly
+/// identical, keeping the order of `articles`.
+pub fn cluster_articles(articles: Vec<Article>) -> Vec<ArticleCluster> {
+    let mut parents = (0..articles.len()).collect::<Vec<_>>();
+
+    let mut by_url: HashMap<String, usize> = HashMap::new();
+    for (index, article) in articles.iter().enumerate() {
+        if let Some(key) = article.link.as_deref().and_then(url_key) {
+            match by_url.get(&key) {
+                Some(&other) => union(&mut parents, index, other),
+                None => {
+                    by_url.insert(key, index);
+                }
+            }
+        }
+    }
+
+    let title_shingles = articles
+        .iter()
+        .map(|article| article.title.as_deref().map(shingles).unwrap_or_default())
+        .collect::<Vec<_>>();
+    // only compare articles that have at least one shingle in common
+    let mut by_shingle: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, shingles) in title_shingles.iter().enumerate() {
+        for shingle in shingles {
+            by_shingle.entry(shingle.as_str()).or_default().push(index);
+        }
+    }
+
+    let window = Duration::hours(TITLE_WINDOW_HOURS);
+    for (index, article) in articles.iter().enumerate() {
+        let candidates = title_shingles[index]
+            .iter()
+            .map(|shingle| &by_shingle[shingle.as_str()])
+            .filter(|indices| indices.len() <= COMMON_SHINGLE_LIMIT)
+            .flat_map(|indices| indices.iter().copied())
+            .filter(|&other| other > index)
+            .collect::<HashSet<_>>();
+
+        for other in candidates {
+            let close_in_time = match (article.pub_date, articles[other].pub_date) {
+                (Some(a), Some(b)) => (a - b).abs() <= window,
+                _ => true,
+            };
+            if close_in_time
+                && jaccard(&title_shingles[index], &title_shingles[other]) >= TITLE_SIMILARITY
+            {
+                union(&mut parents, index, other);
+            }
+        }
+    }
+
+    let mut clusters: Vec<ArticleCluster> = Vec::new();
+    let mut cluster_of_root: HashMap<usize, usize> = HashMap::new();
+    for (index, article) in articles.into_iter().enumerate() {
+        let root = find(&mut parents, index);
+        match cluster_of_root.get(&root) {
+            Some(&cluster) => clusters[cluster].articles.push(article),
+            None => {
+                cluster_of_root.insert(root, clusters.len());
+                clusters.push(ArticleCluster {
+                    articles: vec![article],
+                });
+            }
+        }
+    }
+    clusters
+}
@@ -0,0 +1,189 @@
+use std::{collections::HashSet, path::Path};
+
+use diesel::{
+    connection::SimpleConnection,
+    migration::MigrationSource,
+    prelude::*,
+    sql_query,
+    sql_types::{Integer, Nullable, Text},
+    sqlite::Sqlite,
+};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use tracing::info;
+
+use crate::{
+    canonical, dates,
+    settings::{get_setting, set_setting},
+    Error, Result,
+};
+
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+/// How long a query waits for another connection's write lock, in
+/// milliseconds.
+const BUSY_TIMEOUT_MS: u32 = 5000;
+
+/// Set once the stored timestamps have been rewritten as RFC 3339.
+const TIMESTAMPS_SETTING: &str = "timestamps.format";
+const TIMESTAMPS_FORMAT: &str = "rfc3339";
+
+/// Set once the stored article links have been made canonical.
+const LINKS_SETTING: &str = "links.canonical";
+
+/// Every column holding a timestamp written by the app. Dates used to be
+/// stored as RFC 2822, which doesn't sort as text.
+const TIMESTAMP_COLUMNS: [(&str, &[&str]); 9] = [
+    ("enclosures", &["create_date", "update_date"]),
+    ("feed_items", &["create_date", "update_date"]),
+    (
+        "feeds",
+        &[
+            "create_date",
+            "update_date",
+            "last_refresh_date",
+            "last_error_date",
+        ],
+    ),
+    ("item_tags", &["create_date"]),
+    ("mute_terms", &["create_date", "update_date"]),
+    ("purged_items", &["purge_date"]),
+    ("rules", &["create_date", "update_date"]),
+    ("saved_searches", &["create_date", "update_date"]),
+    ("tags", &["create_date", "update_date"]),
+];
+
+#[derive(QueryableByName)]
+struct StoredValue {
+    #[diesel(sql_type = Integer)]
+    rowid: i32,
+    #[diesel(sql_type = Nullable<Text>)]
+    value: Option<String>,
+}
+
+/// Sets `target` to `convert(source)` on every row where that gives a value.
+/// Rows where the new value would break a unique constraint are left as they
+/// are.
+fn rewrite_column(
+    conn: &mut SqliteConnection,
+    table: &str,
+    source: &str,
+    target: &str,
+    convert: impl Fn(&str) -> Option<String>,
+) -> QueryResult<()> {
+    let values = sql_query(format!(
+        "SELECT rowid AS rowid, {source} AS value FROM {table}"
+    ))
+    .load::<StoredValue>(conn)?;
+    let update = format!("UPDATE OR IGNORE {table} SET {target} = ? WHERE rowid = ?");
+    for StoredValue { rowid, value } in values {
+        let Some(converted) = value.as_deref().and_then(&convert) else {
+            continue;
+        };
+        sql_query(&update)
+            .bind::<Text, _>(converted)
+            .bind::<Integer, _>(rowid)
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Rewrites the RFC 2822 timestamps of databases created by older versions
+/// and fills in `feed_items.published_at`. Done once, in Rust, since SQLite
+/// can't parse RFC 2822.
+fn convert_timestamps(conn: &mut SqliteConnection) -> QueryResult<()> {
+    if get_setting(conn, TIMESTAMPS_SETTING)?.as_deref() == Some(TIMESTAMPS_FORMAT) {
+        return Ok(());
+    }
+
+    conn.transaction(|conn| {
+        for (table, columns) in TIMESTAMP_COLUMNS {
+            for column in columns {
+                rewrite_column(conn, table, column, column, |value| {
+                    dates::parse_stored(value).map(dates::to_stored)
+                })?;
+            }
+        }
+        rewrite_column(conn, "feed_items", "pub_date", "published_at", |value| {
+            dates::parse_feed_date(value).map(dates::to_stored)
+        })?;
+
+        set_setting(conn, TIMESTAMPS_SETTING, Some(TIMESTAMPS_FORMAT))?;
+        Ok(())
+    })
+}
+
+/// Makes the article links stored by versions that didn't clean them up
+/// canonical, so the next refresh matches them instead of storing each
+/// article again. Done once, in Rust, like [`convert_timestamps`].
+fn canonicalize_links(conn: &mut SqliteConnection) -> QueryResult<()> {
+    if get_setting(conn, LINKS_SETTING)?.is_some() {
+        return Ok(());
+    }
+
+    conn.transaction(|conn| {
+        for table in ["feed_items", "purged_items"] {
+            rewrite_column(conn, table, "url", "url", canonical::canonical_url)?;
+        }
+        set_setting(conn, LINKS_SETTING, Some("1"))?;
+        Ok(())
+    })
+}
+
+/// Fails if the database has migrations this build doesn't know about, which
+/// means a newer version of the app has been using it.
+fn check_not_newer(conn: &mut SqliteConnection, path: &Path) -> Result<()> {
+    let known = MigrationSource::<Sqlite>::migrations(&MIGRATIONS)
+        .map_err(|error| Error::Migration(error.to_string()))?
+        .iter()
+        .map(|migration| migration.name().version().to_string())
+        .collect::<HashSet<_>>();
+    let applied = conn
+        .applied_migrations()
+        .map_err(|error| Error::Migration(error.to_string()))?;
+
+    if let Some(unknown) = applied
+        .iter()
+        .map(ToString::to_string)
+        .filter(|version| !known.contains(version))
+        .max()
+    {
+        return Err(Error::NewerDatabase {
+            path: path.to_path_buf(),
+            version: unknown,
+        });
+    }
+    Ok(())
+}
+
+/// The newest migration applied to the database.
+pub fn schema_version(conn: &mut SqliteConnection) -> Result<Option<String>> {
+    let applied = conn
+        .applied_migrations()
+        .map_err(|error| Error::Migration(error.to_string()))?;
+    Ok(applied.iter().map(ToString::to_string).max())
+}
+
+/// Opens the database at `path` and brings its schema up to date.
+pub fn establish(path: &Path) -> Result<SqliteConnection> {
+    let database_url = path.to_string_lossy();
+    let mut conn = SqliteConnection::establish(&database_url)?;
+    conn.batch_execute(&format!(
+        "PRAGMA journal_mode = WAL; PRAGMA busy_timeout = {BUSY_TIMEOUT_MS};"
+    ))?;
+
+    check_not_newer(&mut conn, path)?;
+    let migrated = conn
+        .run_pending_migrations(MIGRATIONS)
+        .map_err(|error| Error::Migration(error.to_string()))?;
+    if !migrated.is_empty() {
+        let versions = migrated.iter().map(ToString::to_string).collect::<Vec<_>>();
+        info!(path = %path.display(), ?versions, "migrated the database");
+    }
+    convert_timestamps(&mut conn)?;
+    canonicalize_links(&mut conn)?;
+
+    // turned on only after migrating: migrations that rebuild a table drop the
+    // old one, which would cascade to every row referencing it
+    conn.batch_execute("PRAGMA foreign_keys = ON;")?;
+    Ok(conn)
+}
@@ -0,0 +1,106 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+
+/// Formats a timestamp for the database. RFC 3339 in UTC with whole seconds,
+/// so timestamps sort correctly as text.
+pub fn to_stored(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Reads a timestamp written by [`to_stored`], or the RFC 2822 text stored
+/// before timestamps were made sortable.
+pub fn parse_stored(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_rfc2822(value))
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// Zone abbreviations feeds use instead of numeric offsets.
+const ZONES: [(&str, &str); 14] = [
+    ("UTC", "+0000"),
+    ("GMT", "+0000"),
+    ("UT", "+0000"),
+    ("Z", "+0000"),
+    ("EST", "-0500"),
+    ("EDT", "-0400"),
+    ("CST", "-0600"),
+    ("CDT", "-0500"),
+    ("MST", "-0700"),
+    ("MDT", "-0600"),
+    ("PST", "-0800"),
+    ("PDT", "-0700"),
+    ("CET", "+0100"),
+    ("CEST", "+0200"),
+];
+
+const ZONED_FORMATS: [&str; 6] = [
+    "%d %b %Y %H:%M:%S %z",
+    "%d %b %Y %H:%M %z",
+    "%d %B %Y %H:%M:%S %z",
+    "%d %b %y %H:%M:%S %z",
+    "%Y-%m-%d %H:%M:%S%.f %z",
+    "%Y-%m-%dT%H:%M:%S%.f%z",
+];
+
+/// Formats without an offset, read as UTC.
+const NAIVE_FORMATS: [&str; 5] = [
+    "%d %b %Y %H:%M:%S",
+    "%d %B %Y %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Rewrites the RFC 822 style dates feeds actually send into something the
+/// strict formats accept: the weekday, which is often wrong or spelled out,
+/// is dropped and zone names become offsets.
+fn normalize(value: &str) -> String {
+    let without_weekday = match value.split_once(',') {
+        Some((weekday, rest)) if weekday.chars().all(char::is_alphabetic) => rest,
+        _ => value,
+    };
+
+    let mut words = without_weekday.split_whitespace().collect::<Vec<_>>();
+    if let Some(last) = words.last_mut() {
+        if let Some((_, offset)) = ZONES
+            .iter()
+            .find(|(zone, _)| last.eq_ignore_ascii_case(zone))
+        {
+            *last = offset;
+        }
+    }
+    words.join(" ")
+}
+
+/// Parses the date of a feed item. Feeds are supposed to use RFC 2822 (RSS)
+/// or RFC 3339 (Atom) but plenty get it slightly wrong, so a handful of
+/// common variations are accepted as well.
+pub fn parse_feed_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let strict =
+        DateTime::parse_from_rfc2822(value).or_else(|_| DateTime::parse_from_rfc3339(value));
+    if let Ok(time) = strict {
+        return Some(time.with_timezone(&Utc));
+    }
+
+    let normalized = normalize(value);
+    let zoned = ZONED_FORMATS
+        .iter()
+        .find_map(|format| DateTime::<FixedOffset>::parse_from_str(&normalized, format).ok());
+    if let Some(time) = zoned {
+        return Some(time.with_timezone(&Utc));
+    }
+
+    let naive = NAIVE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(&normalized, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(&normalized, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        });
+    naive.map(|time| time.and_utc())
+}
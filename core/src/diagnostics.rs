@@ -0,0 +1,238 @@
+//! Reading the log files back for the log viewer, and bundling them with a
+//! few anonymous numbers for bug reports.
+
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use diesel::prelude::*;
+use regex::Regex;
+use tracing::Level;
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use crate::{database, repository, schema, Result};
+
+/// Log files are named `<prefix>.<date>`, one per day.
+pub const LOG_FILE_PREFIX: &str = "rss_reader.log";
+/// How many daily log files are kept before the oldest is removed.
+pub const LOG_FILES_KEPT: usize = 7;
+
+/// One event read back from a log file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogEntry {
+    pub time: String,
+    pub level: Level,
+    /// The feed whose span the event happened in.
+    pub feed_id: Option<i32>,
+    /// The spans, target, message and fields, as written.
+    pub text: String,
+}
+
+impl LogEntry {
+    /// Reads a line written by the `fmt` subscriber without colours, like
+    /// `2025-05-01T09:00:00.000Z  INFO feed{id=3}: rss_reader_core::ingest: refreshing`.
+    pub fn parse(line: &str) -> Option<Self> {
+        let (time, rest) = line.split_once(' ')?;
+        let rest = rest.trim_start();
+        let (level, text) = rest.split_once(' ').unwrap_or((rest, ""));
+        Some(LogEntry {
+            time: time.to_string(),
+            level: level.parse().ok()?,
+            feed_id: feed_id(text),
+            text: text.to_string(),
+        })
+    }
+}
+
+/// The `id` field of the innermost `feed` span in `text`.
+fn feed_id(text: &str) -> Option<i32> {
+    let (_, fields) = text.rsplit_once("feed{")?;
+    let (fields, _) = fields.split_once('}')?;
+    fields
+        .split(' ')
+        .find_map(|field| field.strip_prefix("id="))
+        .and_then(|id| id.parse().ok())
+}
+
+fn log_files(log_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(log_dir)? {
+        let path = entry?.path();
+        let is_log = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with(LOG_FILE_PREFIX));
+        if is_log && path.is_file() {
+            files.push(path);
+        }
+    }
+    // the dates in the names sort oldest first
+    files.sort();
+    Ok(files)
+}
+
+/// Every event in the log files of `log_dir`, oldest first. Lines that don't
+/// start an event, like the rest of a message spanning several lines, are
+/// added to the event before them.
+pub fn read_logs(log_dir: &Path) -> io::Result<Vec<LogEntry>> {
+    let mut entries: Vec<LogEntry> = Vec::new();
+    if !log_dir.exists() {
+        return Ok(entries);
+    }
+    for path in log_files(log_dir)? {
+        let content = fs::read(&path)?;
+        for line in String::from_utf8_lossy(&content).lines() {
+            match (LogEntry::parse(line), entries.last_mut()) {
+                (Some(entry), _) => entries.push(entry),
+                (None, Some(previous)) => {
+                    previous.text.push('\n');
+                    previous.text.push_str(line);
+                }
+                (None, None) => {}
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// How fetching a feed has been going, without anything that says which feed
+/// it is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FetchStats {
+    pub feed_id: i32,
+    pub format: Option<String>,
+    pub items: i64,
+    pub last_refresh_date: Option<String>,
+    pub last_error_date: Option<String>,
+    /// Whether the last refresh failed.
+    pub failing: bool,
+    /// Whether the server supports conditional requests.
+    pub conditional: bool,
+    pub archived: bool,
+}
+
+pub fn fetch_stats(conn: &mut SqliteConnection) -> QueryResult<Vec<FetchStats>> {
+    let counts = {
+        use schema::feed_items::dsl::*;
+
+        feed_items
+            .group_by(channel_id)
+            .select((channel_id, diesel::dsl::count_star()))
+            .load::<(i32, i64)>(conn)?
+    };
+    let feeds = repository::load_feeds(conn)?;
+    Ok(feeds
+        .into_iter()
+        .map(|feed| FetchStats {
+            feed_id: feed.id,
+            format: feed.format,
+            items: counts
+                .iter()
+                .find(|(feed_id, _)| *feed_id == feed.id)
+                .map_or(0, |(_, count)| *count),
+            last_refresh_date: feed.last_refresh_date,
+            last_error_date: feed.last_error_date,
+            failing: feed.last_error.is_some(),
+            conditional: feed.etag.is_some() || feed.last_modified.is_some(),
+            archived: feed.archived,
+        })
+        .collect())
+}
+
+/// Anything that looks like an address, and the `url` fields of spans and
+/// events whatever they hold.
+static ADDRESS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\burl=("[^"]*"|[^\s}]+)|\b[a-zA-Z][a-zA-Z0-9+.-]*://[^\s"'}]+"#).unwrap()
+});
+
+/// A log line with the addresses and names of `feeds` replaced by their id,
+/// and any other address left out.
+fn redact(line: &str, feeds: &[(i32, String)]) -> String {
+    let mut line = line.to_string();
+    for (id, known) in feeds {
+        line = line.replace(known.as_str(), &format!("feed#{id}"));
+    }
+    ADDRESS
+        .replace_all(&line, |captures: &regex::Captures| match captures.get(1) {
+            // already replaced by the feed's id
+            Some(value) if value.as_str().trim_matches('"').starts_with("feed#") => {
+                captures[0].to_string()
+            }
+            Some(_) => "url=<address>".to_string(),
+            None => "<address>".to_string(),
+        })
+        .into_owned()
+}
+
+fn stats_table(stats: &[FetchStats]) -> String {
+    let mut table = String::from(
+        "feed\tformat\titems\tlast refresh\tfailing\tlast error\tconditional\tarchived\n",
+    );
+    for feed in stats {
+        table.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            feed.feed_id,
+            feed.format.as_deref().unwrap_or("-"),
+            feed.items,
+            feed.last_refresh_date.as_deref().unwrap_or("-"),
+            feed.failing,
+            feed.last_error_date.as_deref().unwrap_or("-"),
+            feed.conditional,
+            feed.archived,
+        ));
+    }
+    table
+}
+
+/// Zips the log files of `log_dir`, the schema version and the
+/// [`fetch_stats`] into `destination`. Feed names and addresses are left out:
+/// the logs are copied with those of stored feeds replaced by the feed's id
+/// and every other address removed.
+pub fn export_bundle(
+    conn: &mut SqliteConnection,
+    log_dir: &Path,
+    destination: &Path,
+) -> Result<()> {
+    let schema_version = database::schema_version(conn)?;
+    let stats = fetch_stats(conn)?;
+    let mut feeds = repository::load_feeds(conn)?
+        .into_iter()
+        .flat_map(|feed| [(feed.id, feed.feed_url), (feed.id, feed.name)])
+        .filter(|(_, known)| !known.trim().is_empty())
+        .collect::<Vec<_>>();
+    // longest first, so a name containing another is replaced whole
+    feeds.sort_by_key(|(_, known)| std::cmp::Reverse(known.len()));
+
+    let mut zip = ZipWriter::new(File::create(destination)?);
+    let options = SimpleFileOptions::default();
+
+    zip.start_file("version.txt", options)
+        .map_err(io::Error::from)?;
+    writeln!(zip, "app {}", env!("CARGO_PKG_VERSION"))?;
+    writeln!(
+        zip,
+        "schema {}",
+        schema_version.as_deref().unwrap_or("none")
+    )?;
+
+    zip.start_file("fetch_stats.tsv", options)
+        .map_err(io::Error::from)?;
+    zip.write_all(stats_table(&stats).as_bytes())?;
+
+    if log_dir.exists() {
+        for path in log_files(log_dir)? {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            zip.start_file(format!("logs/{name}"), options)
+                .map_err(io::Error::from)?;
+            let content = fs::read(&path)?;
+            for line in String::from_utf8_lossy(&content).lines() {
+                writeln!(zip, "{}", redact(line, &feeds))?;
+            }
+        }
+    }
+
+    zip.finish().map_err(io::Error::from)?;
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use std::{fmt, io, path::PathBuf};
+
+use url::Url;
+
+/// Everything that can go wrong fetching, parsing and storing feeds.
+#[derive(Debug)]
+pub enum Error {
+    Connection(diesel::ConnectionError),
+    Database(diesel::result::Error),
+    /// The schema couldn't be read or brought up to date.
+    Migration(String),
+    /// The database was last used by a newer version of the app.
+    NewerDatabase {
+        path: PathBuf,
+        version: String,
+    },
+    Http(ureq::Error),
+    Io(io::Error),
+    InvalidUrl(url::ParseError),
+    Parse(rss::Error),
+    /// Nothing that looks like a feed was found at the address.
+    NoFeed(Url),
+    /// A feed with this address is already subscribed to.
+    AlreadySubscribed(Url),
+    FeedNotFound(i32),
+    /// Input the user gave that can't be used, with the reason.
+    Invalid(String),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Connection(error) => write!(f, "couldn't open the database: {error}"),
+            Error::Database(error) => write!(f, "database error: {error}"),
+            Error::Migration(error) => write!(f, "couldn't update the database schema: {error}"),
+            Error::NewerDatabase { path, version } => write!(
+                f,
+                "{} was last used by a newer version of this app (schema {version}). \
+                 Update the app, or start it with --profile to use a different database.",
+                path.display()
+            ),
+            Error::Http(error) => write!(f, "couldn't fetch the feed: {error}"),
+            Error::Io(error) => write!(f, "{error}"),
+            Error::InvalidUrl(error) => write!(f, "invalid URL: {error}"),
+            Error::Parse(error) => write!(f, "couldn't read the feed: {error}"),
+            Error::NoFeed(url) => write!(f, "no feed found at {url}"),
+            Error::AlreadySubscribed(url) => write!(f, "you're already subscribed to {url}"),
+            Error::FeedNotFound(id) => write!(f, "feed {id} doesn't exist"),
+            Error::Invalid(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Connection(error) => Some(error),
+            Error::Database(error) => Some(error),
+            Error::Http(error) => Some(error),
+            Error::Io(error) => Some(error),
+            Error::InvalidUrl(error) => Some(error),
+            Error::Parse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<diesel::result::Error> for Error {
+    fn from(error: diesel::result::Error) -> Self {
+        Error::Database(error)
+    }
+}
+
+impl From<diesel::ConnectionError> for Error {
+    fn from(error: diesel::ConnectionError) -> Self {
+        Error::Connection(error)
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(error: ureq::Error) -> Self {
+        Error::Http(error)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<url::ParseError> for Error {
+    fn from(error: url::ParseError) -> Self {
+        Error::InvalidUrl(error)
+    }
+}
+
+impl From<rss::Error> for Error {
+    fn from(error: rss::Error) -> Self {
+        Error::Parse(error)
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::LazyLock;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use regex::Regex;
+use ureq::Agent;
+use url::Url;
+
+use crate::fetcher::feed_agent;
+
+/// Icons bigger than this are skipped rather than stored in the database.
+const MAX_ICON_BYTES: u64 = 256 * 1024;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Favicon {
+    pub data: Vec<u8>,
+    pub mime_type: String,
+}
+
+static LINK_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?is)<link\b[^>]*>").unwrap());
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)\b(rel|href)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))"#).unwrap()
+});
+
+/// Icon links declared in the page's `<head>`, `rel="icon"` and
+/// `rel="shortcut icon"` before `rel="apple-touch-icon"`.
+fn icon_links(html: &str, page: &Url) -> Vec<Url> {
+    let mut icons = Vec::new();
+    let mut touch_icons = Vec::new();
+    for tag in LINK_TAG.find_iter(html) {
+        let mut rel = None;
+        let mut href = None;
+        for captures in ATTRIBUTE.captures_iter(tag.as_str()) {
+            let value = captures
+                .get(2)
+                .or(captures.get(3))
+                .or(captures.get(4))
+                .map(|value| value.as_str().trim().to_string());
+            match captures[1].to_ascii_lowercase().as_str() {
+                "rel" => rel = value.map(|rel| rel.to_ascii_lowercase()),
+                _ => href = value,
+            }
+        }
+
+        let (Some(rel), Some(href)) = (rel, href) else {
+            continue;
+        };
+        let Ok(href) = page.join(&href) else {
+            continue;
+        };
+        let rels = rel.split_whitespace().collect::<Vec<_>>();
+        if rels.contains(&"icon") {
+            icons.push(href);
+        } else if rels.contains(&"apple-touch-icon") {
+            touch_icons.push(href);
+        }
+    }
+    icons.extend(touch_icons);
+    icons
+}
+
+fn download_icon(agent: &Agent, url: &Url) -> Option<Favicon> {
+    let response = agent.get(url.as_str()).call().ok()?;
+    let mime_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        })
+        .filter(|mime| mime.starts_with("image/"))
+        // plenty of servers send .ico files without a usable content type
+        .or_else(|| {
+            url.path()
+                .ends_with(".ico")
+                .then(|| "image/x-icon".to_string())
+        })?;
+
+    let data = response
+        .into_body()
+        .with_config()
+        .limit(MAX_ICON_BYTES)
+        .read_to_vec()
+        .ok()?;
+    if data.is_empty() {
+        return None;
+    }
+    Some(Favicon { data, mime_type })
+}
+
+/// Finds the site's icon, looking at the icon links of its home page first and
+/// falling back to `/favicon.ico`.
+pub fn fetch_favicon(site: &Url) -> Option<Favicon> {
+    let agent = feed_agent();
+
+    let html = agent
+        .get(site.as_str())
+        .call()
+        .ok()
+        .and_then(|response| response.into_body().read_to_string().ok())
+        .unwrap_or_default();
+    let mut candidates = icon_links(&html, site);
+    if let Ok(fallback) = site.join("/favicon.ico") {
+        candidates.push(fallback);
+    }
+
+    candidates
+        .iter()
+        .find_map(|candidate| download_icon(&agent, candidate))
+}
+
+/// The address of the site a feed belongs to, from the channel's `<link>` or
+/// else the feed's own host.
+pub fn site_url(channel_link: &str, feed_url: &Url) -> Option<Url> {
+    Url::parse(channel_link.trim())
+        .ok()
+        .filter(|url| url.has_host())
+        .or_else(|| feed_url.join("/").ok())
+}
+
+/// The icon as a `data:` URL, so it can be used as an image source without a
+/// file on disk.
+pub fn data_url(mime_type: &str, data: &[u8]) -> String {
+    format!("data:{mime_type};base64,{}", STANDARD.encode(data))
+}
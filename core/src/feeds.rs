@@ -0,0 +1,290 @@
+use std::sync::LazyLock;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::{
+    dsl::{exists, not},
+    prelude::*,
+};
+use regex::Regex;
+use rss::Channel;
+use url::Url;
+
+use crate::{dates, favicon::Favicon, schema, Error, Result, StringTime};
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Channel details that can change between refreshes.
+#[derive(AsChangeset, Clone, Debug, Default, PartialEq)]
+#[diesel(table_name = schema::feeds)]
+pub struct FeedMetadata {
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub language: Option<String>,
+    pub generator: Option<String>,
+    pub copyright: Option<String>,
+}
+
+impl FeedMetadata {
+    pub fn from_channel(channel: &Channel) -> Self {
+        FeedMetadata {
+            description: non_empty(&channel.description),
+            image_url: channel
+                .image
+                .as_ref()
+                .and_then(|image| non_empty(&image.url)),
+            language: channel.language.as_deref().and_then(non_empty),
+            generator: channel.generator.as_deref().and_then(non_empty),
+            copyright: channel.copyright.as_deref().and_then(non_empty),
+        }
+    }
+}
+
+/// Stores the latest channel details. Fields the feed stopped sending keep
+/// their old value.
+pub fn update_feed_metadata(
+    conn: &mut SqliteConnection,
+    feed_id: i32,
+    metadata: FeedMetadata,
+) -> QueryResult<usize> {
+    use schema::feeds::dsl::*;
+
+    if metadata == FeedMetadata::default() {
+        return Ok(0);
+    }
+    diesel::update(feeds.filter(id.eq(feed_id)))
+        .set(metadata)
+        .execute(conn)
+}
+
+pub fn set_feed_icon(
+    conn: &mut SqliteConnection,
+    feed_id: i32,
+    favicon: Favicon,
+) -> QueryResult<usize> {
+    use schema::feeds::dsl::*;
+
+    diesel::update(feeds.filter(id.eq(feed_id)))
+        .set((icon.eq(favicon.data), icon_mime_type.eq(favicon.mime_type)))
+        .execute(conn)
+}
+
+pub fn rename_feed(conn: &mut SqliteConnection, feed_id: i32, new_name: &str) -> Result<()> {
+    use schema::feeds::dsl::*;
+
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        return Err(Error::Invalid("feed name is empty".to_string()));
+    }
+
+    diesel::update(feeds.filter(id.eq(feed_id)))
+        .set((
+            name.eq(new_name),
+            update_date.eq(StringTime::from(Utc::now())),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Points a feed at a new address. The caller is expected to have checked
+/// that `new_feed_url` serves a feed.
+pub fn change_feed_url(
+    conn: &mut SqliteConnection,
+    feed_id: i32,
+    new_feed_url: &Url,
+) -> Result<()> {
+    use schema::feeds::dsl::*;
+
+    let site = new_feed_url
+        .host_str()
+        .ok_or_else(|| Error::Invalid(format!("{new_feed_url} has no host")))?;
+    diesel::update(feeds.filter(id.eq(feed_id)))
+        .set((
+            url.eq(site),
+            feed_url.eq(new_feed_url.as_str()),
+            update_date.eq(StringTime::from(Utc::now())),
+        ))
+        .execute(conn)
+        .map_err(|error| match error {
+            diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::UniqueViolation,
+                _,
+            ) => Error::AlreadySubscribed(new_feed_url.clone()),
+            error => error.into(),
+        })?;
+    Ok(())
+}
+
+/// Unsubscribes from a feed. With `keep_starred` the feed's starred articles
+/// stay readable and the feed is archived instead of removed.
+pub fn delete_feed(
+    conn: &mut SqliteConnection,
+    feed_id: i32,
+    keep_starred: bool,
+) -> QueryResult<()> {
+    use schema::{feed_items, feeds, item_tags, mute_terms, purged_items, rules, tags};
+
+    conn.transaction(|conn| {
+        let mut doomed = feed_items::table
+            .filter(feed_items::channel_id.eq(feed_id))
+            .select(feed_items::id)
+            .into_boxed();
+        if keep_starred {
+            doomed = doomed.filter(feed_items::is_starred.eq(false));
+        }
+        let doomed = doomed.load::<i32>(conn)?;
+
+        // enclosures and tags of the items go with them through ON DELETE CASCADE
+        for chunk in doomed.chunks(500) {
+            diesel::delete(feed_items::table.filter(feed_items::id.eq_any(chunk))).execute(conn)?;
+        }
+        diesel::delete(tags::table.filter(not(exists(
+            item_tags::table.filter(item_tags::tag_id.eq(tags::id)),
+        ))))
+        .execute(conn)?;
+        // an archived feed keeps its row, so nothing cascades from it
+        diesel::delete(rules::table.filter(rules::channel_id.eq(feed_id))).execute(conn)?;
+        diesel::delete(mute_terms::table.filter(mute_terms::channel_id.eq(feed_id)))
+            .execute(conn)?;
+        diesel::delete(purged_items::table.filter(purged_items::channel_id.eq(feed_id)))
+            .execute(conn)?;
+
+        let starred_left = diesel::select(exists(
+            feed_items::table.filter(feed_items::channel_id.eq(feed_id)),
+        ))
+        .get_result::<bool>(conn)?;
+        if starred_left {
+            diesel::update(feeds::table.filter(feeds::id.eq(feed_id)))
+                .set(feeds::archived.eq(true))
+                .execute(conn)?;
+        } else {
+            diesel::delete(feeds::table.filter(feeds::id.eq(feed_id))).execute(conn)?;
+        }
+        Ok(())
+    })
+}
+
+/// What a successful fetch told us about the feed besides its items.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FetchInfo {
+    pub format: Option<String>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl FetchInfo {
+    pub fn from_response(headers: &ureq::http::HeaderMap, content: &[u8]) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        FetchInfo {
+            format: detect_format(content),
+            etag: header("etag"),
+            last_modified: header("last-modified"),
+        }
+    }
+}
+
+static RSS_VERSION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)<rss\b[^>]*\bversion\s*=\s*["']([^"']+)["']"#).unwrap());
+
+/// Names the kind of feed from its root element, e.g. "RSS 2.0" or "Atom".
+pub fn detect_format(content: &[u8]) -> Option<String> {
+    // the root element is always near the top, after the prolog and comments
+    let head = String::from_utf8_lossy(&content[..content.len().min(2048)]);
+    if let Some(captures) = RSS_VERSION.captures(&head) {
+        Some(format!("RSS {}", &captures[1]))
+    } else if head.contains("<rss") {
+        Some("RSS".to_string())
+    } else if head.contains("<rdf:RDF") {
+        Some("RSS 1.0".to_string())
+    } else if head.contains("<feed") {
+        Some("Atom".to_string())
+    } else {
+        None
+    }
+}
+
+pub fn record_refresh(
+    conn: &mut SqliteConnection,
+    feed_id: i32,
+    info: FetchInfo,
+    now: DateTime<Utc>,
+) -> QueryResult<usize> {
+    use schema::feeds::dsl::*;
+
+    diesel::update(feeds.filter(id.eq(feed_id)))
+        .set((
+            last_refresh_date.eq(StringTime::from(now)),
+            last_error.eq(None::<String>),
+            last_error_date.eq(None::<String>),
+            format.eq(info.format),
+            etag.eq(info.etag),
+            last_modified.eq(info.last_modified),
+        ))
+        .execute(conn)
+}
+
+pub fn record_refresh_error(
+    conn: &mut SqliteConnection,
+    feed_id: i32,
+    error: &str,
+    now: DateTime<Utc>,
+) -> QueryResult<usize> {
+    use schema::feeds::dsl::*;
+
+    diesel::update(feeds.filter(id.eq(feed_id)))
+        .set((
+            last_error.eq(error),
+            last_error_date.eq(StringTime::from(now)),
+        ))
+        .execute(conn)
+}
+
+/// Counts and dates describing a feed's stored items.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeedStats {
+    pub total: i64,
+    pub unread: i64,
+    pub oldest: Option<DateTime<Utc>>,
+    pub newest: Option<DateTime<Utc>>,
+    /// Average time between two items, when there are at least two dated ones.
+    pub average_interval: Option<TimeDelta>,
+}
+
+pub fn load_feed_stats(conn: &mut SqliteConnection, feed_id: i32) -> QueryResult<FeedStats> {
+    use schema::feed_items::dsl::*;
+
+    let items = feed_items.filter(channel_id.eq(feed_id));
+    let total = items.count().get_result(conn)?;
+    let unread = items.filter(is_read.eq(false)).count().get_result(conn)?;
+
+    let (first, last, dated) = items
+        .filter(published_at.is_not_null())
+        .select((
+            diesel::dsl::min(published_at),
+            diesel::dsl::max(published_at),
+            diesel::dsl::count(published_at),
+        ))
+        .get_result::<(Option<String>, Option<String>, i64)>(conn)?;
+
+    let oldest = first.as_deref().and_then(dates::parse_stored);
+    let newest = last.as_deref().and_then(dates::parse_stored);
+    let average_interval = match (oldest, newest) {
+        (Some(oldest), Some(newest)) if dated > 1 => Some((newest - oldest) / (dated as i32 - 1)),
+        _ => None,
+    };
+
+    Ok(FeedStats {
+        total,
+        unread,
+        oldest,
+        newest,
+        average_interval,
+    })
+}
@@ -0,0 +1,214 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use rss::Channel;
+use tracing::{debug, info};
+use ureq::{
+    http::StatusCode,
+    unversioned::{
+        resolver::DefaultResolver,
+        transport::{
+            Buffers, ConnectionDetails, Connector, DefaultConnector, NextTimeout, Transport,
+        },
+    },
+    Agent,
+};
+use url::Url;
+
+use crate::{feeds::FetchInfo, parser::parse_channel, Error, Result};
+
+/// How long fetching a feed may take altogether before it's given up on.
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An agent that gives up after [`FETCH_TIMEOUT`].
+pub(crate) fn feed_agent() -> Agent {
+    Agent::config_builder()
+        .timeout_global(Some(FETCH_TIMEOUT))
+        .build()
+        .into()
+}
+
+/// How long a download may go without a byte arriving before it's given up
+/// on. Enclosures can be large, so there's no limit on the download as a whole.
+pub const STALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An agent that gives up on connecting after [`FETCH_TIMEOUT`] and on a
+/// connection after it stalls for [`STALL_TIMEOUT`].
+fn download_agent() -> Agent {
+    let config = Agent::config_builder()
+        .timeout_connect(Some(FETCH_TIMEOUT))
+        .build();
+    let connector = DefaultConnector::new().chain(StallTimeout);
+    Agent::with_parts(config, connector, DefaultResolver::default())
+}
+
+/// Wraps connections in [`Stalling`].
+#[derive(Debug)]
+struct StallTimeout;
+
+impl Connector<Box<dyn Transport>> for StallTimeout {
+    type Out = Stalling;
+
+    fn connect(
+        &self,
+        _: &ConnectionDetails,
+        chained: Option<Box<dyn Transport>>,
+    ) -> std::result::Result<Option<Self::Out>, ureq::Error> {
+        Ok(chained.map(Stalling))
+    }
+}
+
+/// A connection that waits at most [`STALL_TIMEOUT`] for every read and
+/// write, whatever time the request has left overall.
+#[derive(Debug)]
+struct Stalling(Box<dyn Transport>);
+
+impl Stalling {
+    fn shorten(timeout: NextTimeout) -> NextTimeout {
+        NextTimeout {
+            after: timeout.after.min(STALL_TIMEOUT.into()),
+            reason: timeout.reason,
+        }
+    }
+}
+
+impl Transport for Stalling {
+    fn buffers(&mut self) -> &mut dyn Buffers {
+        self.0.buffers()
+    }
+
+    fn transmit_output(
+        &mut self,
+        amount: usize,
+        timeout: NextTimeout,
+    ) -> std::result::Result<(), ureq::Error> {
+        self.0.transmit_output(amount, Self::shorten(timeout))
+    }
+
+    fn await_input(&mut self, timeout: NextTimeout) -> std::result::Result<bool, ureq::Error> {
+        self.0.await_input(Self::shorten(timeout))
+    }
+
+    fn is_open(&mut self) -> bool {
+        self.0.is_open()
+    }
+
+    fn is_tls(&self) -> bool {
+        self.0.is_tls()
+    }
+}
+
+/// Downloads and parses the feed at `url`.
+pub fn fetch_channel(url: &str) -> Result<(Channel, FetchInfo)> {
+    let fetched = fetch_channel_if_modified(url, &FetchInfo::default())?;
+    // nothing was cached, so there's nothing the server could say is unchanged
+    fetched.ok_or_else(|| Error::Invalid(format!("{url} answered 304 to an unconditional request")))
+}
+
+/// Downloads and parses the feed at `url` unless the server says it hasn't
+/// changed since the fetch described by `previous`, in which case there's
+/// nothing to return.
+pub fn fetch_channel_if_modified(
+    url: &str,
+    previous: &FetchInfo,
+) -> Result<Option<(Channel, FetchInfo)>> {
+    debug!(url, "fetching");
+    let started = Instant::now();
+    let mut request = feed_agent().get(url);
+    if let Some(etag) = &previous.etag {
+        request = request.header("If-None-Match", etag);
+    }
+    if let Some(last_modified) = &previous.last_modified {
+        request = request.header("If-Modified-Since", last_modified);
+    }
+
+    let response = request.call()?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        debug!(elapsed_ms = started.elapsed().as_millis(), "not modified");
+        return Ok(None);
+    }
+    let headers = response.headers().clone();
+    let content = response.into_body().read_to_vec()?;
+    debug!(
+        bytes = content.len(),
+        elapsed_ms = started.elapsed().as_millis(),
+        "fetched"
+    );
+    let channel = parse_channel(&content)?;
+    Ok(Some((
+        channel,
+        FetchInfo::from_response(&headers, &content),
+    )))
+}
+
+/// How much of a response [`fetch_raw`] keeps.
+pub const RAW_RESPONSE_LIMIT: u64 = 256 * 1024;
+
+/// Downloads whatever `url` answers with, as text, for showing the user why
+/// it couldn't be read as a feed. Only the first [`RAW_RESPONSE_LIMIT`] bytes
+/// are kept.
+pub fn fetch_raw(url: &str) -> Result<String> {
+    let response = feed_agent().get(url).call()?;
+    let mut content = Vec::new();
+    response
+        .into_body()
+        .into_reader()
+        .take(RAW_RESPONSE_LIMIT)
+        .read_to_end(&mut content)?;
+    Ok(String::from_utf8_lossy(&content).into_owned())
+}
+
+/// Fetches the feed at `url`, falling back to the usual feed paths of a few
+/// blog hosts. `url` is updated to the address that worked.
+pub fn discover_feed(url: &mut Url) -> Result<(Channel, FetchInfo)> {
+    // TODO: get html of website and check if there is a rss+atom link
+    let original = url.clone();
+    if let Ok(feed) = fetch_channel(url.as_str()) {
+        return Ok(feed);
+    }
+
+    let Some(domain) = url.domain() else {
+        return Err(Error::NoFeed(original));
+    };
+    match domain {
+        "tumblr.com" => url.set_path("rss"),
+        "blogspot.com" => url.set_path("feeds/posts/default"),
+        // should work for wordpress and medium sites
+        _ => url.set_path("feed"),
+    };
+    debug!(%url, "trying the usual feed path of the host");
+    fetch_channel(url.as_str()).map_err(|_| Error::NoFeed(original))
+}
+
+/// Saves an enclosure to `dir` as `<enclosure_id>.<extension>`, returning
+/// where it ended up.
+pub fn download_enclosure(enclosure_id: i32, url: &str, dir: &Path) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+
+    let extension = Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            Path::new(url.path())
+                .extension()
+                .map(|extension| extension.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "bin".to_string());
+    let path = dir.join(format!("{enclosure_id}.{extension}"));
+    // write to a temporary file first so an interrupted download is never
+    // mistaken for a finished one
+    let partial_path = path.with_extension(format!("{extension}.part"));
+
+    info!(enclosure_id, url, "downloading enclosure");
+    let response = download_agent().get(url).call()?;
+    let mut reader = response.into_body().into_reader();
+    let mut file = File::create(&partial_path)?;
+    let bytes = io::copy(&mut reader, &mut file)?;
+    fs::rename(&partial_path, &path)?;
+    info!(enclosure_id, bytes, "downloaded enclosure");
+
+    Ok(fs::canonicalize(path)?)
+}
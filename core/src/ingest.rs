@@ -0,0 +1,657 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use rss::{Channel, Item};
+use tracing::{field, info, info_span, warn};
+use url::Url;
+
+use crate::{
+    canonical,
+    favicon::{fetch_favicon, site_url, Favicon},
+    feeds::{
+        record_refresh, record_refresh_error, set_feed_icon, update_feed_metadata, FeedMetadata,
+        FetchInfo,
+    },
+    fetcher::{discover_feed, fetch_channel_if_modified},
+    models::{
+        FeedItemRecord, FeedRecord, NewEnclosureRecord, NewFeedItemRecord, NewFeedRecord,
+        StringTime,
+    },
+    parser::{parse_explicit, parse_itunes_duration},
+    repository, retention,
+    rules::apply_rules,
+    schema, tags, Error, Result,
+};
+
+/// Why an item of a feed wasn't stored.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RejectReason {
+    /// The retention policy removed it before, so it isn't brought back.
+    Purged,
+    Database(String),
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectReason::Purged => write!(f, "removed earlier by the retention policy"),
+            RejectReason::Database(error) => write!(f, "couldn't be saved: {error}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RejectedItem {
+    pub title: Option<String>,
+    pub link: Option<String>,
+    pub reason: RejectReason,
+}
+
+/// What storing the items of one fetch of a feed did.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IngestSummary {
+    pub inserted: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub rejected: Vec<RejectedItem>,
+}
+
+impl IngestSummary {
+    /// Adds the counts of another fetch, for reporting several feeds at once.
+    pub fn merge(&mut self, other: IngestSummary) {
+        self.inserted += other.inserted;
+        self.updated += other.updated;
+        self.unchanged += other.unchanged;
+        self.rejected.extend(other.rejected);
+    }
+
+    /// Counts what storing `item` did, returning the item's id if it's new.
+    pub(crate) fn add(&mut self, item: &Item, outcome: Outcome) -> Option<i32> {
+        match outcome {
+            Outcome::Inserted(item_id) => {
+                self.inserted += 1;
+                return Some(item_id);
+            }
+            Outcome::Updated => self.updated += 1,
+            Outcome::Unchanged => self.unchanged += 1,
+            Outcome::Rejected(reason) => {
+                warn!(title = ?item.title, link = ?item.link, %reason, "item skipped");
+                self.rejected.push(RejectedItem {
+                    title: item.title.clone(),
+                    link: item.link.clone(),
+                    reason,
+                });
+            }
+        }
+        None
+    }
+}
+
+impl fmt::Display for IngestSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} new, {} updated, {} unchanged",
+            self.inserted, self.updated, self.unchanged
+        )?;
+        if !self.rejected.is_empty() {
+            write!(f, ", {} skipped", self.rejected.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// What storing one item of a fetch did.
+pub(crate) enum Outcome {
+    Inserted(i32),
+    Updated,
+    Unchanged,
+    Rejected(RejectReason),
+}
+
+/// The columns a new fetch of an item may change. Read state, stars,
+/// priority and hiding belong to the user and are left alone.
+#[derive(AsChangeset)]
+#[diesel(table_name = schema::feed_items)]
+#[diesel(treat_none_as_null = true)]
+pub(crate) struct ItemChanges {
+    title: Option<String>,
+    description: Option<String>,
+    author: Option<String>,
+    pub_date: Option<String>,
+    published_at: Option<String>,
+    content: Option<String>,
+    thumbnail_url: Option<String>,
+    itunes_author: Option<String>,
+    explicit: Option<bool>,
+    episode: Option<i32>,
+    season: Option<i32>,
+    chapters_url: Option<String>,
+    transcript_url: Option<String>,
+    original_url: Option<String>,
+    guid: Option<String>,
+    update_date: StringTime,
+}
+
+impl ItemChanges {
+    pub(crate) fn new(item: &NewFeedItemRecord) -> Self {
+        ItemChanges {
+            title: item.title.clone(),
+            description: item.description.clone(),
+            author: item.author.clone(),
+            pub_date: item.pub_date.clone(),
+            published_at: item.published_at.clone(),
+            content: item.content.clone(),
+            thumbnail_url: item.thumbnail_url.clone(),
+            itunes_author: item.itunes_author.clone(),
+            explicit: item.explicit,
+            episode: item.episode,
+            season: item.season,
+            chapters_url: item.chapters_url.clone(),
+            transcript_url: item.transcript_url.clone(),
+            original_url: item.original_url.clone(),
+            guid: item.guid.clone(),
+            update_date: StringTime::from(item.update_date),
+        }
+    }
+
+    pub(crate) fn differs_from(&self, stored: &FeedItemRecord) -> bool {
+        self.title != stored.title
+            || self.description != stored.description
+            || self.author != stored.author
+            || self.pub_date != stored.pub_date
+            || self.published_at != stored.published_at
+            || self.content != stored.content
+            || self.thumbnail_url != stored.thumbnail_url
+            || self.itunes_author != stored.itunes_author
+            || self.explicit != stored.explicit
+            || self.episode != stored.episode
+            || self.season != stored.season
+            || self.chapters_url != stored.chapters_url
+            || self.transcript_url != stored.transcript_url
+            || self.original_url != stored.original_url
+            || self.guid != stored.guid
+    }
+}
+
+/// The stored copy of `item`: the one with the same guid, or else the same
+/// link or the same link as the feed published it, or for items without a
+/// link, the one with the same title and date.
+fn find_stored(
+    conn: &mut SqliteConnection,
+    item: &NewFeedItemRecord,
+) -> QueryResult<Option<FeedItemRecord>> {
+    use schema::feed_items::dsl::*;
+
+    let in_feed = || {
+        feed_items
+            .filter(channel_id.eq(item.channel_id))
+            .select(FeedItemRecord::as_select())
+            .into_boxed()
+    };
+    // items stored before guids were kept are still found the other ways
+    if let Some(item_guid) = &item.guid {
+        let stored = in_feed()
+            .filter(guid.eq(item_guid))
+            .first(conn)
+            .optional()?;
+        if stored.is_some() {
+            return Ok(stored);
+        }
+    }
+    match (&item.url, &item.original_url) {
+        (Some(link), Some(published)) => in_feed()
+            .filter(url.eq(link).or(original_url.eq(published)))
+            .first(conn)
+            .optional(),
+        (Some(link), None) => in_feed().filter(url.eq(link)).first(conn).optional(),
+        (None, _) => in_feed()
+            .filter(url.is_null())
+            .filter(title.is(item.title.clone()))
+            .filter(pub_date.is(item.pub_date.clone()))
+            .first(conn)
+            .optional(),
+    }
+}
+
+/// The enclosure of an item stored as `item_id`, if it has one.
+pub(crate) fn enclosure_record(
+    item_id: i32,
+    item: &Item,
+    now: DateTime<Utc>,
+) -> Option<NewEnclosureRecord> {
+    let enclosure = item.enclosure.as_ref()?;
+    let duration = item
+        .itunes_ext
+        .as_ref()
+        .and_then(|ext| ext.duration.as_deref())
+        .and_then(parse_itunes_duration);
+
+    Some(NewEnclosureRecord {
+        item_id,
+        url: enclosure.url.clone(),
+        mime_type: enclosure.mime_type.clone(),
+        // feeds commonly advertise a length of 0 when they don't know it
+        length: enclosure.length.parse().ok().filter(|length| *length > 0),
+        duration,
+        create_date: now,
+        update_date: now,
+    })
+}
+
+/// Adds the enclosure and the feed's categories of an item. Both are kept
+/// when they were already there.
+fn attach_extras(
+    conn: &mut SqliteConnection,
+    item_id: i32,
+    item: &Item,
+    now: DateTime<Utc>,
+) -> QueryResult<()> {
+    if let Some(enclosure) = enclosure_record(item_id, item, now) {
+        diesel::insert_into(schema::enclosures::table)
+            .values(enclosure)
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+    }
+
+    for category in &item.categories {
+        tags::tag_item(conn, item_id, &category.name, tags::FEED_SOURCE, now)?;
+    }
+    Ok(())
+}
+
+fn store_item(
+    conn: &mut SqliteConnection,
+    channel_id: i32,
+    item: &Item,
+    redirects: &HashMap<String, String>,
+    now: DateTime<Utc>,
+) -> QueryResult<Outcome> {
+    use schema::feed_items;
+
+    let mut new_item = NewFeedItemRecord::from_rss_item(channel_id, item.clone(), now);
+    let changes = ItemChanges::new(&new_item);
+
+    let mut stored = find_stored(conn, &new_item)?;
+    if let (None, Some(resolved)) = (
+        &stored,
+        new_item.url.as_ref().and_then(|link| redirects.get(link)),
+    ) {
+        new_item.url = Some(resolved.clone());
+        stored = find_stored(conn, &new_item)?;
+    }
+
+    match stored {
+        None => {
+            if let Some(link) = &new_item.url {
+                if retention::was_purged(conn, channel_id, link)? {
+                    return Ok(Outcome::Rejected(RejectReason::Purged));
+                }
+            }
+            let item_id = diesel::insert_into(feed_items::table)
+                .values(new_item)
+                .on_conflict((feed_items::url, feed_items::channel_id))
+                .do_nothing()
+                .returning(feed_items::id)
+                .get_result::<i32>(conn)
+                .optional()?;
+            let Some(item_id) = item_id else {
+                return Ok(Outcome::Unchanged);
+            };
+            attach_extras(conn, item_id, item, now)?;
+            Ok(Outcome::Inserted(item_id))
+        }
+        Some(stored) if !changes.differs_from(&stored) => Ok(Outcome::Unchanged),
+        Some(stored) => {
+            diesel::update(feed_items::table.find(stored.id))
+                .set(&changes)
+                .execute(conn)?;
+            attach_extras(conn, stored.id, item, now)?;
+            Ok(Outcome::Updated)
+        }
+    }
+}
+
+/// Stores the items of one fetch of a feed in a single transaction, adding
+/// new items and updating the ones the feed has changed, then runs the rules
+/// on the new ones. `redirects` maps the links of new items to where they
+/// lead, see [`resolve_redirects`]. An item that can't be stored is rolled
+/// back on its own and reported instead of failing the whole feed.
+pub fn ingest_items(
+    conn: &mut SqliteConnection,
+    channel_id: i32,
+    items: &[Item],
+    redirects: &HashMap<String, String>,
+    now: DateTime<Utc>,
+) -> QueryResult<IngestSummary> {
+    conn.transaction(|conn| {
+        let mut summary = IngestSummary::default();
+        let mut inserted_ids = Vec::new();
+
+        for item in items {
+            // nested transactions are savepoints
+            let outcome = conn
+                .transaction(|conn| store_item(conn, channel_id, item, redirects, now))
+                .unwrap_or_else(|error| {
+                    Outcome::Rejected(RejectReason::Database(error.to_string()))
+                });
+            inserted_ids.extend(summary.add(item, outcome));
+        }
+
+        apply_rules(conn, &inserted_ids)?;
+        info!(
+            inserted = summary.inserted,
+            updated = summary.updated,
+            unchanged = summary.unchanged,
+            rejected = summary.rejected.len(),
+            "stored items"
+        );
+        Ok(summary)
+    })
+}
+
+/// A feed that was just subscribed to or refreshed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeedUpdate {
+    pub feed_id: i32,
+    pub feed_name: String,
+    pub summary: IngestSummary,
+}
+
+/// A feed as fetched, with everything needed to store it without going back
+/// to the network.
+#[derive(Clone, Debug)]
+pub struct FetchedFeed {
+    /// The address the feed was found at.
+    pub url: Url,
+    pub channel: Channel,
+    pub fetch_info: FetchInfo,
+    /// The site's icon, when the feed didn't have one yet.
+    pub favicon: Option<Favicon>,
+    pub redirects: HashMap<String, String>,
+}
+
+/// Follows the click-tracking redirects of the items whose links aren't in
+/// `known_links` yet. Items seen before are found by the link the feed
+/// published, so each redirect is only followed once.
+pub fn resolve_redirects(items: &[Item], known_links: &HashSet<String>) -> HashMap<String, String> {
+    items
+        .iter()
+        .filter(|item| {
+            item.link
+                .as_ref()
+                .is_none_or(|link| !known_links.contains(link.trim()))
+        })
+        .filter_map(canonical::item_link)
+        .filter(|link| canonical::is_redirect(link) && !known_links.contains(link))
+        .filter_map(|link| Some((link.clone(), canonical::resolve_redirect(&link)?)))
+        .collect()
+}
+
+/// Finds the feed at `address` and everything else [`store_subscription`]
+/// needs. Only goes to the network, so it can run off the UI thread.
+pub fn fetch_subscription(address: &str) -> Result<FetchedFeed> {
+    let _span = info_span!("feed", url = address).entered();
+    info!("subscribing");
+    let fetched = fetch_subscription_in_span(address);
+    if let Err(error) = &fetched {
+        warn!(%error, "subscribing failed");
+    }
+    fetched
+}
+
+fn fetch_subscription_in_span(address: &str) -> Result<FetchedFeed> {
+    let mut url = Url::parse(address.trim())?;
+    let (channel, fetch_info) = discover_feed(&mut url)?;
+    let favicon = site_url(&channel.link, &url).and_then(|site| fetch_favicon(&site));
+    let redirects = resolve_redirects(&channel.items, &HashSet::new());
+    Ok(FetchedFeed {
+        url,
+        channel,
+        fetch_info,
+        favicon,
+        redirects,
+    })
+}
+
+/// Subscribes to a feed found by [`fetch_subscription`] and stores its items.
+pub fn store_subscription(
+    conn: &mut SqliteConnection,
+    fetched: FetchedFeed,
+    now: DateTime<Utc>,
+) -> Result<FeedUpdate> {
+    // the id is filled in once the feed is stored
+    let span = info_span!("feed", id = field::Empty, url = %fetched.url);
+    let _entered = span.enter();
+    let FetchedFeed {
+        url,
+        channel,
+        fetch_info,
+        favicon,
+        redirects,
+    } = fetched;
+    let new_feed = subscription_record(&url, &channel, favicon, now)?;
+
+    conn.transaction(|conn| {
+        let feed_id = repository::insert_feed(conn, new_feed)?;
+        span.record("id", feed_id);
+        record_refresh(conn, feed_id, fetch_info, now)?;
+        let summary = ingest_items(conn, feed_id, &channel.items, &redirects, now)?;
+        Ok(FeedUpdate {
+            feed_id,
+            feed_name: channel.title.clone(),
+            summary,
+        })
+    })
+}
+
+/// The feed a subscription to `channel`, found at `url`, stores.
+pub(crate) fn subscription_record(
+    url: &Url,
+    channel: &Channel,
+    favicon: Option<Favicon>,
+    now: DateTime<Utc>,
+) -> Result<NewFeedRecord> {
+    let site = url
+        .host_str()
+        .ok_or_else(|| Error::Invalid(format!("{url} has no host")))?
+        .to_string();
+
+    let metadata = FeedMetadata::from_channel(channel);
+    let itunes = channel.itunes_ext.clone().unwrap_or_default();
+    Ok(NewFeedRecord {
+        url: site,
+        feed_url: url.to_string(),
+        name: channel.title.clone(),
+        create_date: now,
+        update_date: now,
+        // podcasts usually ship square show artwork through itunes:image
+        artwork_url: itunes
+            .image
+            .or_else(|| channel.image.as_ref().map(|image| image.url.clone())),
+        itunes_author: itunes.author,
+        explicit: itunes.explicit.as_deref().and_then(parse_explicit),
+        description: metadata.description,
+        image_url: metadata.image_url,
+        language: metadata.language,
+        generator: metadata.generator,
+        copyright: metadata.copyright,
+        icon: favicon.as_ref().map(|favicon| favicon.data.clone()),
+        icon_mime_type: favicon.map(|favicon| favicon.mime_type),
+    })
+}
+
+/// Finds the feed at `address`, subscribes to it and stores its items.
+pub fn subscribe(
+    conn: &mut SqliteConnection,
+    address: &str,
+    now: DateTime<Utc>,
+) -> Result<FeedUpdate> {
+    store_subscription(conn, fetch_subscription(address)?, now)
+}
+
+/// What fetching a subscribed feed again needs from the database, read
+/// before going to the network.
+#[derive(Clone, Debug)]
+pub struct RefreshSource {
+    pub feed_id: i32,
+    pub feed_name: String,
+    pub feed_url: String,
+    pub(crate) previous: FetchInfo,
+    pub(crate) has_icon: bool,
+    /// The links of the items already stored, as published and as stored.
+    pub(crate) known_links: HashSet<String>,
+}
+
+impl RefreshSource {
+    pub(crate) fn new(feed: FeedRecord, known_links: HashSet<String>) -> Self {
+        RefreshSource {
+            feed_id: feed.id,
+            feed_name: feed.name,
+            feed_url: feed.feed_url,
+            previous: FetchInfo {
+                format: feed.format,
+                etag: feed.etag,
+                last_modified: feed.last_modified,
+            },
+            has_icon: feed.icon.is_some(),
+            known_links,
+        }
+    }
+}
+
+pub fn refresh_source(conn: &mut SqliteConnection, feed_id: i32) -> Result<RefreshSource> {
+    let feed = repository::load_feed(conn, feed_id)?;
+    let known_links = {
+        use schema::feed_items::dsl::*;
+
+        feed_items
+            .filter(channel_id.eq(feed_id))
+            .select((url, original_url))
+            .load::<(Option<String>, Option<String>)>(conn)?
+            .into_iter()
+            .flat_map(|(link, published)| link.into_iter().chain(published))
+            .collect()
+    };
+    Ok(RefreshSource::new(feed, known_links))
+}
+
+/// The sources of every feed that isn't archived.
+pub fn refresh_sources(conn: &mut SqliteConnection) -> Result<Vec<RefreshSource>> {
+    let feeds = repository::load_feeds(conn)?;
+    feeds
+        .into_iter()
+        .filter(|feed| !feed.archived)
+        .map(|feed| refresh_source(conn, feed.id))
+        .collect()
+}
+
+/// Fetches a subscribed feed again, unless the server says it hasn't
+/// changed. Only goes to the network, so it can run off the UI thread.
+pub fn fetch_refresh(source: &RefreshSource) -> Result<Option<FetchedFeed>> {
+    let _span = info_span!("feed", id = source.feed_id).entered();
+    info!("refreshing");
+    let Some((channel, fetch_info)) =
+        fetch_channel_if_modified(&source.feed_url, &source.previous)?
+    else {
+        return Ok(None);
+    };
+    let url = Url::parse(&source.feed_url)?;
+
+    // sites without an icon may have added one since
+    let favicon = if source.has_icon {
+        None
+    } else {
+        site_url(&channel.link, &url).and_then(|site| fetch_favicon(&site))
+    };
+    let redirects = resolve_redirects(&channel.items, &source.known_links);
+    Ok(Some(FetchedFeed {
+        url,
+        channel,
+        fetch_info,
+        favicon,
+        redirects,
+    }))
+}
+
+/// Stores what [`fetch_refresh`] got for `source`. A failed fetch is recorded
+/// on the feed before it's returned.
+pub fn store_refresh(
+    conn: &mut SqliteConnection,
+    source: &RefreshSource,
+    fetched: Result<Option<FetchedFeed>>,
+    now: DateTime<Utc>,
+) -> Result<FeedUpdate> {
+    let feed_id = source.feed_id;
+    let _span = info_span!("feed", id = feed_id).entered();
+    let fetched = match fetched {
+        Ok(Some(fetched)) => fetched,
+        Ok(None) => {
+            info!("unchanged since the last refresh");
+            record_refresh(conn, feed_id, source.previous.clone(), now)?;
+            return Ok(FeedUpdate {
+                feed_id,
+                feed_name: source.feed_name.clone(),
+                summary: IngestSummary::default(),
+            });
+        }
+        Err(error) => {
+            warn!(%error, "refresh failed");
+            record_refresh_error(conn, feed_id, &error.to_string(), now)?;
+            return Err(error);
+        }
+    };
+
+    conn.transaction(|conn| {
+        record_refresh(conn, feed_id, fetched.fetch_info, now)?;
+        update_feed_metadata(conn, feed_id, FeedMetadata::from_channel(&fetched.channel))?;
+        if let Some(favicon) = fetched.favicon {
+            set_feed_icon(conn, feed_id, favicon)?;
+        }
+        let summary = ingest_items(
+            conn,
+            feed_id,
+            &fetched.channel.items,
+            &fetched.redirects,
+            now,
+        )?;
+        Ok(FeedUpdate {
+            feed_id,
+            feed_name: source.feed_name.clone(),
+            summary,
+        })
+    })
+}
+
+/// Fetches a subscribed feed again and stores what changed. A failed fetch
+/// is recorded on the feed before it's returned.
+pub fn refresh_feed(
+    conn: &mut SqliteConnection,
+    feed_id: i32,
+    now: DateTime<Utc>,
+) -> Result<FeedUpdate> {
+    let source = refresh_source(conn, feed_id)?;
+    let fetched = fetch_refresh(&source);
+    store_refresh(conn, &source, fetched, now)
+}
+
+/// Refreshes every feed that isn't archived, one after the other. A feed
+/// that fails doesn't stop the others.
+pub fn refresh_all_feeds(
+    conn: &mut SqliteConnection,
+    now: DateTime<Utc>,
+) -> Result<Vec<(i32, Result<FeedUpdate>)>> {
+    let sources = refresh_sources(conn)?;
+    info!(feeds = sources.len(), "refreshing all feeds");
+    Ok(sources
+        .iter()
+        .map(|source| {
+            let fetched = fetch_refresh(source);
+            (source.feed_id, store_refresh(conn, source, fetched, now))
+        })
+        .collect())
+}
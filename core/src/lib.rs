@@ -0,0 +1,33 @@
+//! Everything the reader does that doesn't involve the UI: fetching and
+//! parsing feeds, storing them in SQLite and the queries behind each view.
+
+pub mod canonical;
+pub mod cluster;
+pub mod database;
+pub mod dates;
+pub mod diagnostics;
+mod error;
+pub mod favicon;
+pub mod feeds;
+pub mod fetcher;
+pub mod ingest;
+pub mod models;
+pub mod mutes;
+pub mod pagination;
+pub mod parser;
+pub mod profiles;
+pub mod repository;
+pub mod retention;
+pub mod rules;
+pub mod sanitize;
+pub mod schema;
+pub mod search;
+pub mod settings;
+pub mod storage;
+pub mod tags;
+
+pub use error::{Error, Result};
+pub use models::{
+    Article, EnclosureRecord, FeedItemRecord, FeedRecord, FeedTitleUrl, NewEnclosureRecord,
+    NewFeedItemRecord, NewFeedRecord, StringTime,
+};
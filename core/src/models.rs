@@ -0,0 +1,362 @@
+use chrono::{DateTime, Utc};
+use diesel::{
+    backend::Backend, deserialize::FromSql, expression::AsExpression, prelude::*, serialize::ToSql,
+    sql_types,
+};
+use url::Url;
+
+use crate::{dates, schema};
+
+#[derive(AsExpression, Debug, Clone)]
+#[diesel(sql_type = sql_types::Text)]
+pub struct StringTime(String);
+
+impl From<DateTime<Utc>> for StringTime {
+    fn from(val: DateTime<Utc>) -> Self {
+        Self(dates::to_stored(val))
+    }
+}
+
+impl TryFrom<StringTime> for DateTime<Utc> {
+    type Error = String;
+
+    fn try_from(value: StringTime) -> Result<Self, Self::Error> {
+        dates::parse_stored(&value.0).ok_or_else(|| format!("invalid timestamp {:?}", value.0))
+    }
+}
+
+impl<Text, DB> Queryable<Text, DB> for StringTime
+where
+    DB: Backend,
+    Text: diesel::sql_types::SingleValue,
+    String: FromSql<Text, DB>,
+{
+    type Row = String;
+
+    fn build(row: Self::Row) -> diesel::deserialize::Result<Self> {
+        Ok(StringTime(row))
+    }
+}
+
+impl<DB> ToSql<sql_types::Text, DB> for StringTime
+where
+    DB: Backend,
+    String: ToSql<sql_types::Text, DB>,
+{
+    fn to_sql<'b>(
+        &'b self,
+        out: &mut diesel::serialize::Output<'b, '_, DB>,
+    ) -> diesel::serialize::Result {
+        self.0.to_sql(out)
+    }
+}
+
+#[derive(Queryable, Selectable, Identifiable, Clone, Debug, PartialEq)]
+#[diesel(table_name = schema::feeds)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct FeedRecord {
+    pub id: i32,
+    pub url: String,
+    pub feed_url: String,
+    pub name: String,
+    #[diesel(deserialize_as = StringTime)]
+    pub create_date: DateTime<Utc>,
+    #[diesel(deserialize_as = StringTime)]
+    pub update_date: DateTime<Utc>,
+    pub artwork_url: Option<String>,
+    pub itunes_author: Option<String>,
+    pub explicit: Option<bool>,
+    pub retain_count: Option<i32>,
+    pub retain_days: Option<i32>,
+    pub archived: bool,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub language: Option<String>,
+    pub generator: Option<String>,
+    pub copyright: Option<String>,
+    pub icon: Option<Vec<u8>>,
+    pub icon_mime_type: Option<String>,
+    pub last_refresh_date: Option<String>,
+    pub last_error: Option<String>,
+    pub last_error_date: Option<String>,
+    pub format: Option<String>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl FeedRecord {
+    /// A feed as stored by `insert_feed`, before it has ever been refreshed.
+    pub(crate) fn new(id: i32, feed: NewFeedRecord) -> Self {
+        FeedRecord {
+            id,
+            url: feed.url,
+            feed_url: feed.feed_url,
+            name: feed.name,
+            create_date: feed.create_date,
+            update_date: feed.update_date,
+            artwork_url: feed.artwork_url,
+            itunes_author: feed.itunes_author,
+            explicit: feed.explicit,
+            retain_count: None,
+            retain_days: None,
+            archived: false,
+            description: feed.description,
+            image_url: feed.image_url,
+            language: feed.language,
+            generator: feed.generator,
+            copyright: feed.copyright,
+            icon: feed.icon,
+            icon_mime_type: feed.icon_mime_type,
+            last_refresh_date: None,
+            last_error: None,
+            last_error_date: None,
+            format: None,
+            etag: None,
+            last_modified: None,
+        }
+    }
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = schema::feeds)]
+pub struct NewFeedRecord {
+    pub url: String,
+    pub feed_url: String,
+    pub name: String,
+    #[diesel(serialize_as = StringTime)]
+    pub create_date: DateTime<Utc>,
+    #[diesel(serialize_as = StringTime)]
+    pub update_date: DateTime<Utc>,
+    pub artwork_url: Option<String>,
+    pub itunes_author: Option<String>,
+    pub explicit: Option<bool>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub language: Option<String>,
+    pub generator: Option<String>,
+    pub copyright: Option<String>,
+    pub icon: Option<Vec<u8>>,
+    pub icon_mime_type: Option<String>,
+}
+
+impl NewFeedRecord {
+    /// A feed without any of the optional metadata, created and updated at
+    /// `now`.
+    pub fn new(url: String, feed_url: String, name: String, now: DateTime<Utc>) -> Self {
+        NewFeedRecord {
+            url,
+            feed_url,
+            name,
+            create_date: now,
+            update_date: now,
+            artwork_url: None,
+            itunes_author: None,
+            explicit: None,
+            description: None,
+            image_url: None,
+            language: None,
+            generator: None,
+            copyright: None,
+            icon: None,
+            icon_mime_type: None,
+        }
+    }
+}
+
+#[derive(Selectable, Queryable, Clone, Debug)]
+#[diesel(table_name = schema::feeds)]
+pub struct FeedTitleUrl {
+    pub feed_url: String,
+    pub name: String,
+}
+
+#[derive(Queryable, Selectable, Identifiable, Associations, Clone, Debug, PartialEq)]
+#[diesel(belongs_to(FeedRecord, foreign_key = channel_id))]
+#[diesel(table_name = schema::feed_items)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct FeedItemRecord {
+    pub id: i32,
+    pub channel_id: i32,
+    pub title: Option<String>,
+    pub url: Option<String>,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub pub_date: Option<String>,
+    #[diesel(deserialize_as = StringTime)]
+    pub create_date: DateTime<Utc>,
+    #[diesel(deserialize_as = StringTime)]
+    pub update_date: DateTime<Utc>,
+    pub content: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub itunes_author: Option<String>,
+    pub explicit: Option<bool>,
+    pub episode: Option<i32>,
+    pub season: Option<i32>,
+    pub chapters_url: Option<String>,
+    pub transcript_url: Option<String>,
+    pub is_read: bool,
+    pub is_starred: bool,
+    pub is_hidden: bool,
+    pub priority: i32,
+    pub original_url: Option<String>,
+    /// `pub_date` as stored by [`dates::to_stored`], when it could be parsed.
+    pub published_at: Option<String>,
+    /// The RSS `guid` or Atom `id`.
+    pub guid: Option<String>,
+}
+
+impl FeedItemRecord {
+    /// An item as stored by `insert_item`: unread, unstarred, visible and
+    /// without a priority.
+    pub(crate) fn new(id: i32, item: NewFeedItemRecord) -> Self {
+        FeedItemRecord {
+            id,
+            channel_id: item.channel_id,
+            title: item.title,
+            url: item.url,
+            description: item.description,
+            author: item.author,
+            pub_date: item.pub_date,
+            create_date: item.create_date,
+            update_date: item.update_date,
+            content: item.content,
+            thumbnail_url: item.thumbnail_url,
+            itunes_author: item.itunes_author,
+            explicit: item.explicit,
+            episode: item.episode,
+            season: item.season,
+            chapters_url: item.chapters_url,
+            transcript_url: item.transcript_url,
+            is_read: false,
+            is_starred: false,
+            is_hidden: false,
+            priority: 0,
+            original_url: item.original_url,
+            published_at: item.published_at,
+            guid: item.guid,
+        }
+    }
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = schema::feed_items)]
+pub struct NewFeedItemRecord {
+    pub channel_id: i32,
+    pub title: Option<String>,
+    pub url: Option<String>,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub pub_date: Option<String>,
+    #[diesel(serialize_as = StringTime)]
+    pub create_date: DateTime<Utc>,
+    #[diesel(serialize_as = StringTime)]
+    pub update_date: DateTime<Utc>,
+    pub content: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub itunes_author: Option<String>,
+    pub explicit: Option<bool>,
+    pub episode: Option<i32>,
+    pub season: Option<i32>,
+    pub chapters_url: Option<String>,
+    pub transcript_url: Option<String>,
+    pub original_url: Option<String>,
+    pub published_at: Option<String>,
+    pub guid: Option<String>,
+}
+
+impl NewFeedItemRecord {
+    /// An item of `channel_id` with nothing but its dates, both `now`.
+    pub fn new(channel_id: i32, now: DateTime<Utc>) -> Self {
+        NewFeedItemRecord {
+            channel_id,
+            title: None,
+            url: None,
+            description: None,
+            author: None,
+            pub_date: None,
+            create_date: now,
+            update_date: now,
+            content: None,
+            thumbnail_url: None,
+            itunes_author: None,
+            explicit: None,
+            episode: None,
+            season: None,
+            chapters_url: None,
+            transcript_url: None,
+            original_url: None,
+            published_at: None,
+            guid: None,
+        }
+    }
+}
+
+#[derive(Queryable, Selectable, Identifiable, Associations, Clone, Debug, PartialEq)]
+#[diesel(belongs_to(FeedItemRecord, foreign_key = item_id))]
+#[diesel(table_name = schema::enclosures)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct EnclosureRecord {
+    pub id: i32,
+    pub item_id: i32,
+    pub url: String,
+    pub mime_type: String,
+    pub length: Option<i64>,
+    pub duration: Option<i32>,
+    pub playback_position: f64,
+    pub local_path: Option<String>,
+    #[diesel(deserialize_as = StringTime)]
+    pub create_date: DateTime<Utc>,
+    #[diesel(deserialize_as = StringTime)]
+    pub update_date: DateTime<Utc>,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = schema::enclosures)]
+pub struct NewEnclosureRecord {
+    pub item_id: i32,
+    pub url: String,
+    pub mime_type: String,
+    pub length: Option<i64>,
+    pub duration: Option<i32>,
+    #[diesel(serialize_as = StringTime)]
+    pub create_date: DateTime<Utc>,
+    #[diesel(serialize_as = StringTime)]
+    pub update_date: DateTime<Utc>,
+}
+
+impl EnclosureRecord {
+    /// An enclosure as first stored: never played and not downloaded.
+    pub(crate) fn new(id: i32, enclosure: NewEnclosureRecord) -> Self {
+        EnclosureRecord {
+            id,
+            item_id: enclosure.item_id,
+            url: enclosure.url,
+            mime_type: enclosure.mime_type,
+            length: enclosure.length,
+            duration: enclosure.duration,
+            playback_position: 0.0,
+            local_path: None,
+            create_date: enclosure.create_date,
+            update_date: enclosure.update_date,
+        }
+    }
+}
+
+/// An item together with the feed it came from, as shown in the views that
+/// mix several feeds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Article {
+    pub id: i32,
+    pub channel_id: i32,
+    pub title: Option<String>,
+    pub link: Option<String>,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub pub_date: Option<DateTime<Utc>>,
+    pub thumbnail_url: Option<String>,
+    pub is_read: bool,
+    pub is_starred: bool,
+    pub priority: i32,
+    pub channel_title: String,
+    pub channel_link: Url,
+}
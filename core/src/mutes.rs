@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use regex::{Regex, RegexBuilder};
+
+use crate::{schema, storage::Storage, Error, StringTime};
+
+#[derive(Queryable, Selectable, Identifiable, Clone, Debug, PartialEq)]
+#[diesel(table_name = schema::mute_terms)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct MuteTermRecord {
+    pub id: i32,
+    pub channel_id: Option<i32>,
+    pub term: String,
+    pub is_regex: bool,
+    #[diesel(deserialize_as = StringTime)]
+    pub create_date: DateTime<Utc>,
+    #[diesel(deserialize_as = StringTime)]
+    pub update_date: DateTime<Utc>,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = schema::mute_terms)]
+pub struct NewMuteTermRecord {
+    pub channel_id: Option<i32>,
+    pub term: String,
+    pub is_regex: bool,
+    #[diesel(serialize_as = StringTime)]
+    pub create_date: DateTime<Utc>,
+    #[diesel(serialize_as = StringTime)]
+    pub update_date: DateTime<Utc>,
+}
+
+impl NewMuteTermRecord {
+    /// A term as typed, trimmed and checked to compile.
+    pub(crate) fn new(feed_id: Option<i32>, mute_term: &str, regex: bool) -> crate::Result<Self> {
+        let mute_term = mute_term.trim();
+        if mute_term.is_empty() {
+            return Err(Error::Invalid("mute term is empty".to_string()));
+        }
+        compile_term(mute_term, regex).map_err(|error| Error::Invalid(error.to_string()))?;
+
+        let now = Utc::now();
+        Ok(NewMuteTermRecord {
+            channel_id: feed_id,
+            term: mute_term.to_string(),
+            is_regex: regex,
+            create_date: now,
+            update_date: now,
+        })
+    }
+}
+
+fn is_word_char(character: char) -> bool {
+    character.is_alphanumeric() || character == '_'
+}
+
+/// Plain terms match whole words or phrases, ignoring case. A word boundary
+/// is only required next to a letter or digit, since `\b` can't match before
+/// "#ad" or after "C++".
+fn compile_term(term: &str, is_regex: bool) -> Result<Regex, regex::Error> {
+    let pattern = if is_regex {
+        term.to_string()
+    } else {
+        let term = term.trim();
+        let boundary = |character: Option<char>| {
+            if character.is_some_and(is_word_char) {
+                r"\b"
+            } else {
+                ""
+            }
+        };
+        format!(
+            "{}{}{}",
+            boundary(term.chars().next()),
+            regex::escape(term),
+            boundary(term.chars().last())
+        )
+    };
+    RegexBuilder::new(&pattern).case_insensitive(true).build()
+}
+
+/// Every mute term compiled, for checking articles as they're displayed. The
+/// default mutes nothing.
+#[derive(Default)]
+pub struct MuteFilter {
+    terms: Vec<(MuteTermRecord, Regex)>,
+}
+
+impl MuteFilter {
+    /// Terms that no longer compile are left out.
+    pub(crate) fn new(records: Vec<MuteTermRecord>) -> Self {
+        let terms = records
+            .into_iter()
+            .filter_map(|record| {
+                let regex = compile_term(&record.term, record.is_regex).ok()?;
+                Some((record, regex))
+            })
+            .collect();
+        MuteFilter { terms }
+    }
+
+    pub fn load(storage: &mut (impl Storage + ?Sized)) -> crate::Result<Self> {
+        Ok(MuteFilter::new(storage.load_mute_terms()?))
+    }
+
+    /// The id of the first term that mutes the article, if any.
+    pub fn muted_by(
+        &self,
+        channel_id: i32,
+        title: Option<&str>,
+        description: Option<&str>,
+    ) -> Option<i32> {
+        self.terms
+            .iter()
+            .filter(|(record, _)| record.channel_id.is_none_or(|id| id == channel_id))
+            .find(|(_, regex)| {
+                title.is_some_and(|title| regex.is_match(title))
+                    || description.is_some_and(|description| regex.is_match(description))
+            })
+            .map(|(record, _)| record.id)
+    }
+
+    pub fn is_muted(
+        &self,
+        channel_id: i32,
+        title: Option<&str>,
+        description: Option<&str>,
+    ) -> bool {
+        self.muted_by(channel_id, title, description).is_some()
+    }
+}
+
+/// Filters compiled from the same terms mute the same articles.
+impl PartialEq for MuteFilter {
+    fn eq(&self, other: &Self) -> bool {
+        self.terms
+            .iter()
+            .map(|(record, _)| record)
+            .eq(other.terms.iter().map(|(record, _)| record))
+    }
+}
+
+pub fn load_mute_terms(conn: &mut SqliteConnection) -> QueryResult<Vec<MuteTermRecord>> {
+    use schema::mute_terms::dsl::*;
+
+    mute_terms
+        .order(term.asc())
+        .select(MuteTermRecord::as_select())
+        .load(conn)
+}
+
+pub fn add_mute_term(
+    conn: &mut SqliteConnection,
+    feed_id: Option<i32>,
+    mute_term: &str,
+    regex: bool,
+) -> crate::Result<()> {
+    use schema::mute_terms;
+
+    diesel::insert_into(mute_terms::table)
+        .values(NewMuteTermRecord::new(feed_id, mute_term, regex)?)
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn delete_mute_term(conn: &mut SqliteConnection, mute_term_id: i32) -> QueryResult<usize> {
+    use schema::mute_terms::dsl::*;
+
+    diesel::delete(mute_terms.filter(id.eq(mute_term_id))).execute(conn)
+}
+
+/// How many stored articles each term is hiding. An article muted by several
+/// terms counts towards the first one only.
+pub fn hidden_counts(conn: &mut SqliteConnection) -> QueryResult<HashMap<i32, usize>> {
+    let filter = MuteFilter::new(load_mute_terms(conn)?);
+    let items = {
+        use schema::feed_items::dsl::*;
+
+        feed_items
+            .filter(is_hidden.eq(false))
+            .select((channel_id, title, description))
+            .load::<(i32, Option<String>, Option<String>)>(conn)?
+    };
+
+    let mut counts = HashMap::new();
+    for (channel_id, title, description) in items {
+        if let Some(term_id) = filter.muted_by(channel_id, title.as_deref(), description.as_deref())
+        {
+            *counts.entry(term_id).or_default() += 1;
+        }
+    }
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mutes(term: &str, text: &str) -> bool {
+        compile_term(term, false).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn plain_terms_match_whole_words_only() {
+        assert!(mutes("rust", "Learning Rust in 2025"));
+        assert!(mutes("  rust ", "rust"));
+        assert!(!mutes("rust", "Trusty tools"));
+        assert!(mutes("new release", "A new release is out"));
+    }
+
+    #[test]
+    fn terms_can_start_or_end_with_symbols() {
+        assert!(mutes("C++", "Why C++ is still around"));
+        assert!(mutes("C++", "I write C++"));
+        assert!(!mutes("C++", "Objective-CC++"));
+        assert!(mutes(".NET", "Moving to .NET 9"));
+        assert!(!mutes(".NET", "Moving to .NETwork"));
+        assert!(mutes("#ad", "Great deal #ad"));
+        assert!(mutes("#ad", "#ad: a sponsored post"));
+    }
+
+    #[test]
+    fn regex_terms_are_used_as_written() {
+        let regex = compile_term(r"^sponsored\b", true).unwrap();
+        assert!(regex.is_match("Sponsored: a post"));
+        assert!(!regex.is_match("Not sponsored"));
+        assert!(compile_term("(", true).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use std::cmp::Reverse;
+
+use diesel::prelude::*;
+
+use crate::{
+    cluster::{cluster_articles, ArticleCluster},
+    repository::{article_order, to_articles},
+    schema,
+    storage::Storage,
+    FeedItemRecord, FeedTitleUrl, Result,
+};
+
+/// Articles fetched per page of All Articles and of a feed.
+pub const PAGE_SIZE: i64 = 100;
+
+/// The sort key of the last article of a page, see [`article_order`]. The next
+/// page starts right after it, so pages stay consistent while new items are
+/// added and don't get slower the further down they are, unlike `OFFSET`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArticleCursor {
+    priority: i32,
+    published_at: Option<String>,
+    id: i32,
+}
+
+/// [`article_order`] for items that are already loaded: the stored dates sort
+/// as text, and items without one go last just like NULLs do in SQLite.
+pub(crate) fn article_key(
+    priority: i32,
+    published_at: Option<&str>,
+    id: i32,
+) -> (Reverse<i32>, Reverse<Option<&str>>, Reverse<i32>) {
+    (Reverse(priority), Reverse(published_at), Reverse(id))
+}
+
+impl ArticleCursor {
+    /// Where the page after one of `count` items ending with `last` starts,
+    /// unless it wasn't full and so was the last one.
+    fn after(last: Option<&FeedItemRecord>, count: usize) -> Option<Self> {
+        last.filter(|_| count as i64 == PAGE_SIZE)
+            .map(|item| ArticleCursor {
+                priority: item.priority,
+                published_at: item.published_at.clone(),
+                id: item.id,
+            })
+    }
+
+    /// Whether `item` belongs on a page after this cursor.
+    pub(crate) fn precedes(&self, item: &FeedItemRecord) -> bool {
+        article_key(item.priority, item.published_at.as_deref(), item.id)
+            > article_key(self.priority, self.published_at.as_deref(), self.id)
+    }
+}
+
+/// The part of the All Articles view loaded so far.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ArticlePages {
+    /// The stories of every page loaded, a page at a time.
+    pub pages: Vec<Vec<ArticleCluster>>,
+    /// Where the next page starts, `None` once everything has been loaded.
+    pub next: Option<ArticleCursor>,
+}
+
+/// One page of visible items in [`article_order`], starting after `after`,
+/// of every feed or only of `feed_id`, see [`Storage::load_article_page`].
+pub fn load_article_page(
+    conn: &mut SqliteConnection,
+    feed_id: Option<i32>,
+    after: Option<&ArticleCursor>,
+    limit: i64,
+) -> QueryResult<Vec<(FeedItemRecord, Option<FeedTitleUrl>)>> {
+    use schema::feed_items::dsl::*;
+
+    let mut query = feed_items
+        .left_join(schema::feeds::table)
+        .filter(is_hidden.eq(false))
+        .order(article_order())
+        .select((
+            FeedItemRecord::as_select(),
+            Option::<FeedTitleUrl>::as_select(),
+        ))
+        .limit(limit)
+        .into_boxed();
+
+    if let Some(feed_id) = feed_id {
+        query = query.filter(channel_id.eq(feed_id));
+    }
+    if let Some(cursor) = after {
+        let same_priority = priority.eq(cursor.priority);
+        // items without a date sort last, after every dated item
+        query = match &cursor.published_at {
+            Some(date) => query.filter(
+                priority.lt(cursor.priority).or(same_priority.and(
+                    published_at
+                        .lt(date.clone())
+                        .or(published_at.is_null())
+                        .or(published_at.eq(date.clone()).and(id.lt(cursor.id))),
+                )),
+            ),
+            None => query.filter(
+                priority.lt(cursor.priority).or(same_priority
+                    .and(published_at.is_null())
+                    .and(id.lt(cursor.id))),
+            ),
+        };
+    }
+    query.load(conn)
+}
+
+impl ArticlePages {
+    /// The first page, the newest articles.
+    pub fn first(storage: &mut (impl Storage + ?Sized)) -> Result<Self> {
+        let mut pages = ArticlePages::default();
+        pages.append(storage.load_article_page(None, None, PAGE_SIZE)?);
+        Ok(pages)
+    }
+
+    /// Loads the page after the ones already shown, if there is one. Stories
+    /// are grouped within a page, so a story whose sources end up on
+    /// different pages gets a card on each.
+    pub fn load_next(&mut self, storage: &mut (impl Storage + ?Sized)) -> Result<()> {
+        let Some(cursor) = &self.next else {
+            return Ok(());
+        };
+        let items = storage.load_article_page(None, Some(cursor), PAGE_SIZE)?;
+        self.append(items);
+        Ok(())
+    }
+
+    fn append(&mut self, items: Vec<(FeedItemRecord, Option<FeedTitleUrl>)>) {
+        self.next = ArticleCursor::after(items.last().map(|(item, _)| item), items.len());
+        self.pages.push(cluster_articles(to_articles(items)));
+    }
+
+    pub fn has_more(&self) -> bool {
+        self.next.is_some()
+    }
+}
+
+/// The part of a feed's articles loaded so far.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelPages {
+    pub feed_id: i32,
+    /// The items of every page loaded, a page at a time.
+    pub pages: Vec<Vec<FeedItemRecord>>,
+    /// Where the next page starts, `None` once everything has been loaded.
+    pub next: Option<ArticleCursor>,
+}
+
+impl ChannelPages {
+    /// The first page of `feed_id`, its newest articles.
+    pub fn first(storage: &mut (impl Storage + ?Sized), feed_id: i32) -> Result<Self> {
+        let page = storage.load_channel_page(feed_id, None, PAGE_SIZE)?;
+        Ok(ChannelPages {
+            feed_id,
+            next: ArticleCursor::after(page.last(), page.len()),
+            pages: vec![page],
+        })
+    }
+
+    /// Loads the page after the ones already shown, if there is one.
+    pub fn load_next(&mut self, storage: &mut (impl Storage + ?Sized)) -> Result<()> {
+        let Some(cursor) = &self.next else {
+            return Ok(());
+        };
+        let page = storage.load_channel_page(self.feed_id, Some(cursor), PAGE_SIZE)?;
+        self.next = ArticleCursor::after(page.last(), page.len());
+        self.pages.push(page);
+        Ok(())
+    }
+
+    pub fn has_more(&self) -> bool {
+        self.next.is_some()
+    }
+}
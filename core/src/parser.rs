@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+
+use atom_syndication::{Entry, Feed, Link};
+use chrono::{DateTime, Utc};
+use rss::{
+    extension::{Extension, ExtensionMap},
+    Category, Channel, ChannelBuilder, GuidBuilder, Item, ItemBuilder,
+};
+use tracing::{debug, warn};
+
+use crate::{canonical, dates, models::NewFeedItemRecord, Result};
+
+/// Reads an RSS document, or an Atom one converted to the same shape.
+pub fn parse_channel(content: &[u8]) -> Result<Channel> {
+    match Channel::read_from(content) {
+        Ok(channel) => {
+            debug!(format = "RSS", items = channel.items.len(), "parsed");
+            Ok(channel)
+        }
+        // report why it isn't RSS when it isn't Atom either
+        Err(error) => match Feed::read_from(content) {
+            Ok(feed) => {
+                debug!(format = "Atom", items = feed.entries().len(), "parsed");
+                Ok(channel_from_atom(feed))
+            }
+            Err(_) => {
+                warn!(%error, bytes = content.len(), "not an RSS or Atom document");
+                Err(error.into())
+            }
+        },
+    }
+}
+
+/// The `alternate` link, which Atom uses for the page a feed or entry is
+/// about. A link without `rel` is an alternate one.
+fn alternate_link(links: &[Link]) -> Option<String> {
+    links
+        .iter()
+        .find(|link| link.rel() == "alternate")
+        .map(|link| link.href().to_string())
+}
+
+fn item_from_atom(entry: &Entry) -> Item {
+    ItemBuilder::default()
+        .title(Some(entry.title().value.clone()))
+        .link(alternate_link(entry.links()))
+        .description(entry.summary().map(|summary| summary.value.clone()))
+        .content(entry.content().and_then(|content| content.value.clone()))
+        .author(
+            entry
+                .authors()
+                .first()
+                .map(|author| author.name().to_string()),
+        )
+        .pub_date(Some(
+            entry.published().unwrap_or(entry.updated()).to_rfc2822(),
+        ))
+        .guid(Some(
+            GuidBuilder::default()
+                .value(entry.id().to_string())
+                .permalink(false)
+                .build(),
+        ))
+        .categories(
+            entry
+                .categories()
+                .iter()
+                .map(|category| Category::from(category.term()))
+                .collect::<Vec<_>>(),
+        )
+        .build()
+}
+
+fn channel_from_atom(feed: Feed) -> Channel {
+    ChannelBuilder::default()
+        .title(feed.title().value.clone())
+        .link(alternate_link(feed.links()).unwrap_or_default())
+        .description(
+            feed.subtitle()
+                .map(|subtitle| subtitle.value.clone())
+                .unwrap_or_default(),
+        )
+        .items(
+            feed.entries()
+                .iter()
+                .map(item_from_atom)
+                .collect::<Vec<_>>(),
+        )
+        .build()
+}
+
+/// Interprets `itunes:explicit`, which feeds write in several ways.
+pub fn parse_explicit(explicit: &str) -> Option<bool> {
+    match explicit.trim().to_ascii_lowercase().as_str() {
+        "yes" | "true" | "explicit" => Some(true),
+        "no" | "false" | "clean" => Some(false),
+        _ => None,
+    }
+}
+
+/// Gets the `url` of a Podcasting 2.0 element such as `podcast:transcript`.
+fn podcast_link(extensions: &ExtensionMap, name: &str) -> Option<String> {
+    extensions
+        .get("podcast")?
+        .get(name)?
+        .iter()
+        .find_map(|element| element.attrs.get("url").cloned())
+}
+
+impl NewFeedItemRecord {
+    pub fn from_rss_item(channel_id: i32, item: Item, now: DateTime<Utc>) -> Self {
+        let thumbnail_url = item_thumbnail(&item);
+        let chapters_url = podcast_link(&item.extensions, "chapters");
+        let transcript_url = podcast_link(&item.extensions, "transcript");
+        let url = canonical::item_link(&item);
+        let itunes = item.itunes_ext.unwrap_or_default();
+        Self {
+            channel_id,
+            title: item.title,
+            url,
+            original_url: item.link,
+            description: item.description,
+            author: item.author,
+            published_at: item
+                .pub_date
+                .as_deref()
+                .and_then(dates::parse_feed_date)
+                .map(dates::to_stored),
+            pub_date: item.pub_date,
+            create_date: now,
+            update_date: now,
+            content: item.content,
+            thumbnail_url,
+            itunes_author: itunes.author,
+            explicit: itunes.explicit.as_deref().and_then(parse_explicit),
+            episode: itunes
+                .episode
+                .and_then(|episode| episode.trim().parse().ok()),
+            season: itunes.season.and_then(|season| season.trim().parse().ok()),
+            chapters_url,
+            transcript_url,
+            guid: item.guid.map(|guid| guid.value),
+        }
+    }
+}
+
+/// Finds an image for the item, looking at `media:thumbnail` first, then
+/// any image `media:content` (including ones nested in a `media:group`), and
+/// finally an image enclosure.
+fn item_thumbnail(item: &Item) -> Option<String> {
+    fn is_image(extension: &Extension) -> bool {
+        extension
+            .attrs
+            .get("medium")
+            .is_some_and(|medium| medium == "image")
+            || extension
+                .attrs
+                .get("type")
+                .is_some_and(|mime| mime.starts_with("image/"))
+    }
+
+    fn find_in(elements: &BTreeMap<String, Vec<Extension>>) -> Option<String> {
+        let thumbnail = elements
+            .get("thumbnail")
+            .into_iter()
+            .flatten()
+            .find_map(|thumbnail| thumbnail.attrs.get("url"));
+        if let Some(url) = thumbnail {
+            return Some(url.clone());
+        }
+
+        for content in elements.get("content").into_iter().flatten() {
+            if let Some(url) = find_in(&content.children) {
+                return Some(url);
+            }
+            if is_image(content) {
+                if let Some(url) = content.attrs.get("url") {
+                    return Some(url.clone());
+                }
+            }
+        }
+
+        elements
+            .get("group")
+            .into_iter()
+            .flatten()
+            .find_map(|group| find_in(&group.children))
+    }
+
+    if let Some(url) = item.extensions.get("media").and_then(find_in) {
+        return Some(url);
+    }
+
+    item.enclosure
+        .as_ref()
+        .filter(|enclosure| enclosure.mime_type.starts_with("image/"))
+        .map(|enclosure| enclosure.url.clone())
+        .or_else(|| item.itunes_ext.as_ref()?.image.clone())
+}
+
+/// Parses `itunes:duration`, which may be plain seconds, `MM:SS` or `HH:MM:SS`.
+pub fn parse_itunes_duration(duration: &str) -> Option<i32> {
+    duration.trim().split(':').try_fold(0i32, |total, part| {
+        let part = part.trim().split('.').next()?.parse::<i32>().ok()?;
+        total.checked_mul(60)?.checked_add(part)
+    })
+}
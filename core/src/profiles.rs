@@ -0,0 +1,195 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
+};
+
+use crate::{Error, Result};
+
+const APP_DIR: &str = "rss_reader";
+const PROFILES_DIR: &str = "profiles";
+const DATABASE_FILE: &str = "feeds.db";
+const EPISODES_DIR: &str = "episodes";
+const LOGS_DIR: &str = "logs";
+const DEFAULT_PROFILE: &str = "default";
+
+/// Command line flags, `--flag value` or `--flag=value`.
+const DATABASE_FLAG: &str = "--database";
+const PROFILE_FLAG: &str = "--profile";
+/// Environment variables used when the flags aren't given.
+const DATABASE_ENV: &str = "RSS_READER_DATABASE";
+const PROFILE_ENV: &str = "RSS_READER_PROFILE";
+
+static CURRENT: OnceLock<Profile> = OnceLock::new();
+
+/// A set of subscriptions with its own database and downloads.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub database_path: PathBuf,
+    /// Where downloads and anything else belonging to the profile are kept.
+    pub data_dir: PathBuf,
+}
+
+impl Profile {
+    fn named(name: &str) -> Result<Self> {
+        check_name(name)?;
+        let data_dir = profiles_dir()?.join(name);
+        Ok(Profile {
+            name: name.to_string(),
+            database_path: data_dir.join(DATABASE_FILE),
+            data_dir,
+        })
+    }
+
+    /// A database somewhere outside the data directory, keeping its downloads
+    /// next to it.
+    fn at_path(database_path: &Path) -> Self {
+        let data_dir = database_path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        let name = database_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or(DEFAULT_PROFILE.to_string());
+        Profile {
+            name,
+            database_path: database_path.to_path_buf(),
+            data_dir,
+        }
+    }
+
+    /// Picks the database from the `--database` flag, then the
+    /// `RSS_READER_DATABASE` variable, and otherwise uses the profile named by
+    /// `--profile` or `RSS_READER_PROFILE` in the platform data directory.
+    fn from_environment() -> Result<Self> {
+        let args = env::args().collect::<Vec<_>>();
+
+        if let Some(path) = flag_value(&args, DATABASE_FLAG).or_else(|| env::var(DATABASE_ENV).ok())
+        {
+            return Ok(Profile::at_path(Path::new(&path)));
+        }
+        let name = flag_value(&args, PROFILE_FLAG)
+            .or_else(|| env::var(PROFILE_ENV).ok())
+            .unwrap_or(DEFAULT_PROFILE.to_string());
+        Profile::named(&name)
+    }
+
+    pub fn episodes_dir(&self) -> PathBuf {
+        self.data_dir.join(EPISODES_DIR)
+    }
+
+    pub fn log_dir(&self) -> PathBuf {
+        self.data_dir.join(LOGS_DIR)
+    }
+}
+
+fn flag_value(args: &[String], flag: &str) -> Option<String> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next().cloned();
+        }
+        if let Some(value) = arg
+            .strip_prefix(flag)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Profile names become directory names, so they're kept to a safe set of
+/// characters.
+fn check_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || matches!(character, '-' | '_'));
+    if !valid {
+        return Err(Error::Invalid(
+            "profile names can only use letters, numbers, '-' and '_'".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The per-user data directory: `$XDG_DATA_HOME` or `~/.local/share` on
+/// Linux, `~/Library/Application Support` on macOS and `%APPDATA%` on Windows.
+fn platform_data_dir() -> Option<PathBuf> {
+    let non_empty = |name| {
+        env::var_os(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+
+    if cfg!(target_os = "windows") {
+        non_empty("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        non_empty("HOME").map(|home| home.join("Library/Application Support"))
+    } else {
+        non_empty("XDG_DATA_HOME")
+            .or_else(|| non_empty("HOME").map(|home| home.join(".local/share")))
+    }
+}
+
+fn profiles_dir() -> Result<PathBuf> {
+    let data_dir = platform_data_dir().ok_or_else(|| {
+        Error::Invalid("couldn't find a data directory for this user".to_string())
+    })?;
+    Ok(data_dir.join(APP_DIR).join(PROFILES_DIR))
+}
+
+/// The profile this run of the app uses, worked out on first use. Its data
+/// directory is created if it doesn't exist yet.
+pub fn current() -> Result<&'static Profile> {
+    if let Some(profile) = CURRENT.get() {
+        return Ok(profile);
+    }
+    let profile = Profile::from_environment()?;
+    fs::create_dir_all(&profile.data_dir).map_err(|error| {
+        io::Error::new(
+            error.kind(),
+            format!("couldn't create {}: {error}", profile.data_dir.display()),
+        )
+    })?;
+    Ok(CURRENT.get_or_init(|| profile))
+}
+
+/// Names of the profiles in the data directory.
+pub fn list_profiles() -> io::Result<Vec<String>> {
+    let Ok(dir) = profiles_dir() else {
+        return Ok(Vec::new());
+    };
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+
+    let mut names = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            names.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Starts another instance of the app with the given profile, creating it if
+/// needed. The caller decides whether to close the current one.
+pub fn launch_profile(name: &str) -> Result<()> {
+    check_name(name)?;
+    let executable = env::current_exe()?;
+    Command::new(executable)
+        .arg(format!("{PROFILE_FLAG}={name}"))
+        .env_remove(DATABASE_ENV)
+        .spawn()?;
+    Ok(())
+}
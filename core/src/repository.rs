@@ -0,0 +1,236 @@
+use std::str::FromStr;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use url::Url;
+
+use crate::{
+    dates,
+    models::{
+        Article, EnclosureRecord, FeedItemRecord, FeedRecord, FeedTitleUrl, NewFeedItemRecord,
+        NewFeedRecord, StringTime,
+    },
+    schema, Error, Result,
+};
+
+/// Every subscribed feed, archived ones included, oldest first.
+pub fn load_feeds(conn: &mut SqliteConnection) -> QueryResult<Vec<FeedRecord>> {
+    use schema::feeds::dsl::*;
+
+    feeds
+        .order(id.asc())
+        .select(FeedRecord::as_select())
+        .load(conn)
+}
+
+pub fn load_feed(conn: &mut SqliteConnection, feed_id: i32) -> Result<FeedRecord> {
+    use schema::feeds::dsl::*;
+
+    feeds
+        .filter(id.eq(feed_id))
+        .select(FeedRecord::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or(Error::FeedNotFound(feed_id))
+}
+
+/// Subscribes to a feed, returning its id. A feed that was archived when it
+/// was deleted is subscribed to again, its starred items and all.
+pub fn insert_feed(conn: &mut SqliteConnection, feed: NewFeedRecord) -> Result<i32> {
+    use schema::feeds::dsl::*;
+
+    let address = feed.feed_url.clone();
+    let archived_id = feeds
+        .filter(feed_url.eq(&address))
+        .filter(archived.eq(true))
+        .select(id)
+        .first::<i32>(conn)
+        .optional()?;
+    if let Some(archived_id) = archived_id {
+        diesel::update(feeds.filter(id.eq(archived_id)))
+            .set((
+                archived.eq(false),
+                name.eq(&feed.name),
+                update_date.eq(StringTime::from(feed.update_date)),
+            ))
+            .execute(conn)?;
+        return Ok(archived_id);
+    }
+
+    diesel::insert_into(feeds)
+        .values(feed)
+        .returning(id)
+        .get_result(conn)
+        .map_err(|error| match error {
+            diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::UniqueViolation,
+                _,
+            ) => match Url::parse(&address) {
+                Ok(address) => Error::AlreadySubscribed(address),
+                Err(error) => error.into(),
+            },
+            error => error.into(),
+        })
+}
+
+/// Stores a single item as is, returning its id. Fetched items go through
+/// [`crate::ingest::ingest_items`] instead, which also deduplicates them.
+pub fn insert_item(conn: &mut SqliteConnection, item: NewFeedItemRecord) -> QueryResult<i32> {
+    use schema::feed_items::dsl::*;
+
+    diesel::insert_into(feed_items)
+        .values(item)
+        .returning(id)
+        .get_result(conn)
+}
+
+pub fn set_item_read(conn: &mut SqliteConnection, item_id: i32, read: bool) -> QueryResult<usize> {
+    use schema::feed_items::dsl::*;
+
+    diesel::update(feed_items.filter(id.eq(item_id)))
+        .set(is_read.eq(read))
+        .execute(conn)
+}
+
+pub fn set_item_starred(
+    conn: &mut SqliteConnection,
+    item_id: i32,
+    starred: bool,
+) -> QueryResult<usize> {
+    use schema::feed_items::dsl::*;
+
+    diesel::update(feed_items.filter(id.eq(item_id)))
+        .set(is_starred.eq(starred))
+        .execute(conn)
+}
+
+/// How many visible items of a feed haven't been read.
+pub fn unread_count(conn: &mut SqliteConnection, feed_id: i32) -> QueryResult<i64> {
+    use schema::feed_items::dsl::*;
+
+    feed_items
+        .filter(channel_id.eq(feed_id))
+        .filter(is_hidden.eq(false))
+        .filter(is_read.eq(false))
+        .count()
+        .get_result(conn)
+}
+
+/// The visible items of a feed, in the same order as All Articles.
+pub fn load_channel_items(
+    conn: &mut SqliteConnection,
+    feed_id: i32,
+) -> QueryResult<Vec<FeedItemRecord>> {
+    use schema::feed_items::dsl::*;
+
+    feed_items
+        .filter(channel_id.eq(feed_id))
+        .filter(is_hidden.eq(false))
+        .order(article_order())
+        .select(FeedItemRecord::as_select())
+        .load::<FeedItemRecord>(conn)
+}
+
+pub fn load_enclosures(
+    conn: &mut SqliteConnection,
+    feed_item_id: i32,
+) -> QueryResult<Vec<EnclosureRecord>> {
+    use schema::enclosures::dsl::*;
+
+    enclosures
+        .filter(item_id.eq(feed_item_id))
+        .select(EnclosureRecord::as_select())
+        .load::<EnclosureRecord>(conn)
+}
+
+pub fn enclosure_url(
+    conn: &mut SqliteConnection,
+    enclosure_id: i32,
+) -> QueryResult<Option<String>> {
+    use schema::enclosures::dsl::*;
+
+    enclosures
+        .filter(id.eq(enclosure_id))
+        .select(url)
+        .first::<String>(conn)
+        .optional()
+}
+
+/// Records where a downloaded enclosure was saved.
+pub fn set_enclosure_path(
+    conn: &mut SqliteConnection,
+    enclosure_id: i32,
+    path: &str,
+) -> QueryResult<usize> {
+    use schema::enclosures::dsl::*;
+
+    diesel::update(enclosures.filter(id.eq(enclosure_id)))
+        .set((
+            local_path.eq(path),
+            update_date.eq(StringTime::from(Utc::now())),
+        ))
+        .execute(conn)
+}
+
+/// Remembers how far into an episode the listener got, in seconds.
+pub fn save_playback_position(
+    conn: &mut SqliteConnection,
+    enclosure_id: i32,
+    position: f64,
+) -> QueryResult<usize> {
+    use schema::enclosures::dsl::*;
+
+    diesel::update(enclosures.filter(id.eq(enclosure_id)))
+        .set((
+            playback_position.eq(position),
+            update_date.eq(StringTime::from(Utc::now())),
+        ))
+        .execute(conn)
+}
+
+/// Highest priority, then newest first. Items without a usable date sort
+/// after dated ones.
+pub fn article_order() -> (
+    diesel::dsl::Desc<schema::feed_items::priority>,
+    diesel::dsl::Desc<schema::feed_items::published_at>,
+    diesel::dsl::Desc<schema::feed_items::id>,
+) {
+    (
+        schema::feed_items::priority.desc(),
+        schema::feed_items::published_at.desc(),
+        schema::feed_items::id.desc(),
+    )
+}
+
+/// Converts items joined with their feed into articles, keeping their order.
+pub fn to_articles(all_items: Vec<(FeedItemRecord, Option<FeedTitleUrl>)>) -> Vec<Article> {
+    let mut articles = Vec::new();
+    for (item, feed_data) in all_items {
+        let pub_date = item.published_at.as_deref().and_then(dates::parse_stored);
+
+        let Some(FeedTitleUrl { feed_url, name }) = feed_data else {
+            continue;
+        };
+        let Ok(channel_link) = Url::from_str(&feed_url) else {
+            continue;
+        };
+
+        let article = Article {
+            id: item.id,
+            channel_id: item.channel_id,
+            title: item.title,
+            link: item.url,
+            description: item.description,
+            author: item.author,
+            pub_date,
+            thumbnail_url: item.thumbnail_url,
+            is_read: item.is_read,
+            is_starred: item.is_starred,
+            priority: item.priority,
+            channel_title: name,
+            channel_link,
+        };
+        articles.push(article);
+    }
+    articles
+}
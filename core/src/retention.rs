@@ -0,0 +1,267 @@
+use std::{collections::HashSet, time::Duration};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::{prelude::*, sql_query, sql_types::BigInt};
+use tracing::info;
+
+use crate::{
+    dates, schema,
+    settings::{get_setting, set_setting},
+    tags, StringTime,
+};
+
+pub(crate) const KEEP_COUNT_SETTING: &str = "retention.keep_count";
+pub(crate) const KEEP_DAYS_SETTING: &str = "retention.keep_days";
+pub const PURGE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// How many items a feed keeps. Starred items, items the user or a rule
+/// tagged, and items with a downloaded episode, are never purged.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RetentionPolicy {
+    /// Keep only the newest N items.
+    pub keep_count: Option<i32>,
+    /// Keep only items published in the last D days.
+    pub keep_days: Option<i32>,
+}
+
+impl RetentionPolicy {
+    pub fn is_unlimited(&self) -> bool {
+        self.keep_count.is_none() && self.keep_days.is_none()
+    }
+
+    /// Fills in whatever this policy leaves unset from `fallback`.
+    pub(crate) fn or(self, fallback: RetentionPolicy) -> Self {
+        RetentionPolicy {
+            keep_count: self.keep_count.or(fallback.keep_count),
+            keep_days: self.keep_days.or(fallback.keep_days),
+        }
+    }
+}
+
+/// The policy used by feeds that don't set their own.
+pub fn load_global_policy(conn: &mut SqliteConnection) -> QueryResult<RetentionPolicy> {
+    let mut read = |name| -> QueryResult<Option<i32>> {
+        Ok(get_setting(conn, name)?.and_then(|value| value.parse().ok()))
+    };
+    Ok(RetentionPolicy {
+        keep_count: read(KEEP_COUNT_SETTING)?,
+        keep_days: read(KEEP_DAYS_SETTING)?,
+    })
+}
+
+pub fn save_global_policy(conn: &mut SqliteConnection, policy: RetentionPolicy) -> QueryResult<()> {
+    set_setting(
+        conn,
+        KEEP_COUNT_SETTING,
+        policy.keep_count.map(|count| count.to_string()).as_deref(),
+    )?;
+    set_setting(
+        conn,
+        KEEP_DAYS_SETTING,
+        policy.keep_days.map(|days| days.to_string()).as_deref(),
+    )?;
+    Ok(())
+}
+
+pub fn set_feed_policy(
+    conn: &mut SqliteConnection,
+    feed_id: i32,
+    policy: RetentionPolicy,
+) -> QueryResult<usize> {
+    use schema::feeds::dsl::*;
+
+    diesel::update(feeds.filter(id.eq(feed_id)))
+        .set((
+            retain_count.eq(policy.keep_count),
+            retain_days.eq(policy.keep_days),
+        ))
+        .execute(conn)
+}
+
+fn published(published_at: Option<&str>, create_date: &str, now: DateTime<Utc>) -> DateTime<Utc> {
+    published_at
+        .and_then(dates::parse_stored)
+        .or_else(|| dates::parse_stored(create_date))
+        .unwrap_or(now)
+}
+
+/// The ids of the items `policy` removes out of a feed's `(id, published,
+/// kept)` items. Kept items stay regardless, but still count towards the
+/// limit.
+pub(crate) fn expired_items(
+    policy: RetentionPolicy,
+    mut items: Vec<(i32, DateTime<Utc>, bool)>,
+    now: DateTime<Utc>,
+) -> HashSet<i32> {
+    // newest first, so the count limit keeps the latest items
+    items.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+
+    let cutoff = policy
+        .keep_days
+        .map(|days| now - TimeDelta::days(days.into()));
+    items
+        .into_iter()
+        .enumerate()
+        .filter(|(index, (_, published, kept))| {
+            let over_count = policy
+                .keep_count
+                .is_some_and(|count| *index >= count.max(0) as usize);
+            let too_old = cutoff.is_some_and(|cutoff| *published < cutoff);
+            !kept && (over_count || too_old)
+        })
+        .map(|(_, (item_id, ..))| item_id)
+        .collect()
+}
+
+/// Deletes the items that fall outside their feed's retention policy and
+/// returns how many were removed.
+pub fn purge(conn: &mut SqliteConnection, now: DateTime<Utc>) -> QueryResult<usize> {
+    let global = load_global_policy(conn)?;
+    let feeds = {
+        use schema::feeds::dsl::*;
+
+        feeds
+            .select((id, retain_count, retain_days))
+            .load::<(i32, Option<i32>, Option<i32>)>(conn)?
+    };
+    let tagged = {
+        use schema::item_tags::dsl::*;
+
+        // tags from the feed's own categories don't count, most feeds put
+        // one on every item
+        item_tags
+            .filter(source.eq(tags::USER_SOURCE))
+            .select(item_id)
+            .distinct()
+            .load::<i32>(conn)?
+            .into_iter()
+            .collect::<HashSet<_>>()
+    };
+    let downloaded = {
+        use schema::enclosures::dsl::*;
+
+        enclosures
+            .filter(local_path.is_not_null())
+            .select(item_id)
+            .load::<i32>(conn)?
+            .into_iter()
+            .collect::<HashSet<_>>()
+    };
+
+    let mut purged = Vec::new();
+    for (feed_id, keep_count, keep_days) in feeds {
+        let policy = RetentionPolicy {
+            keep_count,
+            keep_days,
+        }
+        .or(global);
+        if policy.is_unlimited() {
+            continue;
+        }
+
+        let items = {
+            use schema::feed_items::dsl::*;
+
+            feed_items
+                .filter(channel_id.eq(feed_id))
+                .select((id, url, published_at, create_date, is_starred))
+                .load::<(i32, Option<String>, Option<String>, String, bool)>(conn)?
+        };
+        let expired = expired_items(
+            policy,
+            items
+                .iter()
+                .map(|(item_id, _, published_on, created_on, starred)| {
+                    let kept = *starred || tagged.contains(item_id) || downloaded.contains(item_id);
+                    (
+                        *item_id,
+                        published(published_on.as_deref(), created_on, now),
+                        kept,
+                    )
+                })
+                .collect(),
+            now,
+        );
+        purged.extend(
+            items
+                .into_iter()
+                .filter(|(item_id, ..)| expired.contains(item_id))
+                .map(|(item_id, link, ..)| (feed_id, item_id, link)),
+        );
+    }
+
+    conn.transaction(|conn| {
+        use schema::{feed_items, purged_items};
+
+        for chunk in purged.chunks(500) {
+            let ids = chunk
+                .iter()
+                .map(|(_, item_id, _)| *item_id)
+                .collect::<Vec<_>>();
+            let tombstones = chunk
+                .iter()
+                .filter_map(|(feed_id, _, link)| {
+                    Some((
+                        purged_items::channel_id.eq(*feed_id),
+                        purged_items::url.eq(link.clone()?),
+                        purged_items::purge_date.eq(StringTime::from(now)),
+                    ))
+                })
+                .collect::<Vec<_>>();
+
+            diesel::insert_or_ignore_into(purged_items::table)
+                .values(tombstones)
+                .execute(conn)?;
+            diesel::delete(feed_items::table.filter(feed_items::id.eq_any(&ids))).execute(conn)?;
+        }
+        if !purged.is_empty() {
+            info!(items = purged.len(), "purged old articles");
+        }
+        Ok(purged.len())
+    })
+}
+
+/// Whether an item with this link was purged from the feed before.
+pub fn was_purged(conn: &mut SqliteConnection, feed_id: i32, link: &str) -> QueryResult<bool> {
+    use schema::purged_items::dsl::*;
+
+    diesel::select(diesel::dsl::exists(
+        purged_items
+            .filter(channel_id.eq(feed_id))
+            .filter(url.eq(link)),
+    ))
+    .get_result(conn)
+}
+
+#[derive(QueryableByName)]
+struct DatabaseSize {
+    #[diesel(sql_type = BigInt)]
+    size: i64,
+}
+
+fn database_size(conn: &mut SqliteConnection) -> QueryResult<i64> {
+    sql_query("SELECT page_count * page_size AS size FROM pragma_page_count(), pragma_page_size()")
+        .get_result::<DatabaseSize>(conn)
+        .map(|row| row.size)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CompactReport {
+    pub rows_purged: usize,
+    pub bytes_reclaimed: i64,
+}
+
+/// Purges old items and then rebuilds the database file to give the freed
+/// space back to the file system.
+pub fn compact(conn: &mut SqliteConnection, now: DateTime<Utc>) -> QueryResult<CompactReport> {
+    let size_before = database_size(conn)?;
+    let rows_purged = purge(conn, now)?;
+    sql_query("INSERT INTO feed_items_fts(feed_items_fts) VALUES('optimize')").execute(conn)?;
+    sql_query("VACUUM").execute(conn)?;
+    let size_after = database_size(conn)?;
+
+    Ok(CompactReport {
+        rows_purged,
+        bytes_reclaimed: size_before - size_after,
+    })
+}
@@ -0,0 +1,405 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use regex::{Regex, RegexBuilder};
+
+use crate::{schema, tags, FeedItemRecord, StringTime};
+
+#[derive(Queryable, Selectable, Identifiable, Clone, Debug, PartialEq)]
+#[diesel(table_name = schema::rules)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct RuleRecord {
+    pub id: i32,
+    pub name: String,
+    pub enabled: bool,
+    pub channel_id: Option<i32>,
+    pub title_pattern: Option<String>,
+    pub description_pattern: Option<String>,
+    pub author: Option<String>,
+    pub tag: Option<String>,
+    pub action: String,
+    pub action_value: Option<String>,
+    #[diesel(deserialize_as = StringTime)]
+    pub create_date: DateTime<Utc>,
+    #[diesel(deserialize_as = StringTime)]
+    pub update_date: DateTime<Utc>,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = schema::rules)]
+pub struct NewRuleRecord {
+    pub name: String,
+    pub enabled: bool,
+    pub channel_id: Option<i32>,
+    pub title_pattern: Option<String>,
+    pub description_pattern: Option<String>,
+    pub author: Option<String>,
+    pub tag: Option<String>,
+    pub action: String,
+    pub action_value: Option<String>,
+    #[diesel(serialize_as = StringTime)]
+    pub create_date: DateTime<Utc>,
+    #[diesel(serialize_as = StringTime)]
+    pub update_date: DateTime<Utc>,
+}
+
+impl RuleRecord {
+    /// A rule as stored by [`save_rule`].
+    pub(crate) fn new(id: i32, rule: NewRuleRecord) -> Self {
+        RuleRecord {
+            id,
+            name: rule.name,
+            enabled: rule.enabled,
+            channel_id: rule.channel_id,
+            title_pattern: rule.title_pattern,
+            description_pattern: rule.description_pattern,
+            author: rule.author,
+            tag: rule.tag,
+            action: rule.action,
+            action_value: rule.action_value,
+            create_date: rule.create_date,
+            update_date: rule.update_date,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RuleAction {
+    MarkRead,
+    Star,
+    Tag(String),
+    Hide,
+    Priority(i32),
+}
+
+impl RuleAction {
+    pub fn from_columns(action: &str, value: Option<&str>) -> Option<Self> {
+        match action {
+            "mark_read" => Some(RuleAction::MarkRead),
+            "star" => Some(RuleAction::Star),
+            "tag" => Some(RuleAction::Tag(value?.trim().to_string())),
+            "hide" => Some(RuleAction::Hide),
+            "priority" => Some(RuleAction::Priority(value?.trim().parse().ok()?)),
+            _ => None,
+        }
+    }
+
+    fn apply(&self, conn: &mut SqliteConnection, item_id: i32) -> QueryResult<()> {
+        use schema::feed_items::dsl::*;
+
+        let item = feed_items.filter(id.eq(item_id));
+        match self {
+            RuleAction::MarkRead => {
+                diesel::update(item).set(is_read.eq(true)).execute(conn)?;
+            }
+            RuleAction::Star => {
+                diesel::update(item)
+                    .set(is_starred.eq(true))
+                    .execute(conn)?;
+            }
+            RuleAction::Tag(tag) => {
+                tags::tag_item(conn, item_id, tag, tags::USER_SOURCE, Utc::now())?;
+            }
+            RuleAction::Hide => {
+                diesel::update(item).set(is_hidden.eq(true)).execute(conn)?;
+            }
+            RuleAction::Priority(value) => {
+                diesel::update(item).set(priority.eq(value)).execute(conn)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum RuleError {
+    InvalidPattern(regex::Error),
+    InvalidAction(String),
+}
+
+impl std::fmt::Display for RuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleError::InvalidPattern(error) => write!(f, "invalid pattern: {error}"),
+            RuleError::InvalidAction(action) => write!(f, "invalid action: {action}"),
+        }
+    }
+}
+
+/// A rule with its patterns compiled, ready to be matched against items.
+pub struct CompiledRule {
+    pub record: RuleRecord,
+    title: Option<Regex>,
+    description: Option<Regex>,
+    action: RuleAction,
+}
+
+fn compile_pattern(pattern: &Option<String>) -> Result<Option<Regex>, RuleError> {
+    pattern
+        .as_deref()
+        .filter(|pattern| !pattern.is_empty())
+        .map(|pattern| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(RuleError::InvalidPattern)
+        })
+        .transpose()
+}
+
+impl NewRuleRecord {
+    /// Checks the patterns and action before the rule is saved.
+    pub fn validate(&self) -> Result<(), RuleError> {
+        compile_pattern(&self.title_pattern)?;
+        compile_pattern(&self.description_pattern)?;
+        RuleAction::from_columns(&self.action, self.action_value.as_deref())
+            .ok_or_else(|| RuleError::InvalidAction(self.action.clone()))?;
+        Ok(())
+    }
+}
+
+impl CompiledRule {
+    pub fn new(record: RuleRecord) -> Result<Self, RuleError> {
+        let action = RuleAction::from_columns(&record.action, record.action_value.as_deref())
+            .ok_or_else(|| RuleError::InvalidAction(record.action.clone()))?;
+        Ok(CompiledRule {
+            title: compile_pattern(&record.title_pattern)?,
+            description: compile_pattern(&record.description_pattern)?,
+            action,
+            record,
+        })
+    }
+
+    pub(crate) fn action(&self) -> &RuleAction {
+        &self.action
+    }
+
+    /// Every condition that is set has to match. `item_tags` are the names of
+    /// the tags on the item.
+    pub fn matches(&self, item: &FeedItemRecord, item_tags: &[String]) -> bool {
+        let record = &self.record;
+        if record
+            .channel_id
+            .is_some_and(|channel_id| channel_id != item.channel_id)
+        {
+            return false;
+        }
+        if let Some(title) = &self.title {
+            if !item
+                .title
+                .as_deref()
+                .is_some_and(|text| title.is_match(text))
+            {
+                return false;
+            }
+        }
+        if let Some(description) = &self.description {
+            let text = item.description.as_deref().or(item.content.as_deref());
+            if !text.is_some_and(|text| description.is_match(text)) {
+                return false;
+            }
+        }
+        if let Some(author) = record.author.as_deref().filter(|author| !author.is_empty()) {
+            let author = author.to_lowercase();
+            if !item
+                .author
+                .as_deref()
+                .is_some_and(|item_author| item_author.to_lowercase().contains(&author))
+            {
+                return false;
+            }
+        }
+        if let Some(tag) = record.tag.as_deref().filter(|tag| !tag.is_empty()) {
+            if !item_tags
+                .iter()
+                .any(|item_tag| item_tag.eq_ignore_ascii_case(tag))
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Tag names keyed by item, for the given items or for every item when
+/// `item_ids` is `None`.
+fn load_tag_names(
+    conn: &mut SqliteConnection,
+    item_ids: Option<&[i32]>,
+) -> QueryResult<HashMap<i32, Vec<String>>> {
+    use schema::{item_tags, tags};
+
+    let mut query = item_tags::table
+        .inner_join(tags::table)
+        .select((item_tags::item_id, tags::name))
+        .into_boxed();
+    if let Some(item_ids) = item_ids {
+        query = query.filter(item_tags::item_id.eq_any(item_ids));
+    }
+    let rows = query.load::<(i32, String)>(conn)?;
+
+    let mut names: HashMap<i32, Vec<String>> = HashMap::new();
+    for (item_id, name) in rows {
+        names.entry(item_id).or_default().push(name);
+    }
+    Ok(names)
+}
+
+fn load_enabled_rules(conn: &mut SqliteConnection) -> QueryResult<Vec<CompiledRule>> {
+    use schema::rules::dsl::*;
+
+    let records = rules
+        .filter(enabled.eq(true))
+        .order(id.asc())
+        .select(RuleRecord::as_select())
+        .load::<RuleRecord>(conn)?;
+
+    // rules are validated when saved, anything that still fails is skipped
+    // rather than stopping every other rule
+    Ok(records
+        .into_iter()
+        .filter_map(|record| CompiledRule::new(record).ok())
+        .collect())
+}
+
+fn apply(
+    conn: &mut SqliteConnection,
+    compiled: &[CompiledRule],
+    items: &[FeedItemRecord],
+    tag_names: &HashMap<i32, Vec<String>>,
+) -> QueryResult<usize> {
+    conn.transaction(|conn| {
+        let mut applied = 0;
+        for item in items {
+            let item_tags = tag_names
+                .get(&item.id)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            for rule in compiled {
+                if rule.matches(item, item_tags) {
+                    rule.action.apply(conn, item.id)?;
+                    applied += 1;
+                }
+            }
+        }
+        Ok(applied)
+    })
+}
+
+/// Runs every enabled rule against newly inserted items, returning how many
+/// actions were applied.
+pub fn apply_rules(conn: &mut SqliteConnection, item_ids: &[i32]) -> QueryResult<usize> {
+    if item_ids.is_empty() {
+        return Ok(0);
+    }
+    let compiled = load_enabled_rules(conn)?;
+    if compiled.is_empty() {
+        return Ok(0);
+    }
+
+    let items = {
+        use schema::feed_items::dsl::*;
+
+        feed_items
+            .filter(id.eq_any(item_ids))
+            .select(FeedItemRecord::as_select())
+            .load::<FeedItemRecord>(conn)?
+    };
+    let tag_names = load_tag_names(conn, Some(item_ids))?;
+    apply(conn, &compiled, &items, &tag_names)
+}
+
+fn load_all_items(conn: &mut SqliteConnection) -> QueryResult<Vec<FeedItemRecord>> {
+    use schema::feed_items::dsl::*;
+
+    feed_items
+        .select(FeedItemRecord::as_select())
+        .load::<FeedItemRecord>(conn)
+}
+
+/// The existing items a rule would act on, without changing anything.
+pub fn dry_run(
+    conn: &mut SqliteConnection,
+    rule: &CompiledRule,
+) -> QueryResult<Vec<FeedItemRecord>> {
+    let items = load_all_items(conn)?;
+    let tag_names = load_tag_names(conn, None)?;
+
+    Ok(items
+        .into_iter()
+        .filter(|item| {
+            let item_tags = tag_names
+                .get(&item.id)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            rule.matches(item, item_tags)
+        })
+        .collect())
+}
+
+/// Applies a single rule to everything already stored.
+pub fn apply_retroactively(conn: &mut SqliteConnection, rule: &CompiledRule) -> QueryResult<usize> {
+    let items = load_all_items(conn)?;
+    let tag_names = load_tag_names(conn, None)?;
+    apply(conn, std::slice::from_ref(rule), &items, &tag_names)
+}
+
+pub fn load_rules(conn: &mut SqliteConnection) -> QueryResult<Vec<RuleRecord>> {
+    use schema::rules::dsl::*;
+
+    rules
+        .order(id.asc())
+        .select(RuleRecord::as_select())
+        .load::<RuleRecord>(conn)
+}
+
+pub fn save_rule(
+    conn: &mut SqliteConnection,
+    rule_id: Option<i32>,
+    rule: NewRuleRecord,
+) -> QueryResult<i32> {
+    use schema::rules::dsl::*;
+
+    match rule_id {
+        Some(rule_id) => {
+            diesel::update(rules.filter(id.eq(rule_id)))
+                .set((
+                    name.eq(rule.name),
+                    enabled.eq(rule.enabled),
+                    channel_id.eq(rule.channel_id),
+                    title_pattern.eq(rule.title_pattern),
+                    description_pattern.eq(rule.description_pattern),
+                    author.eq(rule.author),
+                    tag.eq(rule.tag),
+                    action.eq(rule.action),
+                    action_value.eq(rule.action_value),
+                    update_date.eq(StringTime::from(rule.update_date)),
+                ))
+                .execute(conn)?;
+            Ok(rule_id)
+        }
+        None => diesel::insert_into(rules)
+            .values(rule)
+            .returning(id)
+            .get_result(conn),
+    }
+}
+
+pub fn set_rule_enabled(
+    conn: &mut SqliteConnection,
+    rule_id: i32,
+    is_enabled: bool,
+) -> QueryResult<usize> {
+    use schema::rules::dsl::*;
+
+    diesel::update(rules.filter(id.eq(rule_id)))
+        .set(enabled.eq(is_enabled))
+        .execute(conn)
+}
+
+pub fn delete_rule(conn: &mut SqliteConnection, rule_id: i32) -> QueryResult<usize> {
+    use schema::rules::dsl::*;
+
+    diesel::delete(rules.filter(id.eq(rule_id))).execute(conn)
+}
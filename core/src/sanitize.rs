@@ -0,0 +1,8 @@
+//! Cleaning up the HTML that feeds send before it's shown.
+
+/// Keeps the formatting, links and images of a feed's HTML and removes
+/// everything that could run in the app: scripts, styles, frames, event
+/// handler attributes and `javascript:` links.
+pub fn clean_html(html: &str) -> String {
+    ammonia::clean(html)
+}
@@ -0,0 +1,393 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::{
+    prelude::*,
+    query_builder::{BoxedSqlQuery, SqlQuery},
+    sql_query,
+    sql_types::{BigInt, Bool, Integer, Nullable, Text},
+    sqlite::Sqlite,
+};
+
+use crate::{dates, schema, FeedItemRecord, StringTime};
+
+// FTS5 wraps matches in these, they can't appear in feed text
+const HIGHLIGHT_START: char = '\u{1}';
+const HIGHLIGHT_END: char = '\u{2}';
+pub const RESULT_LIMIT: i32 = 500;
+
+/// A search box query split into the full-text part and the filters.
+///
+/// Everything that isn't a filter is handed to FTS5, so phrase queries
+/// (`"async rust"`), boolean operators and prefix queries (`sched*`) all work.
+/// The supported filters are `feed:`, `author:`, `is:unread`/`is:read` and
+/// `before:`/`after:` taking a `YYYY-MM-DD` date.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchQuery {
+    pub text: String,
+    pub feed: Option<String>,
+    pub author: Option<String>,
+    pub read: Option<bool>,
+    pub before: Option<DateTime<Utc>>,
+    pub after: Option<DateTime<Utc>>,
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> Self {
+        let mut query = SearchQuery::default();
+        let mut text = Vec::new();
+
+        for token in tokenize(input) {
+            let Some((key, value)) = token.split_once(':') else {
+                text.push(token);
+                continue;
+            };
+            let value = value.trim_matches('"').to_string();
+
+            match key.to_ascii_lowercase().as_str() {
+                "feed" => query.feed = Some(value),
+                "author" => query.author = Some(value),
+                "is" if value.eq_ignore_ascii_case("unread") => query.read = Some(false),
+                "is" if value.eq_ignore_ascii_case("read") => query.read = Some(true),
+                "before" => query.before = parse_date(&value),
+                "after" => query.after = parse_date(&value),
+                // column filters like `title:rust` are FTS5 syntax
+                _ => text.push(token),
+            }
+        }
+
+        query.text = text.join(" ");
+        query
+    }
+}
+
+impl SearchQuery {
+    /// Whether an article matches without FTS5, which is how
+    /// [`crate::storage::MemoryStorage`] searches: every term of the text has
+    /// to appear in its title, description, author or content, ignoring case.
+    /// Phrases are matched as written, operators aren't understood.
+    pub(crate) fn matches(&self, item: &FeedItemRecord, feed_name: &str) -> bool {
+        let contains = |text: Option<&str>, part: &str| {
+            text.is_some_and(|text| text.to_lowercase().contains(&part.to_lowercase()))
+        };
+        let fields = [
+            item.title.as_deref(),
+            item.description.as_deref(),
+            item.author.as_deref(),
+            item.content.as_deref(),
+        ];
+        let published = item.published_at.as_deref();
+
+        tokenize(&self.text).iter().all(|token| {
+            let term = token.trim_matches('"').trim_end_matches('*');
+            fields.iter().any(|field| contains(*field, term))
+        }) && self
+            .feed
+            .as_deref()
+            .is_none_or(|feed| contains(Some(feed_name), feed))
+            && self
+                .author
+                .as_deref()
+                .is_none_or(|author| contains(item.author.as_deref(), author))
+            && self.read.is_none_or(|read| item.is_read == read)
+            && self.before.is_none_or(|before| {
+                published.is_some_and(|published| published < dates::to_stored(before).as_str())
+            })
+            && self.after.is_none_or(|after| {
+                published.is_some_and(|published| published >= dates::to_stored(after).as_str())
+            })
+    }
+}
+
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()?
+        .and_hms_opt(0, 0, 0)
+        .map(|date| date.and_utc())
+}
+
+/// Splits on whitespace, keeping quoted phrases together with their quotes.
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for character in input.chars() {
+        match character {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(character);
+            }
+            character if character.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            character => current.push(character),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+/// Turns every term into a quoted string so punctuation can't be read as
+/// FTS5 syntax. Used when the query as typed doesn't parse.
+fn quote_terms(text: &str) -> String {
+    tokenize(text)
+        .iter()
+        .map(|token| format!("\"{}\"", token.trim_matches('"').replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(QueryableByName, Clone, Debug, PartialEq)]
+pub struct SearchResult {
+    #[diesel(sql_type = Integer)]
+    pub id: i32,
+    #[diesel(sql_type = Integer)]
+    pub channel_id: i32,
+    #[diesel(sql_type = Text)]
+    pub feed_name: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub title: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub url: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub pub_date: Option<String>,
+    #[diesel(sql_type = Bool)]
+    pub is_read: bool,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub snippet: Option<String>,
+}
+
+impl SearchResult {
+    /// The snippet split into plain and highlighted runs, with markup removed.
+    pub fn snippet_segments(&self) -> Vec<(String, bool)> {
+        let Some(snippet) = &self.snippet else {
+            return Vec::new();
+        };
+
+        let mut segments = Vec::new();
+        let mut highlighted = false;
+        for part in snippet.split([HIGHLIGHT_START, HIGHLIGHT_END]) {
+            let text = strip_tags(part);
+            if !text.is_empty() {
+                segments.push((text, highlighted));
+            }
+            highlighted = !highlighted;
+        }
+        segments
+    }
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for character in html.chars() {
+        match character {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            character if !in_tag => text.push(character),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Runs `run` with the query text as an FTS5 expression, and if FTS5 can't
+/// parse it, again with every term quoted.
+fn with_fallback<T>(
+    query: &SearchQuery,
+    mut run: impl FnMut(&str) -> QueryResult<T>,
+) -> QueryResult<T> {
+    match run(&query.text) {
+        Err(diesel::result::Error::DatabaseError(..)) if !query.text.is_empty() => {
+            run(&quote_terms(&query.text))
+        }
+        result => result,
+    }
+}
+
+pub fn search(conn: &mut SqliteConnection, query: &SearchQuery) -> QueryResult<Vec<SearchResult>> {
+    with_fallback(query, |match_expression| {
+        run_search(conn, query, match_expression)
+    })
+}
+
+const RESULT_COLUMNS: &str = "feed_items.id, feed_items.channel_id, feeds.name AS feed_name, \
+     feed_items.title, feed_items.url, feed_items.pub_date, feed_items.is_read";
+
+/// Selects `columns` of the visible articles matching the query, without any
+/// order or limit.
+fn matching(
+    query: &SearchQuery,
+    match_expression: &str,
+    columns: &str,
+) -> BoxedSqlQuery<'static, Sqlite, SqlQuery> {
+    let mut statement = if match_expression.is_empty() {
+        sql_query(format!(
+            "SELECT {columns} \
+             FROM feed_items \
+             JOIN feeds ON feeds.id = feed_items.channel_id \
+             WHERE feed_items.is_hidden = 0"
+        ))
+        .into_boxed::<Sqlite>()
+    } else {
+        sql_query(format!(
+            "SELECT {columns} \
+             FROM feed_items_fts \
+             JOIN feed_items ON feed_items.id = feed_items_fts.rowid \
+             JOIN feeds ON feeds.id = feed_items.channel_id \
+             WHERE feed_items.is_hidden = 0 AND feed_items_fts MATCH "
+        ))
+        .into_boxed::<Sqlite>()
+        .sql("?")
+        .bind::<Text, _>(match_expression.to_string())
+    };
+
+    if let Some(feed) = &query.feed {
+        statement = statement
+            .sql(" AND feeds.name LIKE '%' || ? || '%'")
+            .bind::<Text, _>(feed.clone());
+    }
+    if let Some(author) = &query.author {
+        statement = statement
+            .sql(" AND feed_items.author LIKE '%' || ? || '%'")
+            .bind::<Text, _>(author.clone());
+    }
+    if let Some(read) = query.read {
+        statement = statement
+            .sql(" AND feed_items.is_read = ?")
+            .bind::<Bool, _>(read);
+    }
+    if let Some(before) = query.before {
+        statement = statement
+            .sql(" AND feed_items.published_at < ?")
+            .bind::<Text, _>(dates::to_stored(before));
+    }
+    if let Some(after) = query.after {
+        statement = statement
+            .sql(" AND feed_items.published_at >= ?")
+            .bind::<Text, _>(dates::to_stored(after));
+    }
+    statement
+}
+
+fn run_search(
+    conn: &mut SqliteConnection,
+    query: &SearchQuery,
+    match_expression: &str,
+) -> QueryResult<Vec<SearchResult>> {
+    let statement = if match_expression.is_empty() {
+        matching(
+            query,
+            match_expression,
+            &format!("{RESULT_COLUMNS}, NULL AS snippet"),
+        )
+        .sql(" ORDER BY feed_items.published_at DESC, feed_items.id DESC")
+    } else {
+        let columns = format!(
+            "{RESULT_COLUMNS}, \
+             snippet(feed_items_fts, -1, '{HIGHLIGHT_START}', '{HIGHLIGHT_END}', '…', 24) AS snippet"
+        );
+        matching(query, match_expression, &columns).sql(" ORDER BY rank")
+    };
+    statement
+        .sql(" LIMIT ?")
+        .bind::<Integer, _>(RESULT_LIMIT)
+        .load::<SearchResult>(conn)
+}
+
+#[derive(QueryableByName)]
+struct Count {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+/// How many articles match the query, without the limit [`search`] has.
+pub fn count(conn: &mut SqliteConnection, query: &SearchQuery) -> QueryResult<i64> {
+    with_fallback(query, |match_expression| {
+        matching(query, match_expression, "COUNT(*) AS count")
+            .get_result::<Count>(conn)
+            .map(|counted| counted.count)
+    })
+}
+
+pub struct SearchResults {
+    pub query: String,
+    pub results: Vec<SearchResult>,
+}
+
+#[derive(Queryable, Selectable, Identifiable, Clone, Debug, PartialEq)]
+#[diesel(table_name = schema::saved_searches)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct SavedSearchRecord {
+    pub id: i32,
+    pub name: String,
+    pub query: String,
+    #[diesel(deserialize_as = StringTime)]
+    pub create_date: DateTime<Utc>,
+    #[diesel(deserialize_as = StringTime)]
+    pub update_date: DateTime<Utc>,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = schema::saved_searches)]
+pub struct NewSavedSearchRecord {
+    pub name: String,
+    pub query: String,
+    #[diesel(serialize_as = StringTime)]
+    pub create_date: DateTime<Utc>,
+    #[diesel(serialize_as = StringTime)]
+    pub update_date: DateTime<Utc>,
+}
+
+/// A saved search opened from the sidebar.
+pub struct SavedSearchFeed {
+    pub saved_search_id: i32,
+    pub name: String,
+    pub search: SearchResults,
+}
+
+pub fn save_search(conn: &mut SqliteConnection, name: &str, query: &str) -> QueryResult<i32> {
+    use schema::saved_searches;
+
+    let now = Utc::now();
+    diesel::insert_into(saved_searches::table)
+        .values(NewSavedSearchRecord {
+            name: name.trim().to_string(),
+            query: query.trim().to_string(),
+            create_date: now,
+            update_date: now,
+        })
+        .returning(saved_searches::id)
+        .get_result(conn)
+}
+
+pub fn delete_saved_search(
+    conn: &mut SqliteConnection,
+    saved_search_id: i32,
+) -> QueryResult<usize> {
+    use schema::saved_searches::dsl::*;
+
+    diesel::delete(saved_searches.filter(id.eq(saved_search_id))).execute(conn)
+}
+
+pub fn load_saved_searches(conn: &mut SqliteConnection) -> QueryResult<Vec<SavedSearchRecord>> {
+    use schema::saved_searches::dsl::*;
+
+    saved_searches
+        .order(name.asc())
+        .select(SavedSearchRecord::as_select())
+        .load(conn)
+}
+
+/// How many unread articles a saved query currently matches.
+pub fn count_unread(conn: &mut SqliteConnection, query: &str) -> QueryResult<i64> {
+    let query = SearchQuery {
+        read: Some(false),
+        ..SearchQuery::parse(query)
+    };
+    count(conn, &query)
+}
@@ -0,0 +1,29 @@
+use diesel::prelude::*;
+
+use crate::schema;
+
+pub fn get_setting(conn: &mut SqliteConnection, name: &str) -> QueryResult<Option<String>> {
+    use schema::settings::dsl::*;
+
+    settings
+        .filter(key.eq(name))
+        .select(value)
+        .first(conn)
+        .optional()
+}
+
+/// Stores a setting, or removes it when `setting` is `None`.
+pub fn set_setting(
+    conn: &mut SqliteConnection,
+    name: &str,
+    setting: Option<&str>,
+) -> QueryResult<usize> {
+    use schema::settings::dsl::*;
+
+    match setting {
+        Some(setting) => diesel::replace_into(settings)
+            .values((key.eq(name), value.eq(setting)))
+            .execute(conn),
+        None => diesel::delete(settings.filter(key.eq(name))).execute(conn),
+    }
+}
@@ -0,0 +1,1033 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet},
+};
+
+use chrono::{DateTime, Utc};
+use rss::Item;
+use url::Url;
+
+use super::Storage;
+use crate::{
+    dates,
+    feeds::{FeedMetadata, FeedStats, FetchInfo},
+    ingest::{
+        self, FeedUpdate, FetchedFeed, IngestSummary, ItemChanges, Outcome, RefreshSource,
+        RejectReason,
+    },
+    mutes::{MuteFilter, MuteTermRecord, NewMuteTermRecord},
+    pagination::{article_key, ArticleCursor},
+    repository::to_articles,
+    retention::{self, CompactReport, RetentionPolicy, KEEP_COUNT_SETTING, KEEP_DAYS_SETTING},
+    rules::{CompiledRule, NewRuleRecord, RuleAction, RuleRecord},
+    search::{SavedSearchRecord, SearchQuery, SearchResult, RESULT_LIMIT},
+    tags::{self, ItemTag, TagFeed, TagRecord},
+    EnclosureRecord, Error, FeedItemRecord, FeedRecord, FeedTitleUrl, NewFeedItemRecord,
+    NewFeedRecord, Result,
+};
+
+#[derive(Clone, Debug)]
+struct ItemTagRow {
+    item_id: i32,
+    tag_id: i32,
+    source: String,
+}
+
+/// Keeps everything in memory and forgets it when dropped. Behaves like the
+/// SQLite backend as far as the [`Storage`] methods go, ids included, except
+/// that searching doesn't understand FTS5 syntax or rank matches, see
+/// [`SearchQuery`].
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+    feeds: Vec<FeedRecord>,
+    items: Vec<FeedItemRecord>,
+    enclosures: Vec<EnclosureRecord>,
+    tags: Vec<TagRecord>,
+    item_tags: Vec<ItemTagRow>,
+    mute_terms: Vec<MuteTermRecord>,
+    saved_searches: Vec<SavedSearchRecord>,
+    rules: Vec<RuleRecord>,
+    /// The links of purged items by feed, so refreshing doesn't bring them
+    /// back.
+    purged: HashSet<(i32, String)>,
+    settings: BTreeMap<String, String>,
+    /// The last id given out for each table. Like `AUTOINCREMENT`, ids of
+    /// deleted rows aren't used again.
+    last_ids: HashMap<&'static str, i32>,
+}
+
+/// Sorts like [`crate::repository::article_order`].
+fn sort_articles(items: &mut [FeedItemRecord]) {
+    items.sort_by(|a, b| {
+        article_key(a.priority, a.published_at.as_deref(), a.id).cmp(&article_key(
+            b.priority,
+            b.published_at.as_deref(),
+            b.id,
+        ))
+    });
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        MemoryStorage::default()
+    }
+
+    fn next_id(&mut self, table: &'static str) -> i32 {
+        let last_id = self.last_ids.entry(table).or_default();
+        *last_id += 1;
+        *last_id
+    }
+
+    fn feed_mut(&mut self, feed_id: i32) -> Option<&mut FeedRecord> {
+        self.feeds.iter_mut().find(|feed| feed.id == feed_id)
+    }
+
+    fn item_mut(&mut self, item_id: i32) -> Option<&mut FeedItemRecord> {
+        self.items.iter_mut().find(|item| item.id == item_id)
+    }
+
+    fn enclosure_mut(&mut self, enclosure_id: i32) -> Option<&mut EnclosureRecord> {
+        self.enclosures
+            .iter_mut()
+            .find(|enclosure| enclosure.id == enclosure_id)
+    }
+
+    fn feed_title_url(&self, feed_id: i32) -> Option<FeedTitleUrl> {
+        self.feeds
+            .iter()
+            .find(|feed| feed.id == feed_id)
+            .map(|feed| FeedTitleUrl {
+                feed_url: feed.feed_url.clone(),
+                name: feed.name.clone(),
+            })
+    }
+
+    /// Removes items along with their enclosures and tags, like
+    /// `ON DELETE CASCADE` does.
+    fn remove_items(&mut self, item_ids: &HashSet<i32>) {
+        self.items.retain(|item| !item_ids.contains(&item.id));
+        self.enclosures
+            .retain(|enclosure| !item_ids.contains(&enclosure.item_id));
+        self.item_tags
+            .retain(|row| !item_ids.contains(&row.item_id));
+    }
+
+    fn tag_names(&self, item_id: i32) -> Vec<String> {
+        self.item_tags
+            .iter()
+            .filter(|row| row.item_id == item_id)
+            .filter_map(|row| self.tags.iter().find(|tag| tag.id == row.tag_id))
+            .map(|tag| tag.name.clone())
+            .collect()
+    }
+
+    /// See [`crate::tags::tag_item`], tag names ignore ASCII case like
+    /// `COLLATE NOCASE` does.
+    fn tag_item_at(&mut self, item_id: i32, name: &str, source: &str, now: DateTime<Utc>) {
+        let name = name.trim();
+        if name.is_empty() {
+            return;
+        }
+
+        let tag_id = match self
+            .tags
+            .iter()
+            .find(|tag| tag.name.eq_ignore_ascii_case(name))
+        {
+            Some(tag) => tag.id,
+            None => {
+                let id = self.next_id("tags");
+                self.tags.push(TagRecord {
+                    id,
+                    name: name.to_string(),
+                    create_date: now,
+                    update_date: now,
+                });
+                id
+            }
+        };
+        if !self
+            .item_tags
+            .iter()
+            .any(|row| row.item_id == item_id && row.tag_id == tag_id)
+        {
+            self.item_tags.push(ItemTagRow {
+                item_id,
+                tag_id,
+                source: source.to_string(),
+            });
+        }
+    }
+
+    /// Removes the tags no item carries any more.
+    fn remove_unused_tags(&mut self) {
+        let used = self
+            .item_tags
+            .iter()
+            .map(|row| row.tag_id)
+            .collect::<HashSet<_>>();
+        self.tags.retain(|tag| used.contains(&tag.id));
+    }
+
+    /// The visible articles matching `query`, with the name of their feed.
+    fn matching(&self, query: &SearchQuery) -> Vec<(&FeedItemRecord, &str)> {
+        self.items
+            .iter()
+            .filter(|item| !item.is_hidden)
+            .filter_map(|item| {
+                let feed = self.feeds.iter().find(|feed| feed.id == item.channel_id)?;
+                query
+                    .matches(item, &feed.name)
+                    .then_some((item, feed.name.as_str()))
+            })
+            .collect()
+    }
+
+    fn enabled_rules(&self) -> Vec<CompiledRule> {
+        // rules are validated when saved, anything that still fails is skipped
+        // like the SQLite backend does
+        self.rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| CompiledRule::new(rule.clone()).ok())
+            .collect()
+    }
+
+    /// Runs `rules` on `items`, returning how many actions were applied.
+    fn apply_rules(&mut self, rules: &[CompiledRule], items: Vec<FeedItemRecord>) -> usize {
+        let mut applied = 0;
+        for item in items {
+            let item_tags = self.tag_names(item.id);
+            for rule in rules {
+                if !rule.matches(&item, &item_tags) {
+                    continue;
+                }
+                match rule.action() {
+                    RuleAction::Tag(name) => {
+                        self.tag_item_at(item.id, name, tags::USER_SOURCE, Utc::now());
+                    }
+                    action => {
+                        if let Some(stored) = self.item_mut(item.id) {
+                            match action {
+                                RuleAction::MarkRead => stored.is_read = true,
+                                RuleAction::Star => stored.is_starred = true,
+                                RuleAction::Hide => stored.is_hidden = true,
+                                RuleAction::Priority(value) => stored.priority = *value,
+                                RuleAction::Tag(_) => {}
+                            }
+                        }
+                    }
+                }
+                applied += 1;
+            }
+        }
+        applied
+    }
+
+    fn global_policy(&self) -> RetentionPolicy {
+        let read = |name| self.settings.get(name).and_then(|value| value.parse().ok());
+        RetentionPolicy {
+            keep_count: read(KEEP_COUNT_SETTING),
+            keep_days: read(KEEP_DAYS_SETTING),
+        }
+    }
+
+    /// See [`crate::retention::purge`].
+    fn purge(&mut self, now: DateTime<Utc>) -> usize {
+        let global = self.global_policy();
+        // tags from the feed's own categories don't count
+        let tagged = self
+            .item_tags
+            .iter()
+            .filter(|row| row.source == tags::USER_SOURCE)
+            .map(|row| row.item_id)
+            .collect::<HashSet<_>>();
+        let downloaded = self
+            .enclosures
+            .iter()
+            .filter(|enclosure| enclosure.local_path.is_some())
+            .map(|enclosure| enclosure.item_id)
+            .collect::<HashSet<_>>();
+
+        let mut expired = HashSet::new();
+        for feed in &self.feeds {
+            let policy = RetentionPolicy {
+                keep_count: feed.retain_count,
+                keep_days: feed.retain_days,
+            }
+            .or(global);
+            if policy.is_unlimited() {
+                continue;
+            }
+
+            let items = self
+                .items
+                .iter()
+                .filter(|item| item.channel_id == feed.id)
+                .map(|item| {
+                    let published = item
+                        .published_at
+                        .as_deref()
+                        .and_then(dates::parse_stored)
+                        .unwrap_or(item.create_date);
+                    let kept = item.is_starred
+                        || tagged.contains(&item.id)
+                        || downloaded.contains(&item.id);
+                    (item.id, published, kept)
+                })
+                .collect();
+            expired.extend(retention::expired_items(policy, items, now));
+        }
+
+        for item in self.items.iter().filter(|item| expired.contains(&item.id)) {
+            if let Some(link) = &item.url {
+                self.purged.insert((item.channel_id, link.clone()));
+            }
+        }
+        self.remove_items(&expired);
+        expired.len()
+    }
+
+    fn record_refresh(&mut self, feed_id: i32, info: FetchInfo, now: DateTime<Utc>) {
+        if let Some(feed) = self.feed_mut(feed_id) {
+            feed.last_refresh_date = Some(dates::to_stored(now));
+            feed.last_error = None;
+            feed.last_error_date = None;
+            feed.format = info.format;
+            feed.etag = info.etag;
+            feed.last_modified = info.last_modified;
+        }
+    }
+
+    /// The index of the item of `feed_id` that `found` picks, if any.
+    fn position_in_feed(
+        &self,
+        feed_id: i32,
+        found: impl Fn(&FeedItemRecord) -> bool,
+    ) -> Option<usize> {
+        self.items
+            .iter()
+            .position(|stored| stored.channel_id == feed_id && found(stored))
+    }
+
+    /// The stored copy of `item`, found the same ways the SQLite backend
+    /// finds it.
+    fn find_stored(&self, item: &NewFeedItemRecord) -> Option<usize> {
+        if let Some(guid) = &item.guid {
+            let stored =
+                self.position_in_feed(item.channel_id, |stored| stored.guid.as_ref() == Some(guid));
+            if stored.is_some() {
+                return stored;
+            }
+        }
+        match (&item.url, &item.original_url) {
+            (Some(link), Some(published)) => self.position_in_feed(item.channel_id, |stored| {
+                stored.url.as_ref() == Some(link) || stored.original_url.as_ref() == Some(published)
+            }),
+            (Some(link), None) => {
+                self.position_in_feed(item.channel_id, |stored| stored.url.as_ref() == Some(link))
+            }
+            (None, _) => self.position_in_feed(item.channel_id, |stored| {
+                stored.url.is_none()
+                    && stored.title == item.title
+                    && stored.pub_date == item.pub_date
+            }),
+        }
+    }
+
+    /// Adds the enclosure and the feed's categories of an item, keeping the
+    /// ones that were already there.
+    fn attach_extras(&mut self, item_id: i32, item: &Item, now: DateTime<Utc>) {
+        if let Some(enclosure) = ingest::enclosure_record(item_id, item, now) {
+            let stored = self
+                .enclosures
+                .iter()
+                .any(|stored| stored.item_id == item_id && stored.url == enclosure.url);
+            if !stored {
+                let id = self.next_id("enclosures");
+                self.enclosures.push(EnclosureRecord::new(id, enclosure));
+            }
+        }
+        for category in &item.categories {
+            self.tag_item_at(item_id, &category.name, tags::FEED_SOURCE, now);
+        }
+    }
+
+    fn store_item(
+        &mut self,
+        channel_id: i32,
+        item: &Item,
+        redirects: &HashMap<String, String>,
+        now: DateTime<Utc>,
+    ) -> Outcome {
+        let mut new_item = NewFeedItemRecord::from_rss_item(channel_id, item.clone(), now);
+        let changes = ItemChanges::new(&new_item);
+
+        let mut stored = self.find_stored(&new_item);
+        if let (None, Some(resolved)) = (
+            stored,
+            new_item.url.as_ref().and_then(|link| redirects.get(link)),
+        ) {
+            new_item.url = Some(resolved.clone());
+            stored = self.find_stored(&new_item);
+        }
+
+        match stored {
+            None => {
+                if let Some(link) = &new_item.url {
+                    if self.purged.contains(&(channel_id, link.clone())) {
+                        return Outcome::Rejected(RejectReason::Purged);
+                    }
+                }
+                let item_id = match self.insert_item(new_item) {
+                    Ok(item_id) => item_id,
+                    Err(error) => {
+                        return Outcome::Rejected(RejectReason::Database(error.to_string()))
+                    }
+                };
+                self.attach_extras(item_id, item, now);
+                Outcome::Inserted(item_id)
+            }
+            Some(index) if !changes.differs_from(&self.items[index]) => Outcome::Unchanged,
+            Some(index) => {
+                // read state, stars, priority and hiding belong to the user
+                let stored = &mut self.items[index];
+                stored.title = new_item.title;
+                stored.description = new_item.description;
+                stored.author = new_item.author;
+                stored.pub_date = new_item.pub_date;
+                stored.published_at = new_item.published_at;
+                stored.content = new_item.content;
+                stored.thumbnail_url = new_item.thumbnail_url;
+                stored.itunes_author = new_item.itunes_author;
+                stored.explicit = new_item.explicit;
+                stored.episode = new_item.episode;
+                stored.season = new_item.season;
+                stored.chapters_url = new_item.chapters_url;
+                stored.transcript_url = new_item.transcript_url;
+                stored.original_url = new_item.original_url;
+                stored.guid = new_item.guid;
+                stored.update_date = new_item.update_date;
+                let item_id = stored.id;
+                self.attach_extras(item_id, item, now);
+                Outcome::Updated
+            }
+        }
+    }
+
+    /// See [`crate::ingest::ingest_items`].
+    fn store_items(
+        &mut self,
+        channel_id: i32,
+        items: &[Item],
+        redirects: &HashMap<String, String>,
+        now: DateTime<Utc>,
+    ) -> IngestSummary {
+        let mut summary = IngestSummary::default();
+        let mut inserted_ids = HashSet::new();
+        for item in items {
+            let outcome = self.store_item(channel_id, item, redirects, now);
+            inserted_ids.extend(summary.add(item, outcome));
+        }
+
+        let rules = self.enabled_rules();
+        let inserted = self
+            .items
+            .iter()
+            .filter(|item| inserted_ids.contains(&item.id))
+            .cloned()
+            .collect();
+        self.apply_rules(&rules, inserted);
+        summary
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load_feeds(&mut self) -> Result<Vec<FeedRecord>> {
+        Ok(self.feeds.clone())
+    }
+
+    fn load_feed(&mut self, feed_id: i32) -> Result<FeedRecord> {
+        self.feeds
+            .iter()
+            .find(|feed| feed.id == feed_id)
+            .cloned()
+            .ok_or(Error::FeedNotFound(feed_id))
+    }
+
+    fn insert_feed(&mut self, feed: NewFeedRecord) -> Result<i32> {
+        if let Some(stored) = self
+            .feeds
+            .iter_mut()
+            .find(|stored| stored.feed_url == feed.feed_url)
+        {
+            if !stored.archived {
+                return Err(Error::AlreadySubscribed(Url::parse(&feed.feed_url)?));
+            }
+            stored.archived = false;
+            stored.name = feed.name;
+            stored.update_date = feed.update_date;
+            return Ok(stored.id);
+        }
+
+        let id = self.next_id("feeds");
+        self.feeds.push(FeedRecord::new(id, feed));
+        Ok(id)
+    }
+
+    fn rename_feed(&mut self, feed_id: i32, name: &str) -> Result<()> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(Error::Invalid("feed name is empty".to_string()));
+        }
+        if let Some(feed) = self.feed_mut(feed_id) {
+            feed.name = name.to_string();
+            feed.update_date = Utc::now();
+        }
+        Ok(())
+    }
+
+    fn change_feed_url(&mut self, feed_id: i32, feed_url: &Url) -> Result<()> {
+        let site = feed_url
+            .host_str()
+            .ok_or_else(|| Error::Invalid(format!("{feed_url} has no host")))?;
+        if self
+            .feeds
+            .iter()
+            .any(|feed| feed.id != feed_id && feed.feed_url == feed_url.as_str())
+        {
+            return Err(Error::AlreadySubscribed(feed_url.clone()));
+        }
+        if let Some(feed) = self.feed_mut(feed_id) {
+            feed.url = site.to_string();
+            feed.feed_url = feed_url.to_string();
+            feed.update_date = Utc::now();
+        }
+        Ok(())
+    }
+
+    fn delete_feed(&mut self, feed_id: i32, keep_starred: bool) -> Result<()> {
+        let doomed = self
+            .items
+            .iter()
+            .filter(|item| item.channel_id == feed_id && !(keep_starred && item.is_starred))
+            .map(|item| item.id)
+            .collect();
+        self.remove_items(&doomed);
+        self.remove_unused_tags();
+        let of_feed = |channel_id: Option<i32>| channel_id == Some(feed_id);
+        self.rules.retain(|rule| !of_feed(rule.channel_id));
+        self.mute_terms.retain(|term| !of_feed(term.channel_id));
+        self.purged.retain(|(channel_id, _)| *channel_id != feed_id);
+
+        if self.items.iter().any(|item| item.channel_id == feed_id) {
+            if let Some(feed) = self.feed_mut(feed_id) {
+                feed.archived = true;
+            }
+        } else {
+            self.feeds.retain(|feed| feed.id != feed_id);
+        }
+        Ok(())
+    }
+
+    fn load_feed_stats(&mut self, feed_id: i32) -> Result<FeedStats> {
+        let items = self
+            .items
+            .iter()
+            .filter(|item| item.channel_id == feed_id)
+            .collect::<Vec<_>>();
+        let dated = items
+            .iter()
+            .filter_map(|item| item.published_at.as_deref())
+            .collect::<Vec<_>>();
+
+        let oldest = dated
+            .iter()
+            .min()
+            .and_then(|date| dates::parse_stored(date));
+        let newest = dated
+            .iter()
+            .max()
+            .and_then(|date| dates::parse_stored(date));
+        let average_interval = match (oldest, newest) {
+            (Some(oldest), Some(newest)) if dated.len() > 1 => {
+                Some((newest - oldest) / (dated.len() as i32 - 1))
+            }
+            _ => None,
+        };
+
+        Ok(FeedStats {
+            total: items.len() as i64,
+            unread: items.iter().filter(|item| !item.is_read).count() as i64,
+            oldest,
+            newest,
+            average_interval,
+        })
+    }
+
+    fn load_channel_items(&mut self, feed_id: i32) -> Result<Vec<FeedItemRecord>> {
+        let mut items = self
+            .items
+            .iter()
+            .filter(|item| item.channel_id == feed_id && !item.is_hidden)
+            .cloned()
+            .collect::<Vec<_>>();
+        sort_articles(&mut items);
+        Ok(items)
+    }
+
+    fn load_article_page(
+        &mut self,
+        feed_id: Option<i32>,
+        after: Option<&ArticleCursor>,
+        limit: i64,
+    ) -> Result<Vec<(FeedItemRecord, Option<FeedTitleUrl>)>> {
+        let mut items = self
+            .items
+            .iter()
+            .filter(|item| !item.is_hidden)
+            .filter(|item| feed_id.is_none_or(|feed_id| item.channel_id == feed_id))
+            .filter(|item| after.is_none_or(|cursor| cursor.precedes(item)))
+            .cloned()
+            .collect::<Vec<_>>();
+        sort_articles(&mut items);
+        Ok(items
+            .into_iter()
+            .take(limit.max(0) as usize)
+            .map(|item| {
+                let feed = self.feed_title_url(item.channel_id);
+                (item, feed)
+            })
+            .collect())
+    }
+
+    fn insert_item(&mut self, item: NewFeedItemRecord) -> Result<i32> {
+        if !self.feeds.iter().any(|feed| feed.id == item.channel_id) {
+            return Err(Error::FeedNotFound(item.channel_id));
+        }
+
+        let id = self.next_id("feed_items");
+        self.items.push(FeedItemRecord::new(id, item));
+        Ok(id)
+    }
+
+    fn set_item_read(&mut self, item_id: i32, read: bool) -> Result<()> {
+        if let Some(item) = self.item_mut(item_id) {
+            item.is_read = read;
+        }
+        Ok(())
+    }
+
+    fn set_item_starred(&mut self, item_id: i32, starred: bool) -> Result<()> {
+        if let Some(item) = self.item_mut(item_id) {
+            item.is_starred = starred;
+        }
+        Ok(())
+    }
+
+    fn unread_count(&mut self, feed_id: i32) -> Result<i64> {
+        let unread = self
+            .items
+            .iter()
+            .filter(|item| item.channel_id == feed_id && !item.is_hidden && !item.is_read)
+            .count();
+        Ok(unread as i64)
+    }
+
+    fn load_enclosures(&mut self, item_id: i32) -> Result<Vec<EnclosureRecord>> {
+        Ok(self
+            .enclosures
+            .iter()
+            .filter(|enclosure| enclosure.item_id == item_id)
+            .cloned()
+            .collect())
+    }
+
+    fn enclosure_url(&mut self, enclosure_id: i32) -> Result<Option<String>> {
+        Ok(self
+            .enclosures
+            .iter()
+            .find(|enclosure| enclosure.id == enclosure_id)
+            .map(|enclosure| enclosure.url.clone()))
+    }
+
+    fn set_enclosure_path(&mut self, enclosure_id: i32, path: &str) -> Result<()> {
+        if let Some(enclosure) = self.enclosure_mut(enclosure_id) {
+            enclosure.local_path = Some(path.to_string());
+            enclosure.update_date = Utc::now();
+        }
+        Ok(())
+    }
+
+    fn save_playback_position(&mut self, enclosure_id: i32, position: f64) -> Result<()> {
+        if let Some(enclosure) = self.enclosure_mut(enclosure_id) {
+            enclosure.playback_position = position;
+            enclosure.update_date = Utc::now();
+        }
+        Ok(())
+    }
+
+    fn load_tags(&mut self) -> Result<Vec<(TagRecord, i64)>> {
+        let mut tags = self
+            .tags
+            .iter()
+            .map(|tag| {
+                let count = self
+                    .item_tags
+                    .iter()
+                    .filter(|row| row.tag_id == tag.id)
+                    .count();
+                (tag.clone(), count as i64)
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect::<Vec<_>>();
+        tags.sort_by_key(|(tag, _)| tag.name.to_ascii_lowercase());
+        Ok(tags)
+    }
+
+    fn load_item_tags(&mut self, item_id: i32) -> Result<Vec<ItemTag>> {
+        let mut item_tags = self
+            .item_tags
+            .iter()
+            .filter(|row| row.item_id == item_id)
+            .filter_map(|row| {
+                let tag = self.tags.iter().find(|tag| tag.id == row.tag_id)?;
+                Some(ItemTag {
+                    tag_id: tag.id,
+                    name: tag.name.clone(),
+                    source: row.source.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+        item_tags.sort_by_key(|tag| tag.name.to_ascii_lowercase());
+        Ok(item_tags)
+    }
+
+    fn tag_item(&mut self, item_id: i32, name: &str, source: &str) -> Result<()> {
+        self.tag_item_at(item_id, name, source, Utc::now());
+        Ok(())
+    }
+
+    fn untag_item(&mut self, item_id: i32, tag_id: i32) -> Result<()> {
+        self.item_tags
+            .retain(|row| row.item_id != item_id || row.tag_id != tag_id);
+        if !self.item_tags.iter().any(|row| row.tag_id == tag_id) {
+            self.tags.retain(|tag| tag.id != tag_id);
+        }
+        Ok(())
+    }
+
+    fn load_tag_feed(&mut self, tag_id: i32) -> Result<TagFeed> {
+        let name = self
+            .tags
+            .iter()
+            .find(|tag| tag.id == tag_id)
+            .map(|tag| tag.name.clone())
+            .ok_or(diesel::result::Error::NotFound)?;
+
+        let tagged = self
+            .item_tags
+            .iter()
+            .filter(|row| row.tag_id == tag_id)
+            .map(|row| row.item_id)
+            .collect::<HashSet<_>>();
+        let mut items = self
+            .items
+            .iter()
+            .filter(|item| tagged.contains(&item.id))
+            .cloned()
+            .collect::<Vec<_>>();
+        sort_articles(&mut items);
+        let articles = items
+            .into_iter()
+            .map(|item| {
+                let feed = self.feed_title_url(item.channel_id);
+                (item, feed)
+            })
+            .collect();
+
+        Ok(TagFeed {
+            tag_id,
+            name,
+            articles: to_articles(articles),
+        })
+    }
+
+    fn load_mute_terms(&mut self) -> Result<Vec<MuteTermRecord>> {
+        let mut terms = self.mute_terms.clone();
+        terms.sort_by(|a, b| a.term.cmp(&b.term));
+        Ok(terms)
+    }
+
+    fn add_mute_term(&mut self, feed_id: Option<i32>, term: &str, is_regex: bool) -> Result<()> {
+        let term = NewMuteTermRecord::new(feed_id, term, is_regex)?;
+        let id = self.next_id("mute_terms");
+        self.mute_terms.push(MuteTermRecord {
+            id,
+            channel_id: term.channel_id,
+            term: term.term,
+            is_regex: term.is_regex,
+            create_date: term.create_date,
+            update_date: term.update_date,
+        });
+        Ok(())
+    }
+
+    fn delete_mute_term(&mut self, mute_term_id: i32) -> Result<()> {
+        self.mute_terms.retain(|term| term.id != mute_term_id);
+        Ok(())
+    }
+
+    fn hidden_counts(&mut self) -> Result<HashMap<i32, usize>> {
+        let filter = MuteFilter::new(self.load_mute_terms()?);
+        let mut counts = HashMap::new();
+        for item in self.items.iter().filter(|item| !item.is_hidden) {
+            let muted_by = filter.muted_by(
+                item.channel_id,
+                item.title.as_deref(),
+                item.description.as_deref(),
+            );
+            if let Some(term_id) = muted_by {
+                *counts.entry(term_id).or_default() += 1;
+            }
+        }
+        Ok(counts)
+    }
+
+    fn search(&mut self, query: &SearchQuery) -> Result<Vec<SearchResult>> {
+        let mut results = self.matching(query);
+        results.sort_by_key(|(item, _)| (Reverse(item.published_at.clone()), Reverse(item.id)));
+        Ok(results
+            .into_iter()
+            .take(RESULT_LIMIT as usize)
+            .map(|(item, feed_name)| SearchResult {
+                id: item.id,
+                channel_id: item.channel_id,
+                feed_name: feed_name.to_string(),
+                title: item.title.clone(),
+                url: item.url.clone(),
+                pub_date: item.pub_date.clone(),
+                is_read: item.is_read,
+                snippet: None,
+            })
+            .collect())
+    }
+
+    fn count_unread(&mut self, query: &str) -> Result<i64> {
+        let query = SearchQuery {
+            read: Some(false),
+            ..SearchQuery::parse(query)
+        };
+        Ok(self.matching(&query).len() as i64)
+    }
+
+    fn load_saved_searches(&mut self) -> Result<Vec<SavedSearchRecord>> {
+        let mut saved_searches = self.saved_searches.clone();
+        saved_searches.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(saved_searches)
+    }
+
+    fn save_search(&mut self, name: &str, query: &str) -> Result<i32> {
+        let id = self.next_id("saved_searches");
+        let now = Utc::now();
+        self.saved_searches.push(SavedSearchRecord {
+            id,
+            name: name.trim().to_string(),
+            query: query.trim().to_string(),
+            create_date: now,
+            update_date: now,
+        });
+        Ok(id)
+    }
+
+    fn delete_saved_search(&mut self, saved_search_id: i32) -> Result<()> {
+        self.saved_searches
+            .retain(|saved_search| saved_search.id != saved_search_id);
+        Ok(())
+    }
+
+    fn load_rules(&mut self) -> Result<Vec<RuleRecord>> {
+        Ok(self.rules.clone())
+    }
+
+    fn save_rule(&mut self, rule_id: Option<i32>, rule: NewRuleRecord) -> Result<i32> {
+        let Some(rule_id) = rule_id else {
+            let id = self.next_id("rules");
+            self.rules.push(RuleRecord::new(id, rule));
+            return Ok(id);
+        };
+        if let Some(stored) = self.rules.iter_mut().find(|stored| stored.id == rule_id) {
+            *stored = RuleRecord {
+                create_date: stored.create_date,
+                ..RuleRecord::new(rule_id, rule)
+            };
+        }
+        Ok(rule_id)
+    }
+
+    fn set_rule_enabled(&mut self, rule_id: i32, enabled: bool) -> Result<()> {
+        if let Some(rule) = self.rules.iter_mut().find(|rule| rule.id == rule_id) {
+            rule.enabled = enabled;
+        }
+        Ok(())
+    }
+
+    fn delete_rule(&mut self, rule_id: i32) -> Result<()> {
+        self.rules.retain(|rule| rule.id != rule_id);
+        Ok(())
+    }
+
+    fn dry_run(&mut self, rule: &CompiledRule) -> Result<Vec<FeedItemRecord>> {
+        Ok(self
+            .items
+            .iter()
+            .filter(|item| rule.matches(item, &self.tag_names(item.id)))
+            .cloned()
+            .collect())
+    }
+
+    fn apply_retroactively(&mut self, rule: &CompiledRule) -> Result<usize> {
+        let items = self.items.clone();
+        Ok(self.apply_rules(std::slice::from_ref(rule), items))
+    }
+
+    fn load_global_policy(&mut self) -> Result<RetentionPolicy> {
+        Ok(self.global_policy())
+    }
+
+    fn save_global_policy(&mut self, policy: RetentionPolicy) -> Result<()> {
+        let keep_count = policy.keep_count.map(|count| count.to_string());
+        let keep_days = policy.keep_days.map(|days| days.to_string());
+        self.set_setting(KEEP_COUNT_SETTING, keep_count.as_deref())?;
+        self.set_setting(KEEP_DAYS_SETTING, keep_days.as_deref())
+    }
+
+    fn set_feed_policy(&mut self, feed_id: i32, policy: RetentionPolicy) -> Result<()> {
+        if let Some(feed) = self.feed_mut(feed_id) {
+            feed.retain_count = policy.keep_count;
+            feed.retain_days = policy.keep_days;
+        }
+        Ok(())
+    }
+
+    fn compact(&mut self, now: DateTime<Utc>) -> Result<CompactReport> {
+        // there's no file to give space back to
+        Ok(CompactReport {
+            rows_purged: self.purge(now),
+            bytes_reclaimed: 0,
+        })
+    }
+
+    fn refresh_source(&mut self, feed_id: i32) -> Result<RefreshSource> {
+        let feed = self.load_feed(feed_id)?;
+        let known_links = self
+            .items
+            .iter()
+            .filter(|item| item.channel_id == feed_id)
+            .flat_map(|item| item.url.iter().chain(&item.original_url).cloned())
+            .collect();
+        Ok(RefreshSource::new(feed, known_links))
+    }
+
+    fn refresh_sources(&mut self) -> Result<Vec<RefreshSource>> {
+        let feed_ids = self
+            .feeds
+            .iter()
+            .filter(|feed| !feed.archived)
+            .map(|feed| feed.id)
+            .collect::<Vec<_>>();
+        feed_ids
+            .into_iter()
+            .map(|feed_id| self.refresh_source(feed_id))
+            .collect()
+    }
+
+    fn store_subscription(
+        &mut self,
+        fetched: FetchedFeed,
+        now: DateTime<Utc>,
+    ) -> Result<FeedUpdate> {
+        let FetchedFeed {
+            url,
+            channel,
+            fetch_info,
+            favicon,
+            redirects,
+        } = fetched;
+        let feed_id =
+            self.insert_feed(ingest::subscription_record(&url, &channel, favicon, now)?)?;
+        self.record_refresh(feed_id, fetch_info, now);
+        let summary = self.store_items(feed_id, &channel.items, &redirects, now);
+        Ok(FeedUpdate {
+            feed_id,
+            feed_name: channel.title,
+            summary,
+        })
+    }
+
+    fn store_refresh(
+        &mut self,
+        source: &RefreshSource,
+        fetched: Result<Option<FetchedFeed>>,
+        now: DateTime<Utc>,
+    ) -> Result<FeedUpdate> {
+        let feed_id = source.feed_id;
+        let fetched = match fetched {
+            Ok(Some(fetched)) => fetched,
+            Ok(None) => {
+                self.record_refresh(feed_id, source.previous.clone(), now);
+                return Ok(FeedUpdate {
+                    feed_id,
+                    feed_name: source.feed_name.clone(),
+                    summary: IngestSummary::default(),
+                });
+            }
+            Err(error) => {
+                if let Some(feed) = self.feed_mut(feed_id) {
+                    feed.last_error = Some(error.to_string());
+                    feed.last_error_date = Some(dates::to_stored(now));
+                }
+                return Err(error);
+            }
+        };
+
+        self.record_refresh(feed_id, fetched.fetch_info, now);
+        let FeedMetadata {
+            description,
+            image_url,
+            language,
+            generator,
+            copyright,
+        } = FeedMetadata::from_channel(&fetched.channel);
+        if let Some(feed) = self.feed_mut(feed_id) {
+            // fields the feed stopped sending keep their old value
+            feed.description = description.or(feed.description.take());
+            feed.image_url = image_url.or(feed.image_url.take());
+            feed.language = language.or(feed.language.take());
+            feed.generator = generator.or(feed.generator.take());
+            feed.copyright = copyright.or(feed.copyright.take());
+            if let Some(favicon) = fetched.favicon {
+                feed.icon = Some(favicon.data);
+                feed.icon_mime_type = Some(favicon.mime_type);
+            }
+        }
+        let summary = self.store_items(feed_id, &fetched.channel.items, &fetched.redirects, now);
+        Ok(FeedUpdate {
+            feed_id,
+            feed_name: source.feed_name.clone(),
+            summary,
+        })
+    }
+
+    fn get_setting(&mut self, name: &str) -> Result<Option<String>> {
+        Ok(self.settings.get(name).cloned())
+    }
+
+    fn set_setting(&mut self, name: &str, value: Option<&str>) -> Result<()> {
+        match value {
+            Some(value) => self.settings.insert(name.to_string(), value.to_string()),
+            None => self.settings.remove(name),
+        };
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+//! Everything the views read and change, reachable without naming a
+//! database. SQLite is the backend the app runs on, while [`MemoryStorage`]
+//! keeps everything in memory for tests.
+
+mod memory;
+mod sqlite;
+
+pub use memory::MemoryStorage;
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use url::Url;
+
+use crate::{
+    feeds::FeedStats,
+    ingest::{FeedUpdate, FetchedFeed, RefreshSource},
+    mutes::MuteTermRecord,
+    pagination::ArticleCursor,
+    retention::{CompactReport, RetentionPolicy},
+    rules::{CompiledRule, NewRuleRecord, RuleRecord},
+    search::{SavedSearchRecord, SearchQuery, SearchResult},
+    tags::{ItemTag, TagFeed, TagRecord},
+    EnclosureRecord, FeedItemRecord, FeedRecord, FeedTitleUrl, NewFeedItemRecord, NewFeedRecord,
+    Result,
+};
+
+pub trait Storage {
+    /// Every subscribed feed, archived ones included, oldest first.
+    fn load_feeds(&mut self) -> Result<Vec<FeedRecord>>;
+    fn load_feed(&mut self, feed_id: i32) -> Result<FeedRecord>;
+    /// Subscribes to a feed, returning its id. Fails with
+    /// [`crate::Error::AlreadySubscribed`] for an address that's taken,
+    /// unless its feed is archived, which brings that feed back.
+    fn insert_feed(&mut self, feed: NewFeedRecord) -> Result<i32>;
+    fn rename_feed(&mut self, feed_id: i32, name: &str) -> Result<()>;
+    /// Points a feed at a new address, see [`crate::feeds::change_feed_url`].
+    fn change_feed_url(&mut self, feed_id: i32, feed_url: &Url) -> Result<()>;
+    /// Unsubscribes from a feed. With `keep_starred` a feed with starred
+    /// items is archived instead, keeping only those.
+    fn delete_feed(&mut self, feed_id: i32, keep_starred: bool) -> Result<()>;
+    fn load_feed_stats(&mut self, feed_id: i32) -> Result<FeedStats>;
+
+    /// The visible items of a feed, highest priority and newest first.
+    fn load_channel_items(&mut self, feed_id: i32) -> Result<Vec<FeedItemRecord>>;
+    /// At most `limit` visible items in the same order, of every feed or only
+    /// of `feed_id`, the ones right after `after`.
+    fn load_article_page(
+        &mut self,
+        feed_id: Option<i32>,
+        after: Option<&ArticleCursor>,
+        limit: i64,
+    ) -> Result<Vec<(FeedItemRecord, Option<FeedTitleUrl>)>>;
+    /// A page of [`Storage::load_channel_items`], see
+    /// [`Storage::load_article_page`].
+    fn load_channel_page(
+        &mut self,
+        feed_id: i32,
+        after: Option<&ArticleCursor>,
+        limit: i64,
+    ) -> Result<Vec<FeedItemRecord>> {
+        let items = self.load_article_page(Some(feed_id), after, limit)?;
+        Ok(items.into_iter().map(|(item, _)| item).collect())
+    }
+    fn insert_item(&mut self, item: NewFeedItemRecord) -> Result<i32>;
+
+    fn set_item_read(&mut self, item_id: i32, read: bool) -> Result<()>;
+    fn set_item_starred(&mut self, item_id: i32, starred: bool) -> Result<()>;
+    /// How many visible items of a feed haven't been read.
+    fn unread_count(&mut self, feed_id: i32) -> Result<i64>;
+
+    fn load_enclosures(&mut self, item_id: i32) -> Result<Vec<EnclosureRecord>>;
+    fn enclosure_url(&mut self, enclosure_id: i32) -> Result<Option<String>>;
+    /// Records where a downloaded enclosure was saved.
+    fn set_enclosure_path(&mut self, enclosure_id: i32, path: &str) -> Result<()>;
+    /// Remembers how far into an episode the listener got, in seconds.
+    fn save_playback_position(&mut self, enclosure_id: i32, position: f64) -> Result<()>;
+
+    /// Every tag in use along with how many items carry it, by name.
+    fn load_tags(&mut self) -> Result<Vec<(TagRecord, i64)>>;
+    fn load_item_tags(&mut self, item_id: i32) -> Result<Vec<ItemTag>>;
+    /// Attaches the tag called `name` to an item, see [`crate::tags::tag_item`].
+    fn tag_item(&mut self, item_id: i32, name: &str, source: &str) -> Result<()>;
+    /// Removes a tag from an item, and the tag itself once nothing uses it.
+    fn untag_item(&mut self, item_id: i32, tag_id: i32) -> Result<()>;
+    fn load_tag_feed(&mut self, tag_id: i32) -> Result<TagFeed>;
+
+    /// Every mute term, by term.
+    fn load_mute_terms(&mut self) -> Result<Vec<MuteTermRecord>>;
+    /// Mutes a term everywhere, or only in `feed_id`. Fails with
+    /// [`crate::Error::Invalid`] for an empty term or a broken regex.
+    fn add_mute_term(&mut self, feed_id: Option<i32>, term: &str, is_regex: bool) -> Result<()>;
+    fn delete_mute_term(&mut self, mute_term_id: i32) -> Result<()>;
+    /// How many visible articles each term is hiding, see
+    /// [`crate::mutes::hidden_counts`].
+    fn hidden_counts(&mut self) -> Result<HashMap<i32, usize>>;
+
+    /// The visible articles matching a query, see [`crate::search::search`].
+    fn search(&mut self, query: &SearchQuery) -> Result<Vec<SearchResult>>;
+    /// How many unread articles a saved query currently matches.
+    fn count_unread(&mut self, query: &str) -> Result<i64>;
+    /// Every saved search, by name.
+    fn load_saved_searches(&mut self) -> Result<Vec<SavedSearchRecord>>;
+    fn save_search(&mut self, name: &str, query: &str) -> Result<i32>;
+    fn delete_saved_search(&mut self, saved_search_id: i32) -> Result<()>;
+
+    /// Every rule, oldest first.
+    fn load_rules(&mut self) -> Result<Vec<RuleRecord>>;
+    /// Adds a rule, or replaces the one with `rule_id`, returning its id.
+    fn save_rule(&mut self, rule_id: Option<i32>, rule: NewRuleRecord) -> Result<i32>;
+    fn set_rule_enabled(&mut self, rule_id: i32, enabled: bool) -> Result<()>;
+    fn delete_rule(&mut self, rule_id: i32) -> Result<()>;
+    /// The stored items a rule would act on, without changing anything.
+    fn dry_run(&mut self, rule: &CompiledRule) -> Result<Vec<FeedItemRecord>>;
+    /// Applies a rule to every stored item, returning how many actions ran.
+    fn apply_retroactively(&mut self, rule: &CompiledRule) -> Result<usize>;
+
+    /// The policy used by feeds that don't set their own.
+    fn load_global_policy(&mut self) -> Result<RetentionPolicy>;
+    fn save_global_policy(&mut self, policy: RetentionPolicy) -> Result<()>;
+    fn set_feed_policy(&mut self, feed_id: i32, policy: RetentionPolicy) -> Result<()>;
+    /// Purges old items and gives the space they took back, see
+    /// [`crate::retention::compact`].
+    fn compact(&mut self, now: DateTime<Utc>) -> Result<CompactReport>;
+
+    /// What refreshing a feed needs from storage, read before going to the
+    /// network, see [`crate::ingest::fetch_refresh`].
+    fn refresh_source(&mut self, feed_id: i32) -> Result<RefreshSource>;
+    /// The sources of every feed that isn't archived.
+    fn refresh_sources(&mut self) -> Result<Vec<RefreshSource>>;
+    /// Subscribes to a feed found by [`crate::ingest::fetch_subscription`]
+    /// and stores its items.
+    fn store_subscription(
+        &mut self,
+        fetched: FetchedFeed,
+        now: DateTime<Utc>,
+    ) -> Result<FeedUpdate>;
+    /// Stores what [`crate::ingest::fetch_refresh`] got for `source`. A failed
+    /// fetch is recorded on the feed before it's returned.
+    fn store_refresh(
+        &mut self,
+        source: &RefreshSource,
+        fetched: Result<Option<FetchedFeed>>,
+        now: DateTime<Utc>,
+    ) -> Result<FeedUpdate>;
+
+    fn get_setting(&mut self, name: &str) -> Result<Option<String>>;
+    /// Stores a setting, or removes it when `value` is `None`.
+    fn set_setting(&mut self, name: &str, value: Option<&str>) -> Result<()>;
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use diesel::SqliteConnection;
+use url::Url;
+
+use super::Storage;
+use crate::{
+    feeds::{self, FeedStats},
+    ingest::{self, FeedUpdate, FetchedFeed, RefreshSource},
+    mutes::{self, MuteTermRecord},
+    pagination::{self, ArticleCursor},
+    repository,
+    retention::{self, CompactReport, RetentionPolicy},
+    rules::{self, CompiledRule, NewRuleRecord, RuleRecord},
+    search::{self, SavedSearchRecord, SearchQuery, SearchResult},
+    settings,
+    tags::{self, ItemTag, TagFeed, TagRecord},
+    EnclosureRecord, FeedItemRecord, FeedRecord, FeedTitleUrl, NewFeedItemRecord, NewFeedRecord,
+    Result,
+};
+
+impl Storage for SqliteConnection {
+    fn load_feeds(&mut self) -> Result<Vec<FeedRecord>> {
+        Ok(repository::load_feeds(self)?)
+    }
+
+    fn load_feed(&mut self, feed_id: i32) -> Result<FeedRecord> {
+        repository::load_feed(self, feed_id)
+    }
+
+    fn insert_feed(&mut self, feed: NewFeedRecord) -> Result<i32> {
+        repository::insert_feed(self, feed)
+    }
+
+    fn rename_feed(&mut self, feed_id: i32, name: &str) -> Result<()> {
+        feeds::rename_feed(self, feed_id, name)
+    }
+
+    fn change_feed_url(&mut self, feed_id: i32, feed_url: &Url) -> Result<()> {
+        feeds::change_feed_url(self, feed_id, feed_url)
+    }
+
+    fn delete_feed(&mut self, feed_id: i32, keep_starred: bool) -> Result<()> {
+        Ok(feeds::delete_feed(self, feed_id, keep_starred)?)
+    }
+
+    fn load_feed_stats(&mut self, feed_id: i32) -> Result<FeedStats> {
+        Ok(feeds::load_feed_stats(self, feed_id)?)
+    }
+
+    fn load_channel_items(&mut self, feed_id: i32) -> Result<Vec<FeedItemRecord>> {
+        Ok(repository::load_channel_items(self, feed_id)?)
+    }
+
+    fn load_article_page(
+        &mut self,
+        feed_id: Option<i32>,
+        after: Option<&ArticleCursor>,
+        limit: i64,
+    ) -> Result<Vec<(FeedItemRecord, Option<FeedTitleUrl>)>> {
+        Ok(pagination::load_article_page(self, feed_id, after, limit)?)
+    }
+
+    fn insert_item(&mut self, item: NewFeedItemRecord) -> Result<i32> {
+        Ok(repository::insert_item(self, item)?)
+    }
+
+    fn set_item_read(&mut self, item_id: i32, read: bool) -> Result<()> {
+        repository::set_item_read(self, item_id, read)?;
+        Ok(())
+    }
+
+    fn set_item_starred(&mut self, item_id: i32, starred: bool) -> Result<()> {
+        repository::set_item_starred(self, item_id, starred)?;
+        Ok(())
+    }
+
+    fn unread_count(&mut self, feed_id: i32) -> Result<i64> {
+        Ok(repository::unread_count(self, feed_id)?)
+    }
+
+    fn load_enclosures(&mut self, item_id: i32) -> Result<Vec<EnclosureRecord>> {
+        Ok(repository::load_enclosures(self, item_id)?)
+    }
+
+    fn enclosure_url(&mut self, enclosure_id: i32) -> Result<Option<String>> {
+        Ok(repository::enclosure_url(self, enclosure_id)?)
+    }
+
+    fn set_enclosure_path(&mut self, enclosure_id: i32, path: &str) -> Result<()> {
+        repository::set_enclosure_path(self, enclosure_id, path)?;
+        Ok(())
+    }
+
+    fn save_playback_position(&mut self, enclosure_id: i32, position: f64) -> Result<()> {
+        repository::save_playback_position(self, enclosure_id, position)?;
+        Ok(())
+    }
+
+    fn load_tags(&mut self) -> Result<Vec<(TagRecord, i64)>> {
+        Ok(tags::load_tags(self)?)
+    }
+
+    fn load_item_tags(&mut self, item_id: i32) -> Result<Vec<ItemTag>> {
+        Ok(tags::load_item_tags(self, item_id)?)
+    }
+
+    fn tag_item(&mut self, item_id: i32, name: &str, source: &str) -> Result<()> {
+        Ok(tags::tag_item(self, item_id, name, source, Utc::now())?)
+    }
+
+    fn untag_item(&mut self, item_id: i32, tag_id: i32) -> Result<()> {
+        Ok(tags::untag_item(self, item_id, tag_id)?)
+    }
+
+    fn load_tag_feed(&mut self, tag_id: i32) -> Result<TagFeed> {
+        Ok(tags::load_tag_feed(self, tag_id)?)
+    }
+
+    fn load_mute_terms(&mut self) -> Result<Vec<MuteTermRecord>> {
+        Ok(mutes::load_mute_terms(self)?)
+    }
+
+    fn add_mute_term(&mut self, feed_id: Option<i32>, term: &str, is_regex: bool) -> Result<()> {
+        mutes::add_mute_term(self, feed_id, term, is_regex)
+    }
+
+    fn delete_mute_term(&mut self, mute_term_id: i32) -> Result<()> {
+        mutes::delete_mute_term(self, mute_term_id)?;
+        Ok(())
+    }
+
+    fn hidden_counts(&mut self) -> Result<HashMap<i32, usize>> {
+        Ok(mutes::hidden_counts(self)?)
+    }
+
+    fn search(&mut self, query: &SearchQuery) -> Result<Vec<SearchResult>> {
+        Ok(search::search(self, query)?)
+    }
+
+    fn count_unread(&mut self, query: &str) -> Result<i64> {
+        Ok(search::count_unread(self, query)?)
+    }
+
+    fn load_saved_searches(&mut self) -> Result<Vec<SavedSearchRecord>> {
+        Ok(search::load_saved_searches(self)?)
+    }
+
+    fn save_search(&mut self, name: &str, query: &str) -> Result<i32> {
+        Ok(search::save_search(self, name, query)?)
+    }
+
+    fn delete_saved_search(&mut self, saved_search_id: i32) -> Result<()> {
+        search::delete_saved_search(self, saved_search_id)?;
+        Ok(())
+    }
+
+    fn load_rules(&mut self) -> Result<Vec<RuleRecord>> {
+        Ok(rules::load_rules(self)?)
+    }
+
+    fn save_rule(&mut self, rule_id: Option<i32>, rule: NewRuleRecord) -> Result<i32> {
+        Ok(rules::save_rule(self, rule_id, rule)?)
+    }
+
+    fn set_rule_enabled(&mut self, rule_id: i32, enabled: bool) -> Result<()> {
+        rules::set_rule_enabled(self, rule_id, enabled)?;
+        Ok(())
+    }
+
+    fn delete_rule(&mut self, rule_id: i32) -> Result<()> {
+        rules::delete_rule(self, rule_id)?;
+        Ok(())
+    }
+
+    fn dry_run(&mut self, rule: &CompiledRule) -> Result<Vec<FeedItemRecord>> {
+        Ok(rules::dry_run(self, rule)?)
+    }
+
+    fn apply_retroactively(&mut self, rule: &CompiledRule) -> Result<usize> {
+        Ok(rules::apply_retroactively(self, rule)?)
+    }
+
+    fn load_global_policy(&mut self) -> Result<RetentionPolicy> {
+        Ok(retention::load_global_policy(self)?)
+    }
+
+    fn save_global_policy(&mut self, policy: RetentionPolicy) -> Result<()> {
+        Ok(retention::save_global_policy(self, policy)?)
+    }
+
+    fn set_feed_policy(&mut self, feed_id: i32, policy: RetentionPolicy) -> Result<()> {
+        retention::set_feed_policy(self, feed_id, policy)?;
+        Ok(())
+    }
+
+    fn compact(&mut self, now: DateTime<Utc>) -> Result<CompactReport> {
+        Ok(retention::compact(self, now)?)
+    }
+
+    fn refresh_source(&mut self, feed_id: i32) -> Result<RefreshSource> {
+        ingest::refresh_source(self, feed_id)
+    }
+
+    fn refresh_sources(&mut self) -> Result<Vec<RefreshSource>> {
+        ingest::refresh_sources(self)
+    }
+
+    fn store_subscription(
+        &mut self,
+        fetched: FetchedFeed,
+        now: DateTime<Utc>,
+    ) -> Result<FeedUpdate> {
+        ingest::store_subscription(self, fetched, now)
+    }
+
+    fn store_refresh(
+        &mut self,
+        source: &RefreshSource,
+        fetched: Result<Option<FetchedFeed>>,
+        now: DateTime<Utc>,
+    ) -> Result<FeedUpdate> {
+        ingest::store_refresh(self, source, fetched, now)
+    }
+
+    fn get_setting(&mut self, name: &str) -> Result<Option<String>> {
+        Ok(settings::get_setting(self, name)?)
+    }
+
+    fn set_setting(&mut self, name: &str, value: Option<&str>) -> Result<()> {
+        settings::set_setting(self, name, value)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use chrono::{DateTime, Utc};
+use diesel::{dsl::count_star, dsl::exists, dsl::not, prelude::*};
+
+use crate::{
+    repository::{article_order, to_articles},
+    schema, Article, FeedItemRecord, FeedTitleUrl, StringTime,
+};
+
+/// Tags that came from the feed's own `<category>` elements.
+pub const FEED_SOURCE: &str = "feed";
+/// Tags the user added in the app.
+pub const USER_SOURCE: &str = "user";
+
+#[derive(Queryable, Selectable, Identifiable, Clone, Debug, PartialEq)]
+#[diesel(table_name = schema::tags)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct TagRecord {
+    pub id: i32,
+    pub name: String,
+    #[diesel(deserialize_as = StringTime)]
+    pub create_date: DateTime<Utc>,
+    #[diesel(deserialize_as = StringTime)]
+    pub update_date: DateTime<Utc>,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = schema::tags)]
+pub struct NewTagRecord<'a> {
+    pub name: &'a str,
+    #[diesel(serialize_as = StringTime)]
+    pub create_date: DateTime<Utc>,
+    #[diesel(serialize_as = StringTime)]
+    pub update_date: DateTime<Utc>,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = schema::item_tags)]
+pub struct NewItemTagRecord<'a> {
+    pub item_id: i32,
+    pub tag_id: i32,
+    pub source: &'a str,
+    #[diesel(serialize_as = StringTime)]
+    pub create_date: DateTime<Utc>,
+}
+
+/// A tag as attached to a particular item.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemTag {
+    pub tag_id: i32,
+    pub name: String,
+    pub source: String,
+}
+
+pub struct TagFeed {
+    pub tag_id: i32,
+    pub name: String,
+    pub articles: Vec<Article>,
+}
+
+/// Attaches the tag called `tag_name` to an item, creating the tag if needed.
+/// Tag names are matched case-insensitively.
+pub fn tag_item(
+    conn: &mut SqliteConnection,
+    item_id: i32,
+    tag_name: &str,
+    source: &str,
+    now: DateTime<Utc>,
+) -> QueryResult<()> {
+    use schema::{item_tags, tags};
+
+    let tag_name = tag_name.trim();
+    if tag_name.is_empty() {
+        return Ok(());
+    }
+
+    diesel::insert_into(tags::table)
+        .values(NewTagRecord {
+            name: tag_name,
+            create_date: now,
+            update_date: now,
+        })
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+
+    let tag_id = tags::table
+        .filter(tags::name.eq(tag_name))
+        .select(tags::id)
+        .first::<i32>(conn)?;
+
+    diesel::insert_into(item_tags::table)
+        .values(NewItemTagRecord {
+            item_id,
+            tag_id,
+            source,
+            create_date: now,
+        })
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Removes a tag from an item, deleting the tag itself once nothing uses it.
+pub fn untag_item(conn: &mut SqliteConnection, item_id: i32, tag_id: i32) -> QueryResult<()> {
+    use schema::{item_tags, tags};
+
+    conn.transaction(|conn| {
+        diesel::delete(
+            item_tags::table
+                .filter(item_tags::item_id.eq(item_id))
+                .filter(item_tags::tag_id.eq(tag_id)),
+        )
+        .execute(conn)?;
+
+        diesel::delete(tags::table.filter(tags::id.eq(tag_id)).filter(not(exists(
+            item_tags::table.filter(item_tags::tag_id.eq(tag_id)),
+        ))))
+        .execute(conn)?;
+
+        Ok(())
+    })
+}
+
+pub fn load_item_tags(conn: &mut SqliteConnection, item_id: i32) -> QueryResult<Vec<ItemTag>> {
+    use schema::{item_tags, tags};
+
+    let rows = item_tags::table
+        .inner_join(tags::table)
+        .filter(item_tags::item_id.eq(item_id))
+        .order(tags::name.asc())
+        .select((tags::id, tags::name, item_tags::source))
+        .load::<(i32, String, String)>(conn)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(tag_id, name, source)| ItemTag {
+            tag_id,
+            name,
+            source,
+        })
+        .collect())
+}
+
+/// Every tag along with how many items carry it.
+pub fn load_tags(conn: &mut SqliteConnection) -> QueryResult<Vec<(TagRecord, i64)>> {
+    use schema::{item_tags, tags};
+
+    tags::table
+        .inner_join(item_tags::table)
+        .group_by(tags::id)
+        .order(tags::name.asc())
+        .select((TagRecord::as_select(), count_star()))
+        .load::<(TagRecord, i64)>(conn)
+}
+
+pub fn load_tag_feed(conn: &mut SqliteConnection, tag_id: i32) -> QueryResult<TagFeed> {
+    use schema::{feed_items, feeds, item_tags, tags};
+
+    let name = tags::table
+        .filter(tags::id.eq(tag_id))
+        .select(tags::name)
+        .first::<String>(conn)?;
+
+    let all_items = feed_items::table
+        .inner_join(item_tags::table)
+        .left_join(feeds::table)
+        .filter(item_tags::tag_id.eq(tag_id))
+        .order(article_order())
+        .select((
+            FeedItemRecord::as_select(),
+            Option::<FeedTitleUrl>::as_select(),
+        ))
+        .load::<(FeedItemRecord, Option<FeedTitleUrl>)>(conn)?;
+
+    Ok(TagFeed {
+        tag_id,
+        name,
+        articles: to_articles(all_items),
+    })
+}
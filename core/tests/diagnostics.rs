@@ -0,0 +1,237 @@
+//! Reading logs back and exporting the diagnostics bundle.
+
+mod support;
+
+use std::{
+    fs,
+    io::{self, Read, Write},
+    sync::{Arc, Mutex},
+};
+
+use chrono::Utc;
+use rss_reader_core::{
+    diagnostics::{export_bundle, fetch_stats, read_logs, LogEntry, LOG_FILE_PREFIX},
+    ingest::{refresh_feed, subscribe},
+};
+use support::{fixture, FixtureServer, Route, TempDatabase};
+use tempfile::TempDir;
+use tracing::Level;
+use zip::ZipArchive;
+
+/// Collects what the subscriber writes.
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Captured {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buffer);
+        Ok(buffer.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs `f` with events written the way the app writes its log file.
+fn logged(f: impl FnOnce()) -> String {
+    let captured = Captured::default();
+    let writer = captured.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_ansi(false)
+        .with_max_level(Level::DEBUG)
+        .with_writer(move || writer.clone())
+        .finish();
+    tracing::subscriber::with_default(subscriber, f);
+    let bytes = captured.0.lock().unwrap().clone();
+    String::from_utf8(bytes).unwrap()
+}
+
+#[test]
+fn log_lines_are_read_back_with_their_feed() {
+    let entry = LogEntry::parse(
+        "2025-05-01T09:00:00.000000Z  WARN feed{id=3}: rss_reader_core::ingest: refresh failed",
+    )
+    .unwrap();
+    assert_eq!(entry.time, "2025-05-01T09:00:00.000000Z");
+    assert_eq!(entry.level, Level::WARN);
+    assert_eq!(entry.feed_id, Some(3));
+
+    let entry =
+        LogEntry::parse("2025-05-01T09:00:00.000000Z ERROR rss_reader: no database").unwrap();
+    assert_eq!((entry.level, entry.feed_id), (Level::ERROR, None));
+
+    assert_eq!(LogEntry::parse("  continued message"), None);
+}
+
+#[test]
+fn ingesting_logs_events_in_a_span_per_feed() {
+    let server = FixtureServer::start();
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+
+    let mut feed_id = 0;
+    let log = logged(|| {
+        feed_id = subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now())
+            .unwrap()
+            .feed_id;
+        refresh_feed(&mut db.conn, feed_id, Utc::now()).unwrap();
+    });
+
+    let entries = log.lines().filter_map(LogEntry::parse).collect::<Vec<_>>();
+    assert_eq!(
+        entries.len(),
+        log.lines().count(),
+        "every line parses:\n{log}"
+    );
+    let stored = entries
+        .iter()
+        .filter(|entry| entry.text.contains("stored items"))
+        .collect::<Vec<_>>();
+    assert_eq!(stored.len(), 2, "{log}");
+    assert!(
+        stored.iter().all(|entry| entry.feed_id == Some(feed_id)),
+        "{log}"
+    );
+    assert!(stored[0].text.contains("inserted=3"), "{log}");
+    assert!(entries
+        .iter()
+        .any(|entry| entry.level == Level::DEBUG && entry.text.contains("format=\"RSS\"")));
+}
+
+#[test]
+fn logs_are_read_oldest_file_first() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(format!("{LOG_FILE_PREFIX}.2025-05-02")),
+        "2025-05-02T08:00:00Z  INFO rss_reader: second day\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join(format!("{LOG_FILE_PREFIX}.2025-05-01")),
+        "2025-05-01T08:00:00Z  WARN rss_reader: first day\nspanning two lines\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("unrelated.txt"),
+        "2025-05-01T07:00:00Z  INFO other\n",
+    )
+    .unwrap();
+
+    let entries = read_logs(dir.path()).unwrap();
+
+    let texts = entries
+        .iter()
+        .map(|entry| entry.text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        texts,
+        [
+            "rss_reader: first day\nspanning two lines",
+            "rss_reader: second day"
+        ]
+    );
+    assert!(read_logs(&dir.path().join("missing")).unwrap().is_empty());
+}
+
+#[test]
+fn the_bundle_holds_logs_and_stats_without_feed_details() {
+    let server = FixtureServer::start();
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+    let update = subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now()).unwrap();
+    let stats = fetch_stats(&mut db.conn).unwrap();
+    assert_eq!(stats.len(), 1);
+    assert_eq!((stats[0].feed_id, stats[0].items), (update.feed_id, 3));
+    assert_eq!(stats[0].format.as_deref(), Some("RSS 2.0"));
+    assert!(!stats[0].failing);
+
+    let dir = TempDir::new().unwrap();
+    let log_dir = dir.path().join("logs");
+    fs::create_dir(&log_dir).unwrap();
+    fs::write(
+        log_dir.join(format!("{LOG_FILE_PREFIX}.2025-05-01")),
+        "2025-05-01T08:00:00Z  INFO rss_reader: hello\n",
+    )
+    .unwrap();
+    let destination = dir.path().join("diagnostics.zip");
+
+    export_bundle(&mut db.conn, &log_dir, &destination).unwrap();
+
+    let mut zip = ZipArchive::new(fs::File::open(&destination).unwrap()).unwrap();
+    let mut names = zip.file_names().map(str::to_string).collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            "fetch_stats.tsv".to_string(),
+            format!("logs/{LOG_FILE_PREFIX}.2025-05-01"),
+            "version.txt".to_string(),
+        ]
+    );
+    let mut read = |name: &str| {
+        let mut content = String::new();
+        zip.by_name(name)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        content
+    };
+    assert!(read("version.txt").contains("schema 20"));
+    let table = read("fetch_stats.tsv");
+    assert!(table.contains("\tRSS 2.0\t3\t"), "{table}");
+    assert!(
+        !table.contains("127.0.0.1") && !table.contains("Fixture News"),
+        "{table}"
+    );
+}
+
+#[test]
+fn the_bundled_logs_name_feeds_by_id_only() {
+    let server = FixtureServer::start();
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+    let mut feed_id = 0;
+    let mut log = logged(|| {
+        feed_id = subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now())
+            .unwrap()
+            .feed_id;
+        server.serve("/rss.xml", Route::feed(fixture("malformed.xml")));
+        refresh_feed(&mut db.conn, feed_id, Utc::now()).unwrap_err();
+        // a subscription that never got stored
+        subscribe(&mut db.conn, &server.url("/missing.xml"), Utc::now()).unwrap_err();
+    });
+    // what the app logs when it tells about a failure
+    log.push_str(
+        "2025-05-01T08:00:00Z  WARN rss_reader::notifications: Couldn't refresh Fixture News\n",
+    );
+
+    let dir = TempDir::new().unwrap();
+    let log_dir = dir.path().join("logs");
+    fs::create_dir(&log_dir).unwrap();
+    fs::write(log_dir.join(format!("{LOG_FILE_PREFIX}.2025-05-01")), &log).unwrap();
+    let destination = dir.path().join("diagnostics.zip");
+
+    export_bundle(&mut db.conn, &log_dir, &destination).unwrap();
+
+    let mut zip = ZipArchive::new(fs::File::open(&destination).unwrap()).unwrap();
+    let mut bundled = String::new();
+    zip.by_name(&format!("logs/{LOG_FILE_PREFIX}.2025-05-01"))
+        .unwrap()
+        .read_to_string(&mut bundled)
+        .unwrap();
+    assert!(log.contains("127.0.0.1"), "{log}");
+    assert!(
+        !bundled.contains("127.0.0.1") && !bundled.contains("Fixture News"),
+        "{bundled}"
+    );
+    assert!(
+        bundled.contains(&format!("url=feed#{feed_id}")),
+        "{bundled}"
+    );
+    assert!(
+        bundled.contains(&format!("Couldn't refresh feed#{feed_id}")),
+        "{bundled}"
+    );
+    assert_eq!(bundled.lines().count(), log.lines().count());
+}
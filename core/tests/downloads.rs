@@ -0,0 +1,29 @@
+//! Saving enclosures to disk.
+
+mod support;
+
+use std::time::Duration;
+
+use rss_reader_core::fetcher::download_enclosure;
+use support::{FixtureServer, Route};
+use tempfile::TempDir;
+
+#[test]
+fn slow_downloads_arrive_whole() {
+    let server = FixtureServer::start();
+    let episode = "An episode of a podcast. ".repeat(100);
+    server.serve(
+        "/episode.mp3",
+        Route::Slow {
+            body: episode.clone(),
+            pause: Duration::from_millis(20),
+        },
+    );
+    let dir = TempDir::new().unwrap();
+
+    let path = download_enclosure(7, &server.url("/episode.mp3"), dir.path()).unwrap();
+
+    assert_eq!(path.file_name().unwrap(), "7.mp3");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), episode);
+    assert!(!dir.path().join("7.mp3.part").exists());
+}
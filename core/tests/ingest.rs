@@ -0,0 +1,433 @@
+//! Adds and refreshes feeds served by a local HTTP server, end to end, into a
+//! temporary SQLite database.
+
+mod support;
+
+use std::time::Duration;
+
+use chrono::Utc;
+use diesel::RunQueryDsl;
+use rss_reader_core::{
+    ingest::{refresh_feed, subscribe, FeedUpdate},
+    repository::{load_channel_items, load_feed, load_feeds, set_item_read},
+    settings::set_setting,
+    tags::load_item_tags,
+    Error, FeedItemRecord,
+};
+use support::{fixture, huge_feed, FixtureServer, Route, TempDatabase};
+
+fn titles(items: &[FeedItemRecord]) -> Vec<&str> {
+    items
+        .iter()
+        .filter_map(|item| item.title.as_deref())
+        .collect()
+}
+
+#[test]
+fn subscribing_to_rss_stores_the_feed_and_its_items() {
+    let server = FixtureServer::start();
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+
+    let FeedUpdate {
+        feed_id,
+        feed_name,
+        summary,
+    } = subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now()).unwrap();
+
+    assert_eq!(feed_name, "Fixture News");
+    assert_eq!(
+        (summary.inserted, summary.updated, summary.unchanged),
+        (3, 0, 0)
+    );
+    assert!(summary.rejected.is_empty());
+
+    let feed = load_feed(&mut db.conn, feed_id).unwrap();
+    assert_eq!(feed.feed_url, server.url("/rss.xml"));
+    assert_eq!(feed.format.as_deref(), Some("RSS 2.0"));
+    assert_eq!(feed.language.as_deref(), Some("en"));
+    assert!(feed.last_refresh_date.is_some());
+
+    let items = load_channel_items(&mut db.conn, feed_id).unwrap();
+    assert_eq!(titles(&items), ["Third post", "Second post", "First post"]);
+    let first = items
+        .iter()
+        .find(|item| item.title.as_deref() == Some("First post"))
+        .unwrap();
+    assert!(first.published_at.is_some());
+    let tags = load_item_tags(&mut db.conn, first.id).unwrap();
+    assert_eq!(
+        tags.iter().map(|tag| tag.name.as_str()).collect::<Vec<_>>(),
+        ["news"]
+    );
+}
+
+#[test]
+fn subscribing_to_atom_converts_its_entries() {
+    let server = FixtureServer::start();
+    server.serve("/atom.xml", Route::feed(fixture("atom.xml")));
+    let mut db = TempDatabase::new();
+
+    let update = subscribe(&mut db.conn, &server.url("/atom.xml"), Utc::now()).unwrap();
+
+    assert_eq!(update.feed_name, "Fixture Journal");
+    assert_eq!(update.summary.inserted, 2);
+    let feed = load_feed(&mut db.conn, update.feed_id).unwrap();
+    assert_eq!(feed.format.as_deref(), Some("Atom"));
+
+    let items = load_channel_items(&mut db.conn, update.feed_id).unwrap();
+    assert_eq!(titles(&items), ["Atom entry two", "Atom entry one"]);
+    assert_eq!(
+        items[0].url.as_deref(),
+        Some(server.url("/journal/2").as_str())
+    );
+    assert_eq!(
+        items[0].content.as_deref(),
+        Some("<p>The second entry.</p>")
+    );
+    assert_eq!(items[1].description.as_deref(), Some("The first entry."));
+    assert_eq!(items[1].author.as_deref(), Some("Fixture Author"));
+    // entries without <published> fall back to <updated>
+    assert!(items.iter().all(|item| item.published_at.is_some()));
+}
+
+#[test]
+fn subscribing_follows_redirects() {
+    let server = FixtureServer::start();
+    server.serve("/old-feed", Route::Redirect("/rss.xml".to_string()));
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+
+    let update = subscribe(&mut db.conn, &server.url("/old-feed"), Utc::now()).unwrap();
+
+    assert_eq!(update.summary.inserted, 3);
+    assert_eq!(server.requests_for("/rss.xml").len(), 1);
+    // refreshing goes through the redirect again
+    let refreshed = refresh_feed(&mut db.conn, update.feed_id, Utc::now()).unwrap();
+    assert_eq!(refreshed.summary.unchanged, 3);
+}
+
+#[test]
+fn gzip_responses_are_decoded() {
+    let server = FixtureServer::start();
+    server.serve("/rss.xml.gz", Route::Gzip(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+
+    let update = subscribe(&mut db.conn, &server.url("/rss.xml.gz"), Utc::now()).unwrap();
+
+    assert_eq!(update.feed_name, "Fixture News");
+    assert_eq!(update.summary.inserted, 3);
+}
+
+#[test]
+fn slow_responses_still_arrive() {
+    let server = FixtureServer::start();
+    server.serve(
+        "/slow.xml",
+        Route::Slow {
+            body: fixture("rss.xml"),
+            pause: Duration::from_millis(100),
+        },
+    );
+    let mut db = TempDatabase::new();
+
+    let update = subscribe(&mut db.conn, &server.url("/slow.xml"), Utc::now()).unwrap();
+
+    assert_eq!(update.summary.inserted, 3);
+}
+
+#[test]
+fn huge_feeds_are_stored_whole_and_deduplicated() {
+    const ITEMS: usize = 3000;
+    let server = FixtureServer::start();
+    server.serve("/huge.xml", Route::feed(huge_feed(ITEMS)));
+    let mut db = TempDatabase::new();
+
+    let update = subscribe(&mut db.conn, &server.url("/huge.xml"), Utc::now()).unwrap();
+    assert_eq!(update.summary.inserted, ITEMS);
+
+    let refreshed = refresh_feed(&mut db.conn, update.feed_id, Utc::now()).unwrap();
+    assert_eq!(
+        (refreshed.summary.inserted, refreshed.summary.unchanged),
+        (0, ITEMS)
+    );
+    assert_eq!(
+        load_channel_items(&mut db.conn, update.feed_id)
+            .unwrap()
+            .len(),
+        ITEMS
+    );
+}
+
+#[test]
+fn malformed_feeds_are_not_subscribed_to() {
+    let server = FixtureServer::start();
+    server.serve("/malformed.xml", Route::feed(fixture("malformed.xml")));
+    let mut db = TempDatabase::new();
+
+    let result = subscribe(&mut db.conn, &server.url("/malformed.xml"), Utc::now());
+
+    assert!(matches!(result, Err(Error::NoFeed(_))), "{result:?}");
+    assert!(load_feeds(&mut db.conn).unwrap().is_empty());
+}
+
+#[test]
+fn subscribing_twice_is_refused() {
+    let server = FixtureServer::start();
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+
+    subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now()).unwrap();
+    let again = subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now());
+
+    assert!(
+        matches!(again, Err(Error::AlreadySubscribed(_))),
+        "{again:?}"
+    );
+    assert_eq!(load_feeds(&mut db.conn).unwrap().len(), 1);
+}
+
+#[test]
+fn refreshing_an_unchanged_feed_stores_nothing_new() {
+    let server = FixtureServer::start();
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+    let update = subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now()).unwrap();
+
+    let refreshed = refresh_feed(&mut db.conn, update.feed_id, Utc::now()).unwrap();
+
+    assert_eq!(
+        (
+            refreshed.summary.inserted,
+            refreshed.summary.updated,
+            refreshed.summary.unchanged
+        ),
+        (0, 0, 3)
+    );
+    assert_eq!(
+        load_channel_items(&mut db.conn, update.feed_id)
+            .unwrap()
+            .len(),
+        3
+    );
+}
+
+#[test]
+fn links_stored_before_they_were_cleaned_up_are_matched() {
+    let server = FixtureServer::start();
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+    let update = subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now()).unwrap();
+
+    // older versions stored links as the feed wrote them
+    diesel::sql_query("UPDATE feed_items SET url = url || '/?utm_source=rss', original_url = NULL")
+        .execute(&mut db.conn)
+        .unwrap();
+    set_setting(&mut db.conn, "links.canonical", None).unwrap();
+    db.reopen();
+    let refreshed = refresh_feed(&mut db.conn, update.feed_id, Utc::now()).unwrap();
+
+    assert_eq!(refreshed.summary.inserted, 0);
+    assert_eq!(
+        load_channel_items(&mut db.conn, update.feed_id)
+            .unwrap()
+            .len(),
+        3
+    );
+}
+
+/// An Atom feed with a single entry that has no link.
+fn linkless_atom(updated: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Status</title>
+  <id>urn:uuid:60a76c80-d399-11d9-b93c-0003939e0af7</id>
+  <updated>{updated}</updated>
+  <entry>
+    <title>Service status</title>
+    <id>urn:uuid:1225c695-cfb8-4ebb-aaaa-80da344efa6c</id>
+    <updated>{updated}</updated>
+    <summary>All systems go.</summary>
+  </entry>
+</feed>"#
+    )
+}
+
+#[test]
+fn entries_without_a_link_are_found_by_their_id() {
+    let server = FixtureServer::start();
+    server.serve(
+        "/atom.xml",
+        Route::feed(linkless_atom("2025-05-01T18:30:02Z")),
+    );
+    let mut db = TempDatabase::new();
+    let update = subscribe(&mut db.conn, &server.url("/atom.xml"), Utc::now()).unwrap();
+
+    server.serve(
+        "/atom.xml",
+        Route::feed(linkless_atom("2025-05-02T08:00:00Z")),
+    );
+    let refreshed = refresh_feed(&mut db.conn, update.feed_id, Utc::now()).unwrap();
+
+    assert_eq!(
+        (refreshed.summary.inserted, refreshed.summary.updated),
+        (0, 1)
+    );
+    let items = load_channel_items(&mut db.conn, update.feed_id).unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(
+        items[0].guid.as_deref(),
+        Some("urn:uuid:1225c695-cfb8-4ebb-aaaa-80da344efa6c")
+    );
+}
+
+#[test]
+fn refreshing_picks_up_edited_and_new_items() {
+    let server = FixtureServer::start();
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+    let update = subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now()).unwrap();
+    let second = load_channel_items(&mut db.conn, update.feed_id)
+        .unwrap()
+        .into_iter()
+        .find(|item| item.title.as_deref() == Some("Second post"))
+        .unwrap();
+    set_item_read(&mut db.conn, second.id, true).unwrap();
+
+    // the second post gets corrected, a fourth appears and the first drops
+    // out of the document
+    server.serve("/rss.xml", Route::feed(fixture("rss_changed.xml")));
+    let refreshed = refresh_feed(&mut db.conn, update.feed_id, Utc::now()).unwrap();
+
+    assert_eq!(
+        (
+            refreshed.summary.inserted,
+            refreshed.summary.updated,
+            refreshed.summary.unchanged
+        ),
+        (1, 1, 1)
+    );
+    let items = load_channel_items(&mut db.conn, update.feed_id).unwrap();
+    assert_eq!(
+        titles(&items),
+        [
+            "Fourth post",
+            "Third post",
+            "Second post, corrected",
+            "First post"
+        ]
+    );
+    let corrected = items.iter().find(|item| item.id == second.id).unwrap();
+    assert!(corrected.is_read, "edits from the feed keep the read state");
+}
+
+#[test]
+fn unmodified_feeds_are_answered_with_304() {
+    let server = FixtureServer::start();
+    server.serve(
+        "/cached.xml",
+        Route::Feed {
+            body: fixture("rss.xml"),
+            etag: Some("\"v1\"".to_string()),
+        },
+    );
+    let mut db = TempDatabase::new();
+    let update = subscribe(&mut db.conn, &server.url("/cached.xml"), Utc::now()).unwrap();
+    assert_eq!(
+        load_feed(&mut db.conn, update.feed_id)
+            .unwrap()
+            .etag
+            .as_deref(),
+        Some("\"v1\"")
+    );
+
+    let refreshed = refresh_feed(&mut db.conn, update.feed_id, Utc::now()).unwrap();
+
+    let requests = server.requests_for("/cached.xml");
+    assert_eq!(
+        requests.last().unwrap().if_none_match.as_deref(),
+        Some("\"v1\"")
+    );
+    assert_eq!(
+        (
+            refreshed.summary.inserted,
+            refreshed.summary.updated,
+            refreshed.summary.unchanged
+        ),
+        (0, 0, 0)
+    );
+    let feed = load_feed(&mut db.conn, update.feed_id).unwrap();
+    assert_eq!(feed.etag.as_deref(), Some("\"v1\""));
+    assert_eq!(feed.format.as_deref(), Some("RSS 2.0"));
+    assert_eq!(
+        load_channel_items(&mut db.conn, update.feed_id)
+            .unwrap()
+            .len(),
+        3
+    );
+
+    // a new version is fetched in full again
+    server.serve(
+        "/cached.xml",
+        Route::Feed {
+            body: fixture("rss_changed.xml"),
+            etag: Some("\"v2\"".to_string()),
+        },
+    );
+    let refreshed = refresh_feed(&mut db.conn, update.feed_id, Utc::now()).unwrap();
+    assert_eq!(refreshed.summary.inserted, 1);
+    assert_eq!(
+        load_feed(&mut db.conn, update.feed_id)
+            .unwrap()
+            .etag
+            .as_deref(),
+        Some("\"v2\"")
+    );
+}
+
+#[test]
+fn a_feed_that_breaks_keeps_its_items_and_records_the_error() {
+    let server = FixtureServer::start();
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+    let update = subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now()).unwrap();
+
+    server.serve("/rss.xml", Route::feed(fixture("malformed.xml")));
+    let result = refresh_feed(&mut db.conn, update.feed_id, Utc::now());
+
+    assert!(matches!(result, Err(Error::Parse(_))), "{result:?}");
+    let feed = load_feed(&mut db.conn, update.feed_id).unwrap();
+    assert!(feed.last_error.is_some());
+    assert_eq!(
+        load_channel_items(&mut db.conn, update.feed_id)
+            .unwrap()
+            .len(),
+        3
+    );
+
+    // and recovers once the feed is fixed
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    refresh_feed(&mut db.conn, update.feed_id, Utc::now()).unwrap();
+    assert_eq!(
+        load_feed(&mut db.conn, update.feed_id).unwrap().last_error,
+        None
+    );
+}
+
+#[test]
+fn a_feed_that_disappears_records_the_error() {
+    let server = FixtureServer::start();
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+    let update = subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now()).unwrap();
+
+    server.serve("/rss.xml", Route::Redirect("/gone.xml".to_string()));
+    let result = refresh_feed(&mut db.conn, update.feed_id, Utc::now());
+
+    assert!(matches!(result, Err(Error::Http(_))), "{result:?}");
+    assert!(load_feed(&mut db.conn, update.feed_id)
+        .unwrap()
+        .last_error
+        .is_some());
+}
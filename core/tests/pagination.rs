@@ -0,0 +1,81 @@
+//! Paging through All Articles.
+
+mod support;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use rss_reader_core::{
+    ingest::subscribe,
+    pagination::{ArticlePages, PAGE_SIZE},
+    repository::article_order,
+    schema::feed_items,
+};
+use support::{FixtureServer, Route, TempDatabase};
+
+/// An RSS feed with `count` items. Every third one has no date and the dated
+/// ones share their date in runs of up to ten, so page boundaries land among
+/// items that only their id tells apart.
+fn feed_with_ties(count: usize) -> String {
+    let mut feed = String::from(
+        "<?xml version=\"1.0\"?><rss version=\"2.0\"><channel>\
+         <title>Ties</title><link>{base}/</link><description>Ties</description>",
+    );
+    for number in 0..count {
+        let date = if number % 3 == 0 {
+            String::new()
+        } else {
+            format!(
+                "<pubDate>{:02} May 2025 09:00:00 +0000</pubDate>",
+                number / 10 + 1
+            )
+        };
+        feed.push_str(&format!(
+            "<item><title>Story {number}</title><link>{{base}}/story/{number}</link>{date}</item>"
+        ));
+    }
+    feed.push_str("</channel></rss>");
+    feed
+}
+
+#[test]
+fn pages_add_up_to_the_whole_list() {
+    let server = FixtureServer::start();
+    server.serve("/ties.xml", Route::feed(feed_with_ties(250)));
+    let mut db = TempDatabase::new();
+    subscribe(&mut db.conn, &server.url("/ties.xml"), Utc::now()).unwrap();
+
+    // some dated and some undated items are raised above the rest
+    let raised = feed_items::table
+        .select(feed_items::id)
+        .load::<i32>(&mut db.conn)
+        .unwrap()
+        .into_iter()
+        .filter(|id| id % 7 == 0)
+        .collect::<Vec<_>>();
+    diesel::update(feed_items::table.filter(feed_items::id.eq_any(raised)))
+        .set(feed_items::priority.eq(1))
+        .execute(&mut db.conn)
+        .unwrap();
+
+    let expected = feed_items::table
+        .filter(feed_items::is_hidden.eq(false))
+        .order(article_order())
+        .select(feed_items::id)
+        .load::<i32>(&mut db.conn)
+        .unwrap();
+    assert!(expected.len() as i64 > 2 * PAGE_SIZE);
+
+    let mut pages = ArticlePages::first(&mut db.conn).unwrap();
+    while pages.has_more() {
+        pages.load_next(&mut db.conn).unwrap();
+    }
+
+    let paged = pages
+        .pages
+        .iter()
+        .flatten()
+        .flat_map(|cluster| &cluster.articles)
+        .map(|article| article.id)
+        .collect::<Vec<_>>();
+    assert_eq!(paged, expected);
+}
@@ -0,0 +1,45 @@
+//! Purging articles that fall outside the retention policy.
+
+mod support;
+
+use chrono::Utc;
+use rss_reader_core::{
+    ingest::subscribe,
+    repository::load_channel_items,
+    retention::{purge, save_global_policy, RetentionPolicy},
+    tags::{tag_item, USER_SOURCE},
+};
+use support::{fixture, FixtureServer, Route, TempDatabase};
+
+#[test]
+fn feed_categories_dont_keep_items_but_user_tags_do() {
+    let server = FixtureServer::start();
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+    let update = subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now()).unwrap();
+    let items = load_channel_items(&mut db.conn, update.feed_id).unwrap();
+    let second = items
+        .iter()
+        .find(|item| item.title.as_deref() == Some("Second post"))
+        .unwrap();
+    tag_item(&mut db.conn, second.id, "keep", USER_SOURCE, Utc::now()).unwrap();
+    save_global_policy(
+        &mut db.conn,
+        RetentionPolicy {
+            keep_count: Some(1),
+            keep_days: None,
+        },
+    )
+    .unwrap();
+
+    // the first post only has the feed's "news" category
+    let purged = purge(&mut db.conn, Utc::now()).unwrap();
+
+    assert_eq!(purged, 1);
+    let titles = load_channel_items(&mut db.conn, update.feed_id)
+        .unwrap()
+        .into_iter()
+        .filter_map(|item| item.title)
+        .collect::<Vec<_>>();
+    assert_eq!(titles, ["Third post", "Second post"]);
+}
@@ -0,0 +1,249 @@
+//! Rules acting on stored and newly fetched articles.
+
+mod support;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use rss_reader_core::{
+    ingest::subscribe,
+    rules::{apply_retroactively, dry_run, load_rules, save_rule, CompiledRule, NewRuleRecord},
+    schema::feed_items,
+    tags::load_item_tags,
+    FeedItemRecord,
+};
+use support::{fixture, FixtureServer, Route, TempDatabase};
+
+const LAUNCH_FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+  <channel>
+    <title>Launch News</title>
+    <link>{base}/</link>
+    <description>Served by the test harness</description>
+    <item>
+      <title>Launch day</title>
+      <link>{base}/launch</link>
+      <author>ada@example.com (Ada Lovelace)</author>
+      <description>The rocket goes up.</description>
+      <category>space</category>
+    </item>
+    <item>
+      <title>Weekly links</title>
+      <link>{base}/links</link>
+      <author>bob@example.com (Bob)</author>
+      <description>Links about Rust.</description>
+    </item>
+    <item>
+      <title>Sponsored: buy now</title>
+      <link>{base}/sponsored</link>
+      <author>ada@example.com (Ada Lovelace)</author>
+      <description>An advert.</description>
+      <category>ads</category>
+    </item>
+  </channel>
+</rss>"#;
+
+/// A rule that matches everything until conditions are added.
+fn rule(action: &str, action_value: Option<&str>) -> NewRuleRecord {
+    NewRuleRecord {
+        name: action.to_string(),
+        enabled: true,
+        channel_id: None,
+        title_pattern: None,
+        description_pattern: None,
+        author: None,
+        tag: None,
+        action: action.to_string(),
+        action_value: action_value.map(str::to_string),
+        create_date: Utc::now(),
+        update_date: Utc::now(),
+    }
+}
+
+fn compile(conn: &mut SqliteConnection, new_rule: NewRuleRecord) -> CompiledRule {
+    let rule_id = save_rule(conn, None, new_rule).unwrap();
+    let record = load_rules(conn)
+        .unwrap()
+        .into_iter()
+        .find(|record| record.id == rule_id)
+        .unwrap();
+    CompiledRule::new(record).unwrap()
+}
+
+/// Every stored item, hidden ones included, in the order they were stored.
+fn all_items(conn: &mut SqliteConnection) -> Vec<FeedItemRecord> {
+    feed_items::table
+        .order(feed_items::id.asc())
+        .select(FeedItemRecord::as_select())
+        .load(conn)
+        .unwrap()
+}
+
+fn titles(items: &[FeedItemRecord]) -> Vec<&str> {
+    items
+        .iter()
+        .filter_map(|item| item.title.as_deref())
+        .collect()
+}
+
+/// Subscribes to the launch feed and the fixture feed, returning the id of
+/// the launch feed.
+fn subscribe_both(server: &FixtureServer, db: &mut TempDatabase) -> i32 {
+    let launch = subscribe(&mut db.conn, &server.url("/launch.xml"), Utc::now()).unwrap();
+    subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now()).unwrap();
+    launch.feed_id
+}
+
+fn serve_both() -> FixtureServer {
+    let server = FixtureServer::start();
+    server.serve("/launch.xml", Route::feed(LAUNCH_FEED.to_string()));
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    server
+}
+
+#[test]
+fn each_condition_narrows_what_a_rule_matches() {
+    let server = serve_both();
+    let mut db = TempDatabase::new();
+    let launch_id = subscribe_both(&server, &mut db);
+    let before = all_items(&mut db.conn);
+
+    let cases = [
+        (
+            NewRuleRecord {
+                channel_id: Some(launch_id),
+                ..rule("star", None)
+            },
+            vec!["Launch day", "Weekly links", "Sponsored: buy now"],
+        ),
+        (
+            NewRuleRecord {
+                title_pattern: Some("^sponsored".to_string()),
+                ..rule("star", None)
+            },
+            vec!["Sponsored: buy now"],
+        ),
+        (
+            NewRuleRecord {
+                description_pattern: Some(r"\brust\b".to_string()),
+                ..rule("star", None)
+            },
+            vec!["Weekly links"],
+        ),
+        (
+            NewRuleRecord {
+                author: Some("ADA".to_string()),
+                ..rule("star", None)
+            },
+            vec!["Launch day", "Sponsored: buy now"],
+        ),
+        (
+            NewRuleRecord {
+                tag: Some("Space".to_string()),
+                ..rule("star", None)
+            },
+            vec!["Launch day"],
+        ),
+        (
+            NewRuleRecord {
+                author: Some("ada".to_string()),
+                tag: Some("ads".to_string()),
+                ..rule("star", None)
+            },
+            vec!["Sponsored: buy now"],
+        ),
+    ];
+    for (new_rule, expected) in cases {
+        let description = format!("{new_rule:?}");
+        let compiled = compile(&mut db.conn, new_rule);
+        let matched = dry_run(&mut db.conn, &compiled).unwrap();
+        assert_eq!(titles(&matched), expected, "{description}");
+    }
+
+    // a dry run only looks
+    assert_eq!(all_items(&mut db.conn), before);
+}
+
+#[test]
+fn each_action_only_touches_matching_items() {
+    let server = serve_both();
+    let actions = [
+        ("mark_read", None),
+        ("star", None),
+        ("tag", Some("launches")),
+        ("hide", None),
+        ("priority", Some("5")),
+    ];
+    for (action, value) in actions {
+        let mut db = TempDatabase::new();
+        subscribe_both(&server, &mut db);
+        let before = all_items(&mut db.conn);
+        let compiled = compile(
+            &mut db.conn,
+            NewRuleRecord {
+                title_pattern: Some("^launch".to_string()),
+                ..rule(action, value)
+            },
+        );
+
+        assert_eq!(
+            apply_retroactively(&mut db.conn, &compiled).unwrap(),
+            1,
+            "{action}"
+        );
+
+        let after = all_items(&mut db.conn);
+        for (old, new) in before.iter().zip(&after) {
+            if old.title.as_deref() != Some("Launch day") {
+                assert_eq!(old, new, "{action}");
+                assert!(
+                    load_item_tags(&mut db.conn, new.id)
+                        .unwrap()
+                        .iter()
+                        .all(|tag| tag.name != "launches"),
+                    "{action}"
+                );
+                continue;
+            }
+            let tags = load_item_tags(&mut db.conn, new.id).unwrap();
+            let changed = match action {
+                "mark_read" => new.is_read,
+                "star" => new.is_starred,
+                "tag" => tags.iter().any(|tag| tag.name == "launches"),
+                "hide" => new.is_hidden,
+                _ => new.priority == 5,
+            };
+            assert!(changed, "{action}");
+        }
+    }
+}
+
+#[test]
+fn enabled_rules_run_on_items_as_they_arrive() {
+    let server = serve_both();
+    let mut db = TempDatabase::new();
+    compile(
+        &mut db.conn,
+        NewRuleRecord {
+            tag: Some("ads".to_string()),
+            ..rule("hide", None)
+        },
+    );
+    compile(
+        &mut db.conn,
+        NewRuleRecord {
+            enabled: false,
+            ..rule("star", None)
+        },
+    );
+
+    subscribe_both(&server, &mut db);
+
+    let items = all_items(&mut db.conn);
+    let hidden = items
+        .iter()
+        .filter(|item| item.is_hidden)
+        .collect::<Vec<_>>();
+    assert_eq!(hidden.len(), 1);
+    assert_eq!(hidden[0].title.as_deref(), Some("Sponsored: buy now"));
+    assert!(items.iter().all(|item| !item.is_starred));
+}
@@ -0,0 +1,26 @@
+//! What's left of feed HTML once it's cleaned for the reader.
+
+use rss_reader_core::sanitize::clean_html;
+
+#[test]
+fn scripts_and_event_handlers_are_removed() {
+    let html = r#"<p>Hello</p><script>alert("hi")</script><img src="https://example.com/a.png" onerror="alert(1)">"#;
+    let cleaned = clean_html(html);
+
+    assert!(cleaned.contains("<p>Hello</p>"));
+    assert!(cleaned.contains(r#"src="https://example.com/a.png""#));
+    assert!(!cleaned.contains("script"));
+    assert!(!cleaned.contains("alert"));
+    assert!(!cleaned.contains("onerror"));
+}
+
+#[test]
+fn frames_and_javascript_links_are_removed() {
+    let html = r#"<iframe src="https://example.com/"></iframe><a href="javascript:alert(1)">click</a><a href="https://example.com/post">post</a>"#;
+    let cleaned = clean_html(html);
+
+    assert!(!cleaned.contains("iframe"));
+    assert!(!cleaned.contains("javascript:"));
+    assert!(cleaned.contains(r#"href="https://example.com/post""#));
+    assert!(cleaned.contains(">click</a>"));
+}
@@ -0,0 +1,96 @@
+//! Searching the stored articles.
+
+mod support;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use rss_reader_core::{
+    ingest::subscribe,
+    repository::{load_channel_items, set_item_read},
+    schema::feed_items,
+    search::{count, count_unread, search, SearchQuery},
+};
+use support::{fixture, FixtureServer, Route, TempDatabase};
+
+#[test]
+fn counts_agree_with_the_results() {
+    let server = FixtureServer::start();
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+    let update = subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now()).unwrap();
+    let first = load_channel_items(&mut db.conn, update.feed_id)
+        .unwrap()
+        .into_iter()
+        .find(|item| item.title.as_deref() == Some("First post"))
+        .unwrap();
+    set_item_read(&mut db.conn, first.id, true).unwrap();
+
+    for text in [
+        "post",
+        "second",
+        "is:unread",
+        "feed:fixture",
+        "\"unbalanced",
+    ] {
+        let query = SearchQuery::parse(text);
+        let results = search(&mut db.conn, &query).unwrap();
+        assert_eq!(
+            count(&mut db.conn, &query).unwrap(),
+            results.len() as i64,
+            "{text}"
+        );
+    }
+    assert_eq!(count_unread(&mut db.conn, "post").unwrap(), 2);
+}
+
+#[test]
+fn hidden_articles_are_not_found() {
+    let server = FixtureServer::start();
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+    let update = subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now()).unwrap();
+    let second = load_channel_items(&mut db.conn, update.feed_id)
+        .unwrap()
+        .into_iter()
+        .find(|item| item.title.as_deref() == Some("Second post"))
+        .unwrap();
+
+    // as a rule hiding it would
+    diesel::update(feed_items::table.find(second.id))
+        .set(feed_items::is_hidden.eq(true))
+        .execute(&mut db.conn)
+        .unwrap();
+
+    for text in ["second", ""] {
+        let results = search(&mut db.conn, &SearchQuery::parse(text)).unwrap();
+        assert!(
+            results.iter().all(|result| result.id != second.id),
+            "{text:?}"
+        );
+    }
+}
+
+#[test]
+fn saved_search_counts_leave_hidden_articles_out() {
+    let server = FixtureServer::start();
+    server.serve("/rss.xml", Route::feed(fixture("rss.xml")));
+    let mut db = TempDatabase::new();
+    let update = subscribe(&mut db.conn, &server.url("/rss.xml"), Utc::now()).unwrap();
+    let items = load_channel_items(&mut db.conn, update.feed_id).unwrap();
+    assert_eq!(
+        count_unread(&mut db.conn, "post").unwrap(),
+        items.len() as i64
+    );
+
+    diesel::update(feed_items::table.find(items[0].id))
+        .set(feed_items::is_hidden.eq(true))
+        .execute(&mut db.conn)
+        .unwrap();
+
+    assert_eq!(
+        count_unread(&mut db.conn, "post").unwrap(),
+        items.len() as i64 - 1
+    );
+    let query = SearchQuery::parse("post");
+    assert_eq!(count(&mut db.conn, &query).unwrap(), items.len() as i64 - 1);
+}
@@ -0,0 +1,559 @@
+//! Runs the same checks against the SQLite and in-memory storage backends,
+//! so tests written against [`MemoryStorage`] say something about the app.
+
+use std::{collections::HashMap, path::Path};
+
+use chrono::{TimeZone, Utc};
+use rss::Channel;
+use rss_reader_core::{
+    database, dates,
+    feeds::FetchInfo,
+    ingest::FetchedFeed,
+    pagination::{ChannelPages, PAGE_SIZE},
+    retention::RetentionPolicy,
+    rules::{CompiledRule, NewRuleRecord},
+    search::SearchQuery,
+    storage::{MemoryStorage, Storage},
+    tags::USER_SOURCE,
+    Error, NewFeedItemRecord, NewFeedRecord,
+};
+use url::Url;
+
+fn new_feed(feed_url: &str, name: &str) -> NewFeedRecord {
+    NewFeedRecord::new(
+        "example.com".to_string(),
+        feed_url.to_string(),
+        name.to_string(),
+        Utc::now(),
+    )
+}
+
+fn new_item(channel_id: i32, title: &str, day: Option<u32>) -> NewFeedItemRecord {
+    let published = day.map(|day| Utc.with_ymd_and_hms(2025, 5, day, 12, 0, 0).unwrap());
+    NewFeedItemRecord {
+        title: Some(title.to_string()),
+        url: Some(format!("https://example.com/{title}")),
+        pub_date: published.map(|date| date.to_rfc2822()),
+        published_at: published.map(dates::to_stored),
+        ..NewFeedItemRecord::new(channel_id, Utc::now())
+    }
+}
+
+fn new_rule(title_pattern: &str, action: &str) -> NewRuleRecord {
+    NewRuleRecord {
+        name: format!("{action} {title_pattern}"),
+        enabled: true,
+        channel_id: None,
+        title_pattern: Some(title_pattern.to_string()),
+        description_pattern: None,
+        author: None,
+        tag: None,
+        action: action.to_string(),
+        action_value: None,
+        create_date: Utc::now(),
+        update_date: Utc::now(),
+    }
+}
+
+/// What fetching the fixture `name` would have found.
+fn fetched(name: &str) -> FetchedFeed {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    let content = std::fs::read(path).unwrap();
+    FetchedFeed {
+        url: Url::parse("https://example.com/rss.xml").unwrap(),
+        channel: Channel::read_from(&content[..]).unwrap(),
+        fetch_info: FetchInfo::default(),
+        favicon: None,
+        redirects: HashMap::new(),
+    }
+}
+
+fn sqlite() -> impl Storage {
+    database::establish(Path::new(":memory:")).unwrap()
+}
+
+fn titles(storage: &mut impl Storage, feed_id: i32) -> Vec<String> {
+    storage
+        .load_channel_items(feed_id)
+        .unwrap()
+        .into_iter()
+        .filter_map(|item| item.title)
+        .collect()
+}
+
+fn feeds_keep_their_order_and_addresses_are_unique(storage: &mut impl Storage) {
+    let first = storage
+        .insert_feed(new_feed("https://example.com/a.xml", "A"))
+        .unwrap();
+    let second = storage
+        .insert_feed(new_feed("https://example.com/b.xml", "B"))
+        .unwrap();
+
+    let names = storage
+        .load_feeds()
+        .unwrap()
+        .into_iter()
+        .map(|feed| (feed.id, feed.name))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![(first, "A".to_string()), (second, "B".to_string())]
+    );
+
+    let duplicate = storage.insert_feed(new_feed("https://example.com/a.xml", "Again"));
+    assert!(matches!(duplicate, Err(Error::AlreadySubscribed(_))));
+    assert!(matches!(
+        storage.load_feed(second + 100),
+        Err(Error::FeedNotFound(_))
+    ));
+}
+
+fn renaming_trims_and_rejects_empty_names(storage: &mut impl Storage) {
+    let feed_id = storage
+        .insert_feed(new_feed("https://example.com/a.xml", "A"))
+        .unwrap();
+
+    storage.rename_feed(feed_id, "  Renamed ").unwrap();
+    assert_eq!(storage.load_feed(feed_id).unwrap().name, "Renamed");
+    assert!(matches!(
+        storage.rename_feed(feed_id, "   "),
+        Err(Error::Invalid(_))
+    ));
+}
+
+fn items_sort_newest_first_with_undated_last(storage: &mut impl Storage) {
+    let feed_id = storage
+        .insert_feed(new_feed("https://example.com/a.xml", "A"))
+        .unwrap();
+    storage
+        .insert_item(new_item(feed_id, "undated", None))
+        .unwrap();
+    storage
+        .insert_item(new_item(feed_id, "old", Some(1)))
+        .unwrap();
+    storage
+        .insert_item(new_item(feed_id, "new", Some(20)))
+        .unwrap();
+
+    assert_eq!(titles(storage, feed_id), ["new", "old", "undated"]);
+}
+
+fn read_state_is_counted_per_feed(storage: &mut impl Storage) {
+    let feed_id = storage
+        .insert_feed(new_feed("https://example.com/a.xml", "A"))
+        .unwrap();
+    let other_id = storage
+        .insert_feed(new_feed("https://example.com/b.xml", "B"))
+        .unwrap();
+    let first = storage
+        .insert_item(new_item(feed_id, "first", Some(1)))
+        .unwrap();
+    storage
+        .insert_item(new_item(feed_id, "second", Some(2)))
+        .unwrap();
+    storage
+        .insert_item(new_item(other_id, "elsewhere", Some(3)))
+        .unwrap();
+
+    assert_eq!(storage.unread_count(feed_id).unwrap(), 2);
+    storage.set_item_read(first, true).unwrap();
+    assert_eq!(storage.unread_count(feed_id).unwrap(), 1);
+    assert_eq!(storage.unread_count(other_id).unwrap(), 1);
+
+    storage.set_item_read(first, false).unwrap();
+    assert_eq!(storage.unread_count(feed_id).unwrap(), 2);
+}
+
+fn deleting_a_feed_can_keep_starred_items(storage: &mut impl Storage) {
+    let kept_id = storage
+        .insert_feed(new_feed("https://example.com/a.xml", "A"))
+        .unwrap();
+    let starred = storage
+        .insert_item(new_item(kept_id, "starred", Some(1)))
+        .unwrap();
+    storage
+        .insert_item(new_item(kept_id, "plain", Some(2)))
+        .unwrap();
+    storage.set_item_starred(starred, true).unwrap();
+
+    storage.delete_feed(kept_id, true).unwrap();
+    assert!(storage.load_feed(kept_id).unwrap().archived);
+    assert_eq!(titles(storage, kept_id), ["starred"]);
+
+    let gone_id = storage
+        .insert_feed(new_feed("https://example.com/b.xml", "B"))
+        .unwrap();
+    let starred = storage
+        .insert_item(new_item(gone_id, "starred", Some(1)))
+        .unwrap();
+    storage.set_item_starred(starred, true).unwrap();
+
+    storage.delete_feed(gone_id, false).unwrap();
+    assert!(matches!(
+        storage.load_feed(gone_id),
+        Err(Error::FeedNotFound(_))
+    ));
+    assert!(titles(storage, gone_id).is_empty());
+}
+
+fn archived_feeds_can_be_subscribed_to_again(storage: &mut impl Storage) {
+    let feed_id = storage
+        .insert_feed(new_feed("https://example.com/a.xml", "A"))
+        .unwrap();
+    let starred = storage
+        .insert_item(new_item(feed_id, "starred", Some(1)))
+        .unwrap();
+    storage.set_item_starred(starred, true).unwrap();
+    storage.delete_feed(feed_id, true).unwrap();
+
+    let again = storage
+        .insert_feed(new_feed("https://example.com/a.xml", "A again"))
+        .unwrap();
+
+    assert_eq!(again, feed_id);
+    let feed = storage.load_feed(feed_id).unwrap();
+    assert!(!feed.archived);
+    assert_eq!(feed.name, "A again");
+    assert_eq!(titles(storage, feed_id), ["starred"]);
+    let duplicate = storage.insert_feed(new_feed("https://example.com/a.xml", "A"));
+    assert!(matches!(duplicate, Err(Error::AlreadySubscribed(_))));
+}
+
+fn a_feed_pages_through_all_of_its_items(storage: &mut impl Storage) {
+    let feed_id = storage
+        .insert_feed(new_feed("https://example.com/a.xml", "A"))
+        .unwrap();
+    let other_id = storage
+        .insert_feed(new_feed("https://example.com/b.xml", "B"))
+        .unwrap();
+    // runs of items sharing a day, and some without one, across page ends
+    for number in 0..(2 * PAGE_SIZE as u32 + 50) {
+        let day = (number % 4 != 0).then_some(number / 20 + 1);
+        storage
+            .insert_item(new_item(feed_id, &format!("item {number}"), day))
+            .unwrap();
+    }
+    storage
+        .insert_item(new_item(other_id, "elsewhere", Some(1)))
+        .unwrap();
+
+    let mut pages = ChannelPages::first(storage, feed_id).unwrap();
+    while pages.has_more() {
+        pages.load_next(storage).unwrap();
+    }
+
+    assert_eq!(pages.pages.len(), 3);
+    let paged = pages.pages.into_iter().flatten().collect::<Vec<_>>();
+    assert_eq!(paged, storage.load_channel_items(feed_id).unwrap());
+}
+
+fn settings_can_be_replaced_and_removed(storage: &mut impl Storage) {
+    assert_eq!(storage.get_setting("theme").unwrap(), None);
+
+    storage.set_setting("theme", Some("dark")).unwrap();
+    storage.set_setting("theme", Some("light")).unwrap();
+    assert_eq!(
+        storage.get_setting("theme").unwrap().as_deref(),
+        Some("light")
+    );
+
+    storage.set_setting("theme", None).unwrap();
+    assert_eq!(storage.get_setting("theme").unwrap(), None);
+}
+
+fn tags_are_shared_by_name_and_dropped_once_unused(storage: &mut impl Storage) {
+    let feed_id = storage
+        .insert_feed(new_feed("https://example.com/a.xml", "A"))
+        .unwrap();
+    let first = storage
+        .insert_item(new_item(feed_id, "first", Some(1)))
+        .unwrap();
+    let second = storage
+        .insert_item(new_item(feed_id, "second", Some(2)))
+        .unwrap();
+
+    storage.tag_item(first, "Rust", USER_SOURCE).unwrap();
+    storage.tag_item(second, " rust ", USER_SOURCE).unwrap();
+    storage.tag_item(second, "  ", USER_SOURCE).unwrap();
+
+    let tags = storage.load_tags().unwrap();
+    assert_eq!(tags.len(), 1);
+    let (tag, count) = &tags[0];
+    assert_eq!((tag.name.as_str(), *count), ("Rust", 2));
+    let item_tags = storage.load_item_tags(second).unwrap();
+    assert_eq!(
+        item_tags
+            .iter()
+            .map(|tag| tag.name.as_str())
+            .collect::<Vec<_>>(),
+        ["Rust"]
+    );
+    assert_eq!(storage.load_tag_feed(tag.id).unwrap().articles.len(), 2);
+
+    storage.untag_item(first, tag.id).unwrap();
+    storage.untag_item(second, tag.id).unwrap();
+    assert!(storage.load_tags().unwrap().is_empty());
+}
+
+fn mute_terms_count_the_articles_they_hide(storage: &mut impl Storage) {
+    let feed_id = storage
+        .insert_feed(new_feed("https://example.com/a.xml", "A"))
+        .unwrap();
+    storage
+        .insert_item(new_item(feed_id, "Sponsored post", Some(1)))
+        .unwrap();
+    storage
+        .insert_item(new_item(feed_id, "Regular post", Some(2)))
+        .unwrap();
+
+    assert!(matches!(
+        storage.add_mute_term(None, "  ", false),
+        Err(Error::Invalid(_))
+    ));
+    assert!(matches!(
+        storage.add_mute_term(None, "(", true),
+        Err(Error::Invalid(_))
+    ));
+    storage.add_mute_term(None, " sponsored ", false).unwrap();
+    storage
+        .add_mute_term(Some(feed_id), "unused", false)
+        .unwrap();
+
+    let terms = storage.load_mute_terms().unwrap();
+    assert_eq!(
+        terms
+            .iter()
+            .map(|term| term.term.as_str())
+            .collect::<Vec<_>>(),
+        ["sponsored", "unused"]
+    );
+    assert_eq!(
+        storage.hidden_counts().unwrap(),
+        HashMap::from([(terms[0].id, 1)])
+    );
+
+    storage.delete_mute_term(terms[0].id).unwrap();
+    assert!(storage.hidden_counts().unwrap().is_empty());
+}
+
+fn searches_find_articles_across_feeds(storage: &mut impl Storage) {
+    let feed_id = storage
+        .insert_feed(new_feed("https://example.com/a.xml", "Alpha"))
+        .unwrap();
+    let other_id = storage
+        .insert_feed(new_feed("https://example.com/b.xml", "Beta"))
+        .unwrap();
+    let read = storage
+        .insert_item(new_item(feed_id, "rust release", Some(1)))
+        .unwrap();
+    storage
+        .insert_item(new_item(other_id, "go release", Some(2)))
+        .unwrap();
+    storage
+        .insert_item(new_item(other_id, "unrelated", Some(3)))
+        .unwrap();
+    storage.set_item_read(read, true).unwrap();
+
+    // SQLite ranks text matches by relevance, so only what's found is compared
+    let found = |storage: &mut dyn Storage, query: &str| {
+        let mut titles = storage
+            .search(&SearchQuery::parse(query))
+            .unwrap()
+            .into_iter()
+            .filter_map(|result| result.title)
+            .collect::<Vec<_>>();
+        titles.sort();
+        titles
+    };
+    assert_eq!(found(storage, "release"), ["go release", "rust release"]);
+    assert_eq!(found(storage, "release feed:alpha"), ["rust release"]);
+    assert_eq!(found(storage, "release is:unread"), ["go release"]);
+    assert_eq!(storage.count_unread("release").unwrap(), 1);
+
+    let saved = storage.save_search(" Releases ", "release").unwrap();
+    storage.save_search("Alpha", "feed:alpha").unwrap();
+    let names = storage
+        .load_saved_searches()
+        .unwrap()
+        .into_iter()
+        .map(|saved_search| saved_search.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Alpha", "Releases"]);
+    storage.delete_saved_search(saved).unwrap();
+    assert_eq!(storage.load_saved_searches().unwrap().len(), 1);
+}
+
+fn rules_act_on_the_articles_they_match(storage: &mut impl Storage) {
+    let feed_id = storage
+        .insert_feed(new_feed("https://example.com/a.xml", "A"))
+        .unwrap();
+    storage
+        .insert_item(new_item(feed_id, "sponsored post", Some(1)))
+        .unwrap();
+    storage
+        .insert_item(new_item(feed_id, "regular post", Some(2)))
+        .unwrap();
+
+    let rule_id = storage
+        .save_rule(None, new_rule("sponsored", "hide"))
+        .unwrap();
+    let rule = storage.load_rules().unwrap().remove(0);
+    let compiled = CompiledRule::new(rule).unwrap();
+    assert_eq!(storage.dry_run(&compiled).unwrap().len(), 1);
+    assert_eq!(titles(storage, feed_id).len(), 2);
+
+    assert_eq!(storage.apply_retroactively(&compiled).unwrap(), 1);
+    assert_eq!(titles(storage, feed_id), ["regular post"]);
+    assert!(storage
+        .search(&SearchQuery::parse("sponsored"))
+        .unwrap()
+        .is_empty());
+
+    storage
+        .save_rule(Some(rule_id), new_rule("regular", "star"))
+        .unwrap();
+    storage.set_rule_enabled(rule_id, false).unwrap();
+    let rules = storage.load_rules().unwrap();
+    assert_eq!(rules.len(), 1);
+    assert_eq!(
+        (rules[0].id, rules[0].action.as_str(), rules[0].enabled),
+        (rule_id, "star", false)
+    );
+    storage.delete_rule(rule_id).unwrap();
+    assert!(storage.load_rules().unwrap().is_empty());
+}
+
+fn compacting_purges_all_but_kept_articles(storage: &mut impl Storage) {
+    let feed_id = storage
+        .insert_feed(new_feed("https://example.com/a.xml", "A"))
+        .unwrap();
+    let starred = storage
+        .insert_item(new_item(feed_id, "oldest", Some(1)))
+        .unwrap();
+    storage
+        .insert_item(new_item(feed_id, "older", Some(2)))
+        .unwrap();
+    storage
+        .insert_item(new_item(feed_id, "newest", Some(3)))
+        .unwrap();
+    storage.set_item_starred(starred, true).unwrap();
+
+    let policy = RetentionPolicy {
+        keep_count: Some(1),
+        keep_days: None,
+    };
+    storage.save_global_policy(policy).unwrap();
+    assert_eq!(storage.load_global_policy().unwrap(), policy);
+
+    assert_eq!(storage.compact(Utc::now()).unwrap().rows_purged, 1);
+    assert_eq!(titles(storage, feed_id), ["newest", "oldest"]);
+
+    // a feed's own policy wins over the global one
+    storage
+        .set_feed_policy(
+            feed_id,
+            RetentionPolicy {
+                keep_count: Some(5),
+                keep_days: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(storage.compact(Utc::now()).unwrap().rows_purged, 0);
+}
+
+fn refreshing_updates_items_and_keeps_read_state(storage: &mut impl Storage) {
+    let subscribed = storage
+        .store_subscription(fetched("rss.xml"), Utc::now())
+        .unwrap();
+    let feed_id = subscribed.feed_id;
+    assert_eq!(subscribed.feed_name, "Fixture News");
+    assert_eq!(subscribed.summary.inserted, 3);
+    assert_eq!(
+        titles(storage, feed_id),
+        ["Third post", "Second post", "First post"]
+    );
+    assert!(storage
+        .load_feed(feed_id)
+        .unwrap()
+        .last_refresh_date
+        .is_some());
+    let items = storage.load_channel_items(feed_id).unwrap();
+    let item_tags = storage.load_item_tags(items[2].id).unwrap();
+    assert_eq!(
+        item_tags
+            .iter()
+            .map(|tag| tag.name.as_str())
+            .collect::<Vec<_>>(),
+        ["news"]
+    );
+    storage.set_item_read(items[1].id, true).unwrap();
+
+    let source = storage.refresh_source(feed_id).unwrap();
+    let refreshed = storage
+        .store_refresh(&source, Ok(Some(fetched("rss_changed.xml"))), Utc::now())
+        .unwrap();
+
+    let summary = refreshed.summary;
+    assert_eq!(
+        (summary.inserted, summary.updated, summary.unchanged),
+        (1, 1, 1)
+    );
+    let items = storage.load_channel_items(feed_id).unwrap();
+    let corrected = items
+        .iter()
+        .find(|item| item.title.as_deref() == Some("Second post, corrected"));
+    assert!(corrected.unwrap().is_read);
+
+    let failed = storage.store_refresh(
+        &source,
+        Err(Error::Invalid("broken".to_string())),
+        Utc::now(),
+    );
+    assert!(failed.is_err());
+    assert_eq!(
+        storage.load_feed(feed_id).unwrap().last_error.as_deref(),
+        Some("broken")
+    );
+    assert_eq!(titles(storage, feed_id).len(), 4);
+}
+
+macro_rules! on_both_backends {
+    ($($check:ident),* $(,)?) => {
+        mod memory {
+            $(
+                #[test]
+                fn $check() {
+                    super::$check(&mut super::MemoryStorage::new());
+                }
+            )*
+        }
+
+        mod sqlite {
+            $(
+                #[test]
+                fn $check() {
+                    super::$check(&mut super::sqlite());
+                }
+            )*
+        }
+    };
+}
+
+on_both_backends!(
+    feeds_keep_their_order_and_addresses_are_unique,
+    renaming_trims_and_rejects_empty_names,
+    items_sort_newest_first_with_undated_last,
+    read_state_is_counted_per_feed,
+    deleting_a_feed_can_keep_starred_items,
+    archived_feeds_can_be_subscribed_to_again,
+    a_feed_pages_through_all_of_its_items,
+    settings_can_be_replaced_and_removed,
+    tags_are_shared_by_name_and_dropped_once_unused,
+    mute_terms_count_the_articles_they_hide,
+    searches_find_articles_across_feeds,
+    rules_act_on_the_articles_they_match,
+    compacting_purges_all_but_kept_articles,
+    refreshing_updates_items_and_keeps_read_state,
+);
@@ -0,0 +1,266 @@
+//! A local HTTP server handing out fixture feeds, and a throwaway database to
+//! ingest them into.
+
+// every test crate uses a different part of it
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read, Write},
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use diesel::SqliteConnection;
+use flate2::{write::GzEncoder, Compression};
+use rss_reader_core::database;
+use tempfile::TempDir;
+use tiny_http::{Header, Request, Response, Server, StatusCode};
+
+/// Reads a feed from `tests/fixtures`. `{base}` in it stands for the address
+/// of the server it's served from.
+pub fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    fs::read_to_string(&path).unwrap_or_else(|error| panic!("couldn't read {path}: {error}"))
+}
+
+/// An RSS feed with `count` items, for checking that big documents go
+/// through.
+pub fn huge_feed(count: usize) -> String {
+    let mut feed = String::from(
+        "<?xml version=\"1.0\"?><rss version=\"2.0\"><channel>\
+         <title>Huge Feed</title><link>{base}/</link><description>Lots</description>",
+    );
+    for number in 0..count {
+        feed.push_str(&format!(
+            "<item><title>Item {number}</title><link>{{base}}/huge/{number}</link>\
+             <description>{}</description>\
+             <pubDate>Thu, 01 May 2025 09:{:02}:{:02} +0000</pubDate></item>",
+            "Filler text to give every item some weight. ".repeat(10),
+            number / 60 % 60,
+            number % 60,
+        ));
+    }
+    feed.push_str("</channel></rss>");
+    feed
+}
+
+/// How the server answers a path.
+#[derive(Clone, Debug)]
+pub enum Route {
+    /// The document, answered with 304 when the request's `If-None-Match`
+    /// matches `etag`.
+    Feed { body: String, etag: Option<String> },
+    /// A permanent redirect to another path of the server.
+    Redirect(String),
+    /// The document, trickled out in small pieces with a pause between them.
+    Slow { body: String, pause: Duration },
+    /// The document, gzip compressed.
+    Gzip(String),
+}
+
+impl Route {
+    pub fn feed(body: String) -> Self {
+        Route::Feed { body, etag: None }
+    }
+}
+
+/// What the server was asked for, in order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedRequest {
+    pub path: String,
+    pub if_none_match: Option<String>,
+}
+
+pub struct FixtureServer {
+    server: Arc<Server>,
+    base: String,
+    routes: Arc<Mutex<HashMap<String, Route>>>,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    listener: Option<JoinHandle<()>>,
+}
+
+impl FixtureServer {
+    pub fn start() -> Self {
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let port = server.server_addr().to_ip().unwrap().port();
+        let base = format!("http://127.0.0.1:{port}");
+        let routes = Arc::new(Mutex::new(HashMap::new()));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let listener = {
+            let server = Arc::clone(&server);
+            let base = base.clone();
+            let routes = Arc::clone(&routes);
+            let requests = Arc::clone(&requests);
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    let path = request.url().to_string();
+                    requests.lock().unwrap().push(RecordedRequest {
+                        path: path.clone(),
+                        if_none_match: header(&request, "If-None-Match"),
+                    });
+                    let route = routes.lock().unwrap().get(&path).cloned();
+                    let base = base.clone();
+                    // answered on their own threads so a slow route doesn't
+                    // hold up the others. The client may have hung up by the
+                    // time the answer is ready, which is fine.
+                    thread::spawn(move || {
+                        let _ = respond(request, route, &base);
+                    });
+                }
+            })
+        };
+
+        FixtureServer {
+            server,
+            base,
+            routes,
+            requests,
+            listener: Some(listener),
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base)
+    }
+
+    /// Sets how `path` is answered from now on, replacing what it served
+    /// before.
+    pub fn serve(&self, path: &str, route: Route) {
+        self.routes.lock().unwrap().insert(path.to_string(), route);
+    }
+
+    pub fn requests_for(&self, path: &str) -> Vec<RecordedRequest> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.path == path)
+            .cloned()
+            .collect()
+    }
+}
+
+impl Drop for FixtureServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(listener) = self.listener.take() {
+            let _ = listener.join();
+        }
+    }
+}
+
+fn header(request: &Request, name: &'static str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.to_string())
+}
+
+fn with_header<R: Read>(response: Response<R>, name: &str, value: &str) -> Response<R> {
+    response.with_header(Header::from_bytes(name, value).unwrap())
+}
+
+fn respond(request: Request, route: Option<Route>, base: &str) -> io::Result<()> {
+    let fill = |body: &str| body.replace("{base}", base);
+
+    match route {
+        None => request.respond(Response::from_string("not found").with_status_code(404)),
+        Some(Route::Feed { body, etag }) => {
+            let cached = etag.is_some() && header(&request, "If-None-Match") == etag;
+            if cached {
+                request.respond(Response::empty(StatusCode(304)))
+            } else {
+                let mut response = with_header(
+                    Response::from_string(fill(&body)),
+                    "Content-Type",
+                    "application/xml",
+                );
+                if let Some(etag) = etag {
+                    response = with_header(response, "ETag", &etag);
+                }
+                request.respond(response)
+            }
+        }
+        Some(Route::Redirect(path)) => request.respond(with_header(
+            Response::empty(StatusCode(301)),
+            "Location",
+            &format!("{base}{path}"),
+        )),
+        Some(Route::Slow { body, pause }) => {
+            let body = fill(&body).into_bytes();
+            let length = body.len();
+            let reader = SlowReader {
+                body,
+                position: 0,
+                pause,
+            };
+            request.respond(Response::new(
+                StatusCode(200),
+                vec![Header::from_bytes("Content-Type", "application/xml").unwrap()],
+                reader,
+                Some(length),
+                None,
+            ))
+        }
+        Some(Route::Gzip(body)) => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(fill(&body).as_bytes()).unwrap();
+            let compressed = encoder.finish().unwrap();
+            let response = with_header(
+                with_header(
+                    Response::from_data(compressed),
+                    "Content-Type",
+                    "application/xml",
+                ),
+                "Content-Encoding",
+                "gzip",
+            );
+            request.respond(response)
+        }
+    }
+}
+
+/// Hands out a body a few hundred bytes at a time.
+struct SlowReader {
+    body: Vec<u8>,
+    position: usize,
+    pause: Duration,
+}
+
+impl Read for SlowReader {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.body.len() {
+            return Ok(0);
+        }
+        thread::sleep(self.pause);
+        let end = (self.position + 256.min(buffer.len())).min(self.body.len());
+        let chunk = &self.body[self.position..end];
+        buffer[..chunk.len()].copy_from_slice(chunk);
+        self.position = end;
+        Ok(chunk.len())
+    }
+}
+
+/// A database file in a temporary directory that's removed when dropped.
+pub struct TempDatabase {
+    pub conn: SqliteConnection,
+    dir: TempDir,
+}
+
+impl TempDatabase {
+    pub fn new() -> Self {
+        let dir = TempDir::new().unwrap();
+        let conn = database::establish(&dir.path().join("feeds.db")).unwrap();
+        TempDatabase { conn, dir }
+    }
+
+    /// Opens the database again, as starting the app does.
+    pub fn reopen(&mut self) {
+        self.conn = database::establish(&self.dir.path().join("feeds.db")).unwrap();
+    }
+}
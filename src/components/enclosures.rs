@@ -0,0 +1,157 @@
+use dioxus::prelude::*;
+use rss_reader_core::EnclosureRecord;
+
+use super::LoadError;
+use crate::{
+    downloads::DownloadQueue,
+    notifications::{use_notifications, Notifications},
+    with_storage,
+};
+
+fn format_duration(seconds: i32) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, (seconds % 3600) / 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+fn format_length(bytes: i64) -> String {
+    let megabytes = bytes as f64 / (1024.0 * 1024.0);
+    format!("{megabytes:.1} MB")
+}
+
+/// Plays the downloaded copy when there is one, otherwise streams the episode.
+fn source(enclosure: &EnclosureRecord) -> String {
+    match &enclosure.local_path {
+        Some(path) => format!("file://{path}"),
+        None => enclosure.url.clone(),
+    }
+}
+
+fn save_playback_position(
+    mut notifications: Signal<Notifications>,
+    enclosure_id: i32,
+    position: f64,
+) {
+    let saved = with_storage(|storage| storage.save_playback_position(enclosure_id, position));
+    notifications
+        .write()
+        .check("Couldn't remember where playback stopped", saved);
+}
+
+async fn current_time(element_id: &str) -> Option<f64> {
+    document::eval(&format!(
+        "return document.getElementById('{element_id}').currentTime;"
+    ))
+    .join::<f64>()
+    .await
+    .ok()
+}
+
+#[component]
+fn AudioPlayer(enclosure: EnclosureRecord) -> Element {
+    let element_id = format!("enclosure-{}", enclosure.id);
+    let enclosure_id = enclosure.id;
+    let mut last_saved = use_signal(|| enclosure.playback_position);
+    let notifications = use_notifications();
+
+    let resume_id = element_id.clone();
+    let timeupdate_id = element_id.clone();
+    let pause_id = element_id.clone();
+
+    rsx! {
+        audio { id: "{element_id}", class: "w-full", controls: true, preload: "metadata",
+            src: source(&enclosure),
+            onloadedmetadata: move |_| {
+                let resume_id = resume_id.clone();
+                async move {
+                    // pick up where the listener left off
+                    let position = *last_saved.peek();
+                    let _ = document::eval(&format!(
+                        "document.getElementById('{resume_id}').currentTime = {position};"
+                    ))
+                    .await;
+                }
+            },
+            ontimeupdate: move |_| {
+                let timeupdate_id = timeupdate_id.clone();
+                async move {
+                    let Some(position) = current_time(&timeupdate_id).await else {
+                        return;
+                    };
+                    // timeupdate fires several times a second, only persist periodically
+                    if (position - *last_saved.peek()).abs() >= 15.0 {
+                        save_playback_position(notifications, enclosure_id, position);
+                        last_saved.set(position);
+                    }
+                }
+            },
+            onpause: move |_| {
+                let pause_id = pause_id.clone();
+                async move {
+                    if let Some(position) = current_time(&pause_id).await {
+                        save_playback_position(notifications, enclosure_id, position);
+                        last_saved.set(position);
+                    }
+                }
+            },
+            onended: move |_| {
+                save_playback_position(notifications, enclosure_id, 0.0);
+                last_saved.set(0.0);
+            },
+        }
+    }
+}
+
+#[component]
+pub fn Enclosures(item_id: i32, download_queue: Signal<DownloadQueue>) -> Element {
+    // queried once per item and again whenever the queue changes, which is
+    // when a download starts or finishes
+    let records = use_memo(use_reactive!(|item_id| {
+        let _ = download_queue.read();
+        with_storage(|storage| storage.load_enclosures(item_id)).map_err(|error| error.to_string())
+    }));
+    let records = match records() {
+        Ok(records) => records,
+        Err(error) => {
+            return rsx! {
+                LoadError { action: "Couldn't load the episode files", error }
+            }
+        }
+    };
+    let queue = download_queue.read();
+
+    rsx! {
+        for enclosure in records.into_iter().filter(|enclosure| !enclosure.mime_type.starts_with("image/")) {
+            div { key: "{enclosure.id}", class: "flex flex-col gap-1",
+                if enclosure.mime_type.starts_with("audio/") {
+                    AudioPlayer { enclosure: enclosure.clone() }
+                } else if enclosure.mime_type.starts_with("video/") {
+                    video { class: "w-full", controls: true, preload: "metadata", src: source(&enclosure) }
+                } else {
+                    a { href: enclosure.url.clone(), "{enclosure.mime_type}" }
+                }
+                div { class: "flex items-center gap-2 text-sm",
+                    if let Some(duration) = enclosure.duration {
+                        span { {format_duration(duration)} }
+                    }
+                    if let Some(length) = enclosure.length {
+                        span { {format_length(length)} }
+                    }
+                    if enclosure.local_path.is_some() {
+                        span { class: "badge", "Downloaded" }
+                    } else if queue.contains(enclosure.id) {
+                        span { class: "badge", "Downloading" }
+                    } else {
+                        button { class: "btn btn-xs",
+                            onclick: move |_| download_queue.write().push(enclosure.id),
+                            "Download"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use std::collections::HashMap;
+
+use dioxus::prelude::*;
+use rss_reader_core::{favicon::data_url, FeedRecord};
+
+/// The icons of the stored feeds as `data:` URLs, by feed id. Worked out
+/// once whenever the feed list changes and provided as a context by `App`.
+pub type FeedIcons = HashMap<i32, String>;
+
+pub fn feed_icons(feeds: &[FeedRecord]) -> FeedIcons {
+    feeds
+        .iter()
+        .filter_map(|feed| {
+            let (data, mime_type) = (feed.icon.as_ref()?, feed.icon_mime_type.as_ref()?);
+            Some((feed.id, data_url(mime_type, data)))
+        })
+        .collect()
+}
+
+/// The cached favicon of a feed's site, or nothing if it doesn't have one.
+#[component]
+pub fn FeedIcon(feed_id: i32) -> Element {
+    let icons = use_context::<Memo<FeedIcons>>();
+    let Some(src) = icons.read().get(&feed_id).cloned() else {
+        return rsx! {};
+    };
+
+    rsx! {
+        img { class: "size-4 inline-block", src, alt: "" }
+    }
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use dioxus::prelude::*;
+use rss_reader_core::{dates, feeds::FeedStats};
+
+use crate::with_storage;
+
+fn format_date(date: Option<DateTime<Utc>>) -> String {
+    date.map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or("never".to_string())
+}
+
+/// Dates stored as text, like `last_refresh_date`.
+fn format_stored_date(date: Option<&str>) -> String {
+    format_date(date.and_then(dates::parse_stored))
+}
+
+fn format_interval(interval: TimeDelta) -> String {
+    if interval.num_days() >= 2 {
+        format!("about every {} days", interval.num_days())
+    } else if interval.num_hours() >= 2 {
+        format!("about every {} hours", interval.num_hours())
+    } else {
+        format!("about every {} minutes", interval.num_minutes().max(1))
+    }
+}
+
+#[component]
+fn Property(label: String, value: String) -> Element {
+    rsx! {
+        tr {
+            th { "{label}" }
+            td { class: "break-all", "{value}" }
+        }
+    }
+}
+
+/// Everything we know about a subscription, for checking on feeds that
+/// misbehave.
+#[component]
+pub fn FeedProperties(feed_id: i32, on_close: EventHandler) -> Element {
+    let loaded = with_storage(|storage| {
+        let feed = storage.load_feed(feed_id)?;
+        Ok::<_, rss_reader_core::Error>((feed, storage.load_feed_stats(feed_id)?))
+    });
+    let Ok((feed, stats)) = loaded else {
+        return rsx! {};
+    };
+    let FeedStats {
+        total,
+        unread,
+        oldest,
+        newest,
+        average_interval,
+    } = stats;
+    let unknown = || "unknown".to_string();
+
+    rsx! {
+        dialog { class: "modal modal-open",
+            div { class: "modal-box max-w-3xl",
+                h3 { class: "text-lg font-bold", "{feed.name}" }
+                if let Some(description) = &feed.description {
+                    p { class: "text-sm", "{description}" }
+                }
+                table { class: "table table-sm",
+                    tbody {
+                        Property { label: "Site", value: feed.url.clone() }
+                        Property { label: "Feed URL", value: feed.feed_url.clone() }
+                        Property { label: "Added", value: format_date(Some(feed.create_date)) }
+                        Property { label: "Last refreshed", value: format_stored_date(feed.last_refresh_date.as_deref()) }
+                        if let Some(error) = &feed.last_error {
+                            Property {
+                                label: "Last error",
+                                value: format!("{error} ({})", format_stored_date(feed.last_error_date.as_deref())),
+                            }
+                        }
+                        Property { label: "Format", value: feed.format.clone().unwrap_or_else(unknown) }
+                        Property { label: "Language", value: feed.language.clone().unwrap_or_else(unknown) }
+                        Property { label: "Generator", value: feed.generator.clone().unwrap_or_else(unknown) }
+                        Property { label: "ETag", value: feed.etag.clone().unwrap_or("none".to_string()) }
+                        Property { label: "Last-Modified", value: feed.last_modified.clone().unwrap_or("none".to_string()) }
+                        Property { label: "Articles", value: format!("{total} ({unread} unread)") }
+                        Property { label: "Oldest article", value: format_date(oldest) }
+                        Property { label: "Newest article", value: format_date(newest) }
+                        Property {
+                            label: "Posts",
+                            value: average_interval.map(format_interval).unwrap_or_else(unknown),
+                        }
+                    }
+                }
+                div { class: "modal-action",
+                    button { class: "btn", onclick: move |_| on_close.call(()), "Close" }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rss_reader_core::storage::{MemoryStorage, Storage};
+
+    use super::*;
+    use crate::components::testing::{add_item, rendered_text, subscribe};
+
+    #[test]
+    fn properties_count_the_feeds_articles() {
+        let mut storage = MemoryStorage::new();
+        let feed_id = subscribe(&mut storage, "news");
+        let read = add_item(&mut storage, feed_id, "first");
+        add_item(&mut storage, feed_id, "second");
+        storage.set_item_read(read, true).unwrap();
+        assert_eq!(feed_id, 1);
+
+        let text = rendered_text(storage, || {
+            rsx! { FeedProperties { feed_id: 1, on_close: |_| {} } }
+        });
+
+        assert!(text.contains(&"news".to_string()));
+        assert!(text.contains(&"https://example.com/news.xml".to_string()));
+        assert!(text.contains(&"2 (1 unread)".to_string()));
+    }
+}
@@ -0,0 +1,50 @@
+use dioxus::prelude::*;
+use rss_reader_core::ingest::IngestSummary;
+
+/// The outcome of the last time a feed was added or refreshed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IngestReport {
+    pub feed_name: String,
+    pub summary: IngestSummary,
+}
+
+#[component]
+pub fn IngestReportAlert(mut report: Signal<Option<IngestReport>>) -> Element {
+    let mut show_skipped = use_signal(|| false);
+    let Some(IngestReport { feed_name, summary }) = report() else {
+        return rsx! {};
+    };
+
+    rsx! {
+        div { class: if summary.rejected.is_empty() { "alert alert-info" } else { "alert alert-warning" },
+            div { class: "flex flex-col gap-1",
+                span { "{feed_name}: {summary}" }
+                if !summary.rejected.is_empty() {
+                    button { class: "btn btn-ghost btn-xs",
+                        onclick: move |_| show_skipped.toggle(),
+                        if show_skipped() { "Hide skipped articles" } else { "Show skipped articles" }
+                    }
+                }
+                if show_skipped() {
+                    ul { class: "list",
+                        for rejected in summary.rejected.iter() {
+                            li { class: "list-row",
+                                span { class: "font-semibold",
+                                    {rejected.title.clone().or(rejected.link.clone()).unwrap_or("Untitled".to_string())}
+                                }
+                                span { "{rejected.reason}" }
+                            }
+                        }
+                    }
+                }
+            }
+            button { class: "btn btn-ghost btn-xs", aria_label: "Dismiss",
+                onclick: move |_| {
+                    show_skipped.set(false);
+                    report.set(None);
+                },
+                "✕"
+            }
+        }
+    }
+}
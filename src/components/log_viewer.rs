@@ -0,0 +1,155 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use dioxus::prelude::*;
+use rss_reader_core::{
+    database,
+    diagnostics::{export_bundle, read_logs, LogEntry},
+    profiles, Error,
+};
+use tracing::Level;
+
+use super::LoadError;
+use crate::{notifications::use_notifications, with_storage};
+
+/// How many of the latest matching events are shown.
+const SHOWN_ENTRIES: usize = 500;
+const LEVELS: [Level; 4] = [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG];
+
+fn level_class(level: Level) -> &'static str {
+    match level {
+        Level::ERROR => "badge badge-sm badge-error",
+        Level::WARN => "badge badge-sm badge-warning",
+        Level::INFO => "badge badge-sm badge-info",
+        _ => "badge badge-sm",
+    }
+}
+
+/// The log files of the current profile, with the diagnostics export.
+#[component]
+pub fn LogViewer() -> Element {
+    let mut revision = use_signal(|| 0);
+    // the least important level shown
+    let mut level = use_signal(|| Level::INFO);
+    let mut feed_filter: Signal<Option<i32>> = use_signal(|| None);
+    let mut exported_to: Signal<Option<PathBuf>> = use_signal(|| None);
+    let mut notifications = use_notifications();
+
+    // read again only on reload, changing the filters works on what was read
+    let loaded = use_resource(move || async move {
+        let _ = revision.read();
+        let log_dir = profiles::current()?.log_dir();
+        let entries = smol::unblock({
+            let log_dir = log_dir.clone();
+            move || read_logs(&log_dir)
+        })
+        .await?;
+        Ok::<_, Error>((
+            log_dir,
+            entries,
+            with_storage(|storage| storage.load_feeds())?,
+        ))
+    });
+    let loaded = loaded.read();
+    let (log_dir, entries, feeds) = match &*loaded {
+        Some(Ok(loaded)) => loaded,
+        Some(Err(error)) => {
+            return rsx! {
+                h1 { "Logs" }
+                LoadError { action: "Couldn't read the logs", error: error.to_string() }
+            }
+        }
+        None => {
+            return rsx! {
+                h1 { "Logs" }
+                span { class: "loading loading-spinner" }
+            }
+        }
+    };
+    let shown = entries
+        .iter()
+        .rev()
+        .filter(|entry| entry.level <= level())
+        .filter(|entry| feed_filter().is_none_or(|feed_id| entry.feed_id == Some(feed_id)))
+        .take(SHOWN_ENTRIES)
+        .collect::<Vec<&LogEntry>>();
+
+    rsx! {
+        h1 { "Logs" }
+        p { class: "text-sm", "Kept in {log_dir.display()} for a week." }
+        div { class: "flex flex-wrap items-center gap-2",
+            label { class: "select",
+                span { class: "label", "Level" }
+                select {
+                    onchange: move |event| {
+                        if let Ok(selected) = event.value().parse() {
+                            level.set(selected);
+                        }
+                    },
+                    for option_level in LEVELS {
+                        option { value: "{option_level}", selected: option_level == level(),
+                            "{option_level}"
+                        }
+                    }
+                }
+            }
+            label { class: "select",
+                span { class: "label", "Feed" }
+                select {
+                    onchange: move |event| feed_filter.set(event.value().parse().ok()),
+                    option { value: "", "All feeds" }
+                    for feed in feeds.iter() {
+                        option { value: "{feed.id}", selected: feed_filter() == Some(feed.id),
+                            "{feed.name}"
+                        }
+                    }
+                }
+            }
+            button { class: "btn btn-sm", onclick: move |_| revision += 1, "Reload" }
+            button { class: "btn btn-sm",
+                onclick: move |_| async move {
+                    // the bundle describes the database file itself, so it's
+                    // written off the UI thread on a connection of its own
+                    let exported = smol::unblock(|| {
+                        let profile = profiles::current()?;
+                        let destination = profile
+                            .data_dir
+                            .join(format!("diagnostics-{}.zip", Utc::now().format("%Y%m%d-%H%M%S")));
+                        let mut conn = database::establish(&profile.database_path)?;
+                        export_bundle(&mut conn, &profile.log_dir(), &destination)?;
+                        Ok::<_, Error>(destination)
+                    })
+                    .await;
+                    if let Some(destination) = notifications.write().check("Couldn't export the diagnostics", exported) {
+                        exported_to.set(Some(destination));
+                    }
+                },
+                "Export diagnostics bundle"
+            }
+        }
+        if let Some(path) = &*exported_to.read() {
+            div { class: "alert alert-info",
+                span { "Saved to {path.display()}. Feeds appear in it by number only." }
+                button { class: "btn btn-ghost btn-xs", aria_label: "Dismiss",
+                    onclick: move |_| exported_to.set(None),
+                    "✕"
+                }
+            }
+        }
+        if shown.is_empty() {
+            p { "Nothing logged at this level." }
+        }
+        ul { class: "list font-mono text-xs max-w-[120ch]",
+            for entry in shown {
+                li { class: "list-row",
+                    span { class: "whitespace-nowrap opacity-60", "{entry.time}" }
+                    span { class: level_class(entry.level), "{entry.level}" }
+                    if let Some(feed) = entry.feed_id.and_then(|feed_id| feeds.iter().find(|feed| feed.id == feed_id)) {
+                        span { class: "font-semibold", "{feed.name}" }
+                    }
+                    span { class: "whitespace-pre-wrap break-all", "{entry.text}" }
+                }
+            }
+        }
+    }
+}
@@ -1,9 +1,37 @@
+mod add_feed;
+mod enclosures;
+mod feed_icon;
+mod feed_properties;
 mod hero;
+mod ingest_report;
+mod log_viewer;
+mod mutes;
+mod notifications;
+mod page_window;
+mod profiles;
 mod reader;
-mod add_feed;
-mod side_nav_item;
 mod refresh_feed_button;
-pub use refresh_feed_button::RefreshButton;
-pub use add_feed::AddFeed;
-pub use side_nav_item::{SideNavItem, FeedNameId};
+mod retention;
+mod rules;
+mod search;
+mod side_nav_item;
+mod tags;
+#[cfg(test)]
+pub mod testing;
+pub use add_feed::{add_feed, AddFeed};
+pub use enclosures::Enclosures;
+pub use feed_icon::{feed_icons, FeedIcon};
+pub use feed_properties::FeedProperties;
+pub use ingest_report::{IngestReport, IngestReportAlert};
+pub use log_viewer::LogViewer;
+pub use mutes::{use_mute_filter, MuteRevision, MuteSettings};
+pub use notifications::{ErrorLog, ErrorLogNav, LoadError, ToastStack};
+pub use page_window::use_page_window;
+pub use profiles::ProfileSettings;
 pub use reader::Feed;
+pub use refresh_feed_button::{refresh, RefreshButton};
+pub use retention::RetentionSettings;
+pub use rules::RulesSettings;
+pub use search::{SaveSearchForm, SavedSearchNav, SearchBox, SearchResultList};
+pub use side_nav_item::{FeedNameId, SideNavItem};
+pub use tags::{TagChips, TagNav};
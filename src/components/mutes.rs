@@ -0,0 +1,132 @@
+use dioxus::prelude::*;
+use rss_reader_core::{mutes::MuteFilter, Error};
+
+use super::LoadError;
+use crate::{
+    notifications::{use_notifications, Notice},
+    with_storage,
+};
+
+/// Counts the changes to the mute terms, so what's compiled from them is only
+/// rebuilt when they change.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MuteRevision(u32);
+
+/// The mute terms compiled once, and again whenever [`MuteSettings`] changes
+/// them.
+pub fn use_mute_filter() -> Memo<Result<MuteFilter, String>> {
+    let revision = use_context::<Signal<MuteRevision>>();
+    use_memo(move || {
+        let _ = revision.read();
+        with_storage(|storage| MuteFilter::load(storage)).map_err(|error| error.to_string())
+    })
+}
+
+#[component]
+pub fn MuteSettings() -> Element {
+    let mut revision = use_context::<Signal<MuteRevision>>();
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+    let mut notifications = use_notifications();
+
+    let _ = revision.read();
+    let loaded = with_storage(|storage| {
+        Ok::<_, Error>((
+            storage.load_mute_terms()?,
+            storage.hidden_counts()?,
+            storage.load_feeds()?,
+        ))
+    });
+    let (terms, counts, feeds) = match loaded {
+        Ok(loaded) => loaded,
+        Err(load_error) => {
+            return rsx! {
+                h1 { "Muted Words" }
+                LoadError { action: "Couldn't load the muted words", error: load_error.to_string() }
+            }
+        }
+    };
+
+    rsx! {
+        h1 { "Muted Words" }
+        p { class: "text-sm",
+            "Articles whose title or description contain a muted word or phrase are hidden, not deleted."
+        }
+        ul { class: "list",
+            for term in terms {
+                li { key: "{term.id}", class: "list-row items-center",
+                    div {
+                        div { class: "font-bold",
+                            if term.is_regex { "/{term.term}/" } else { "{term.term}" }
+                        }
+                        div { class: "text-sm",
+                            match term.channel_id {
+                                Some(channel_id) => {
+                                    let name = feeds
+                                        .iter()
+                                        .find(|feed| feed.id == channel_id)
+                                        .map(|feed| feed.name.clone())
+                                        .unwrap_or_default();
+                                    rsx! { "Only in {name}" }
+                                }
+                                None => rsx! { "All feeds" },
+                            }
+                            " · {counts.get(&term.id).copied().unwrap_or_default()} hidden"
+                        }
+                    }
+                    button { class: "btn btn-xs btn-error",
+                        onclick: move |_| {
+                            let deleted = with_storage(|storage| storage.delete_mute_term(term.id));
+                            if notifications.write().check("Couldn't unmute", deleted).is_some() {
+                                revision.write().0 += 1;
+                            }
+                        },
+                        "Unmute"
+                    }
+                }
+            }
+        }
+        if let Some(error) = &*error.read() {
+            div { class: "alert alert-error", "{error}" }
+        }
+        form { class: "flex flex-wrap items-center gap-2",
+            onsubmit: move |event| {
+                let form = event.data.values();
+                let term = form.get("term").map(|value| value.as_value()).unwrap_or_default();
+                let channel_id = form.get("channel_id").and_then(|value| value.as_value().parse().ok());
+                let is_regex = form
+                    .get("is_regex")
+                    .is_some_and(|value| matches!(value.as_value().as_str(), "true" | "on"));
+
+                match with_storage(|storage| storage.add_mute_term(channel_id, &term, is_regex)) {
+                    Ok(()) => {
+                        error.set(None);
+                        revision.write().0 += 1;
+                    }
+                    // a bad term is pointed out next to the form
+                    Err(Error::Invalid(reason)) => error.set(Some(reason)),
+                    Err(mute_error) => {
+                        notifications.write().report(Notice::error(format!("Couldn't mute: {mute_error}")));
+                    }
+                }
+            },
+            label { class: "input",
+                span { class: "label", "Word or phrase" }
+                input { name: "term", required: true }
+            }
+            label { class: "select",
+                span { class: "label", "Feed" }
+                select { name: "channel_id",
+                    option { value: "", "All feeds" }
+                    for feed in feeds.iter() {
+                        option { value: "{feed.id}", "{feed.name}" }
+                    }
+                }
+            }
+            label { class: "label",
+                input { r#type: "checkbox", class: "checkbox", name: "is_regex" }
+                "Regex"
+            }
+            button { class: "btn", "Mute" }
+        }
+    }
+}
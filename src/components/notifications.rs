@@ -0,0 +1,206 @@
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use rss_reader_core::fetcher::{fetch_raw, RAW_RESPONSE_LIMIT};
+use smol::Timer;
+
+use crate::{
+    notifications::{use_notifications, Notice, Retry, Severity},
+    CurrentView,
+};
+
+/// How long an info toast stays up.
+const INFO_TOAST_DURATION: Duration = Duration::from_secs(5);
+
+fn retry_label(retry: &Retry) -> &'static str {
+    match retry {
+        Retry::Subscribe(_) => "Try again",
+        Retry::Refresh(_) | Retry::RefreshAll => "Retry",
+        Retry::Download(_) => "Retry download",
+    }
+}
+
+#[component]
+fn Toast(
+    notice: Notice,
+    current_view: Signal<Option<CurrentView>>,
+    on_retry: EventHandler<Retry>,
+) -> Element {
+    let mut notifications = use_notifications();
+    let id = notice.id;
+    let lingers = notice.severity == Severity::Error;
+
+    use_future(move || async move {
+        if !lingers {
+            Timer::after(INFO_TOAST_DURATION).await;
+            notifications.write().dismiss(id);
+        }
+    });
+
+    rsx! {
+        div { role: "alert",
+            class: if lingers { "alert alert-error max-w-[60ch]" } else { "alert alert-info max-w-[60ch]" },
+            span { "{notice.message}" }
+            div { class: "flex gap-1",
+                if let Some(retry) = notice.retry.clone() {
+                    button { class: "btn btn-xs",
+                        onclick: move |_| {
+                            notifications.write().dismiss(id);
+                            on_retry.call(retry.clone());
+                        },
+                        {retry_label(&retry)}
+                    }
+                }
+                if notice.raw_response_url.is_some() {
+                    button { class: "btn btn-xs",
+                        onclick: move |_| current_view.set(Some(CurrentView::ErrorLog(Some(id)))),
+                        "View raw response"
+                    }
+                }
+                button { class: "btn btn-ghost btn-xs", aria_label: "Dismiss",
+                    onclick: move |_| notifications.write().dismiss(id),
+                    "✕"
+                }
+            }
+        }
+    }
+}
+
+/// The latest notices, in the corner of the window.
+#[component]
+pub fn ToastStack(
+    current_view: Signal<Option<CurrentView>>,
+    on_retry: EventHandler<Retry>,
+) -> Element {
+    let notifications = use_notifications();
+    let toasts = notifications.read().toasts().cloned().collect::<Vec<_>>();
+
+    rsx! {
+        div { class: "toast toast-end z-50",
+            for notice in toasts {
+                Toast { key: "{notice.id}", notice, current_view, on_retry }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn ErrorLogNav(current_view: Signal<Option<CurrentView>>) -> Element {
+    let notifications = use_notifications();
+    let count = notifications.read().errors().count();
+
+    rsx! {
+        li {
+            a { onclick: move |_| current_view.set(Some(CurrentView::ErrorLog(None))),
+                "Errors"
+                if count > 0 {
+                    span { class: "badge badge-sm badge-error", "{count}" }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn RawResponse(url: String) -> Element {
+    let response = use_resource(move || {
+        let url = url.clone();
+        async move { smol::unblock(move || fetch_raw(&url)).await }
+    });
+
+    rsx! {
+        match &*response.read() {
+            None => rsx! { span { class: "loading loading-spinner loading-sm" } },
+            Some(Ok(body)) => rsx! {
+                pre { class: "max-h-96 overflow-auto whitespace-pre-wrap break-all text-xs bg-base-200 p-2",
+                    "{body}"
+                }
+                if body.len() as u64 >= RAW_RESPONSE_LIMIT {
+                    span { class: "text-sm", "Only the start of the response is shown." }
+                }
+            },
+            Some(Err(error)) => rsx! {
+                div { class: "alert alert-error", "Couldn't fetch the response again: {error}" }
+            },
+        }
+    }
+}
+
+/// Every error reported this session. `expanded` is the notice whose raw
+/// response is showing.
+#[component]
+pub fn ErrorLog(
+    current_view: Signal<Option<CurrentView>>,
+    expanded: Option<usize>,
+    on_retry: EventHandler<Retry>,
+) -> Element {
+    let mut notifications = use_notifications();
+    let errors = notifications.read().errors().cloned().collect::<Vec<_>>();
+
+    rsx! {
+        h1 { "Errors" }
+        if errors.is_empty() {
+            p { "Nothing has gone wrong so far." }
+        } else {
+            button { class: "btn btn-sm", onclick: move |_| notifications.write().clear_errors(),
+                "Clear log"
+            }
+        }
+        ul { class: "list min-w-[45ch] max-w-[80ch]",
+            for notice in errors {
+                li { key: "{notice.id}", class: "list-row flex flex-col gap-1",
+                    div { class: "flex items-center gap-2",
+                        span { class: "text-sm opacity-60",
+                            {notice.date.format("%Y-%m-%d %H:%M:%S").to_string()}
+                        }
+                        span { "{notice.message}" }
+                    }
+                    div { class: "flex gap-1",
+                        if let Some(retry) = notice.retry.clone() {
+                            button { class: "btn btn-xs",
+                                onclick: move |_| on_retry.call(retry.clone()),
+                                {retry_label(&retry)}
+                            }
+                        }
+                        if notice.raw_response_url.is_some() {
+                            if expanded == Some(notice.id) {
+                                button { class: "btn btn-xs",
+                                    onclick: move |_| current_view.set(Some(CurrentView::ErrorLog(None))),
+                                    "Hide raw response"
+                                }
+                            } else {
+                                button { class: "btn btn-xs",
+                                    onclick: move |_| current_view.set(Some(CurrentView::ErrorLog(Some(notice.id)))),
+                                    "View raw response"
+                                }
+                            }
+                        }
+                    }
+                    if let (true, Some(url)) = (expanded == Some(notice.id), notice.raw_response_url.clone()) {
+                        RawResponse { url }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Stands in for a part of the page whose data couldn't be loaded, and
+/// reports the failure.
+#[component]
+pub fn LoadError(action: String, error: String) -> Element {
+    let mut notifications = use_notifications();
+    let message = format!("{action}: {error}");
+
+    // reported once it's rendered, not while rendering
+    use_effect({
+        let message = message.clone();
+        move || {
+            notifications.write().report(Notice::error(message.clone()));
+        }
+    });
+
+    rsx! {
+        div { role: "alert", class: "alert alert-error", "{message}" }
+    }
+}
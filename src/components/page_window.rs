@@ -0,0 +1,66 @@
+use std::collections::{BTreeSet, HashMap};
+
+use dioxus::prelude::*;
+
+/// Which pages of a long list are in view. Only those and their neighbours
+/// are rendered, every other page is left as empty space as tall as it was,
+/// so however much has been loaded the list keeps its length and scroll
+/// position without keeping every card around.
+#[derive(Clone, Copy)]
+pub struct PageWindow {
+    visible: Signal<BTreeSet<usize>>,
+    heights: Signal<HashMap<usize, f64>>,
+}
+
+pub fn use_page_window() -> PageWindow {
+    PageWindow {
+        visible: use_signal(BTreeSet::new),
+        heights: use_signal(HashMap::new),
+    }
+}
+
+impl PageWindow {
+    /// Whether page `index` of `count` is rendered. Pages next to one in view
+    /// are too, so they're there before scrolling reaches them.
+    pub fn shows(&self, index: usize, count: usize) -> bool {
+        let visible = self.visible.read();
+        // pages of a list shown before can still be recorded, they're
+        // forgotten once pages with their index scroll out of view
+        let (Some(first), Some(last)) = (
+            visible.range(..count).next(),
+            visible.range(..count).next_back(),
+        ) else {
+            return true;
+        };
+        index + 1 >= *first && index <= last + 1
+    }
+
+    /// The style that keeps page `index` as tall as it was while it's not
+    /// rendered.
+    pub fn placeholder(&self, index: usize) -> String {
+        // read without subscribing, the heights only matter once the window
+        // moves
+        let height = self.heights.peek().get(&index).copied().unwrap_or_default();
+        format!("height: {height}px")
+    }
+
+    pub fn on_visible(&mut self, index: usize, event: Event<VisibleData>) {
+        let Ok(intersecting) = event.is_intersecting() else {
+            return;
+        };
+        if self.visible.peek().contains(&index) != intersecting {
+            let mut visible = self.visible.write();
+            if intersecting {
+                visible.insert(index);
+            } else {
+                visible.remove(&index);
+            }
+        }
+    }
+
+    pub fn on_resize(&mut self, index: usize, event: Event<ResizeData>) {
+        if let Ok(size) = event.get_border_box_size() {
+            self.heights.write().insert(index, size.height);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use dioxus::prelude::*;
+
+use rss_reader_core::profiles::{self, launch_profile, list_profiles};
+
+use super::LoadError;
+
+/// Opens the profile in a new window and closes this one.
+fn switch_to(name: &str) -> rss_reader_core::Result<()> {
+    launch_profile(name)?;
+    std::process::exit(0);
+}
+
+#[component]
+pub fn ProfileSettings() -> Element {
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+    let current = match profiles::current() {
+        Ok(current) => current,
+        Err(error) => {
+            return rsx! {
+                h1 { "Profile" }
+                LoadError { action: "Couldn't find the current profile", error: error.to_string() }
+            }
+        }
+    };
+    let others = list_profiles()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|name| *name != current.name)
+        .collect::<Vec<_>>();
+
+    rsx! {
+        h1 { "Profile" }
+        p { class: "text-sm",
+            "Each profile has its own subscriptions and downloads. Using "
+            span { class: "font-bold", "{current.name}" }
+            ", stored in "
+            code { "{current.database_path.display()}" }
+        }
+        if let Some(error) = &*error.read() {
+            div { class: "alert alert-error", "{error}" }
+        }
+        ul { class: "list",
+            for name in others {
+                li { key: "{name}", class: "list-row items-center",
+                    div { class: "font-bold", "{name}" }
+                    button { class: "btn btn-xs",
+                        onclick: move |_| {
+                            if let Err(switch_error) = switch_to(&name) {
+                                error.set(Some(switch_error.to_string()));
+                            }
+                        },
+                        "Switch"
+                    }
+                }
+            }
+        }
+        form { class: "flex flex-wrap items-center gap-2",
+            onsubmit: move |event| {
+                let form = event.data.values();
+                let name = form.get("name").map(|value| value.as_value()).unwrap_or_default();
+                if let Err(switch_error) = switch_to(name.trim()) {
+                    error.set(Some(switch_error.to_string()));
+                }
+            },
+            label { class: "input",
+                span { class: "label", "New profile" }
+                input { name: "name", required: true, pattern: "[A-Za-z0-9_-]+" }
+            }
+            button { class: "btn", "Create and Switch" }
+        }
+    }
+}
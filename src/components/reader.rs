@@ -1,67 +1,430 @@
+use std::rc::Rc;
+
 use dioxus::prelude::*;
-use crate::CurrentView;
+use rss_reader_core::{
+    cluster::ArticleCluster, mutes::MuteFilter, sanitize::clean_html, Article, FeedItemRecord,
+};
+
+use crate::{
+    components::{
+        use_mute_filter, use_page_window, Enclosures, ErrorLog, FeedIcon, LoadError, LogViewer,
+        MuteSettings, ProfileSettings, RetentionSettings, RulesSettings, SaveSearchForm,
+        SearchResultList, TagChips,
+    },
+    downloads::DownloadQueue,
+    notifications::{use_notifications, Notifications, Retry},
+    with_storage, CurrentView,
+};
+
+fn mark_read(mut notifications: Signal<Notifications>, item_id: i32) {
+    let marked = with_storage(|storage| storage.set_item_read(item_id, true));
+    notifications
+        .write()
+        .check("Couldn't mark the article as read", marked);
+}
+
+/// How close to the end of the article list, in pixels, the next page starts
+/// loading.
+const LOAD_MORE_MARGIN: f64 = 800.0;
 
+/// Show artwork and author for podcast subscriptions.
+#[component]
+fn PodcastHeader(channel_id: i32) -> Element {
+    let feed = with_storage(|storage| storage.load_feed(channel_id));
+    let Ok(feed) = feed else {
+        return rsx! {};
+    };
+
+    rsx! {
+        div { class: "flex items-center gap-4",
+            if let Some(artwork_url) = &feed.artwork_url {
+                img { class: "w-24 rounded", src: "{artwork_url}", alt: "{feed.name}" }
+            }
+            if let Some(author) = &feed.itunes_author {
+                span { "{author}" }
+            }
+            if feed.explicit == Some(true) {
+                span { class: "badge badge-warning", "Explicit" }
+            }
+        }
+    }
+}
 
 #[component]
-pub fn Feed(current_view: Signal<Option<CurrentView>> ) -> Element {
+fn StarButton(item_id: i32, starred: bool) -> Element {
+    let mut starred = use_signal(|| starred);
+    let mut notifications = use_notifications();
+
     rsx! {
+        button { class: "btn btn-ghost btn-xs", aria_label: "Star",
+            onclick: move |_| {
+                let value = !starred();
+                let saved = with_storage(|storage| storage.set_item_starred(item_id, value));
+                if notifications.write().check("Couldn't star the article", saved).is_some() {
+                    starred.set(value);
+                }
+            },
+            if starred() { "★" } else { "☆" }
+        }
+    }
+}
+
+/// Summary card used by the views that mix articles from several feeds.
+#[component]
+fn ArticleCard(
+    article: Article,
+    current_view: Signal<Option<CurrentView>>,
+    download_queue: Signal<DownloadQueue>,
+) -> Element {
+    let notifications = use_notifications();
+
+    rsx! {
+        div { class: "card card-border bg-base-100 min-w-[45ch] max-w-[80ch] shadow-sm",
+            if let Some(thumbnail_url) = &article.thumbnail_url {
+                figure {
+                    img { src: "{thumbnail_url}", alt: article.title.clone().unwrap_or_default() }
+                }
+            }
+            div { class: "card-body",
+                h2 { class: "card-title",
+                    a {
+                        class: if !article.is_read {"font-bold"},
+                        href: article.link.clone().unwrap_or_default(),
+                        onclick: move |_| mark_read(notifications, article.id),
+                        {article.title.clone().unwrap_or_default()}
+                    }
+                    StarButton { item_id: article.id, starred: article.is_starred }
+                }
+                div { class: "flex items-center gap-2 text-sm",
+                    FeedIcon { feed_id: article.channel_id }
+                    "{article.channel_title}"
+                }
+                p {
+                    {article.pub_date.map(|date| date.format("%Y-%m-%d").to_string()).unwrap_or_default()}
+                }
+                p {
+                    {article.description.clone().unwrap_or_default()}
+                }
+                Enclosures { item_id: article.id, download_queue }
+                TagChips { item_id: article.id, current_view }
+            }
+        }
+    }
+}
+
+/// A story with the other feeds that published it listed underneath.
+#[component]
+fn ClusterCard(
+    cluster: ArticleCluster,
+    current_view: Signal<Option<CurrentView>>,
+    download_queue: Signal<DownloadQueue>,
+) -> Element {
+    let mut expanded = use_signal(|| false);
+    let notifications = use_notifications();
+    let others = &cluster.articles[1..];
+
+    rsx! {
+        ArticleCard { article: cluster.primary().clone(), current_view, download_queue }
+        if !others.is_empty() {
+            button { class: "btn btn-ghost btn-xs",
+                onclick: move |_| expanded.toggle(),
+                if expanded() {
+                    "Hide other sources"
+                } else if others.len() == 1 {
+                    "1 more source"
+                } else {
+                    "{others.len()} more sources"
+                }
+            }
+            if expanded() {
+                ul { class: "list",
+                    for article in others.iter().cloned() {
+                        li { key: "{article.id}", class: "list-row",
+                            span { class: "flex items-center gap-2 font-semibold",
+                                FeedIcon { feed_id: article.channel_id }
+                                "{article.channel_title}"
+                            }
+                            a {
+                                class: if !article.is_read { "link font-bold" } else { "link" },
+                                href: article.link.clone().unwrap_or_default(),
+                                onclick: move |_| mark_read(notifications, article.id),
+                                {article.title.clone().unwrap_or_default()}
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn near_bottom(list: &MountedData) -> bool {
+    let (Ok(offset), Ok(size), Ok(rect)) = (
+        list.get_scroll_offset().await,
+        list.get_scroll_size().await,
+        list.get_client_rect().await,
+    ) else {
+        return false;
+    };
+    offset.y + rect.height() >= size.height - LOAD_MORE_MARGIN
+}
+
+fn load_next_page(
+    mut current_view: Signal<Option<CurrentView>>,
+    mut notifications: Signal<Notifications>,
+) {
+    let loaded = with_storage(|storage| match &mut *current_view.write() {
+        Some(CurrentView::AllFeeds(pages)) => pages.load_next(storage),
+        Some(CurrentView::SelectedFeed(channel)) => channel.pages.load_next(storage),
+        _ => Ok(()),
+    });
+    notifications
+        .write()
+        .check("Couldn't load more articles", loaded);
+}
+
+fn episode_label(item: &FeedItemRecord) -> Option<String> {
+    match (item.season, item.episode) {
+        (Some(season), Some(episode)) => Some(format!("S{season} · E{episode}")),
+        (None, Some(episode)) => Some(format!("Episode {episode}")),
+        (Some(season), None) => Some(format!("Season {season}")),
+        (None, None) => None,
+    }
+}
+
+/// A whole article of the feed being read.
+#[component]
+fn ItemCard(
+    item: FeedItemRecord,
+    current_view: Signal<Option<CurrentView>>,
+    download_queue: Signal<DownloadQueue>,
+) -> Element {
+    let notifications = use_notifications();
+
+    rsx! {
+        div { class: "card card-border bg-base-100 min-w-[45ch] max-w-[80ch] shadow-sm",
+            div { class: "card-body w-full",
+                h2 { class: "card-title",
+                    a {
+                        class: if !item.is_read {"font-bold"},
+                        href: item.url.clone().unwrap_or_default(),
+                        onclick: move |_| mark_read(notifications, item.id),
+                        {item.title.clone().unwrap_or_default()}
+                    }
+                    StarButton { item_id: item.id, starred: item.is_starred }
+                }
+                p {
+                    {item.pub_date.clone().unwrap_or_default()}
+                }
+                div { class: "flex items-center gap-2",
+                    if let Some(label) = episode_label(&item) {
+                        span { class: "badge", "{label}" }
+                    }
+                    if item.explicit == Some(true) {
+                        span { class: "badge badge-warning", "Explicit" }
+                    }
+                    if let Some(transcript_url) = &item.transcript_url {
+                        a { class: "link", href: "{transcript_url}", "Transcript" }
+                    }
+                    if let Some(chapters_url) = &item.chapters_url {
+                        a { class: "link", href: "{chapters_url}", "Chapters" }
+                    }
+                }
+                // prefer the full article body when the feed provides one
+                div {
+                    dangerous_inner_html: clean_html(item.content.as_deref().or(item.description.as_deref()).unwrap_or_default())
+                }
+                Enclosures { item_id: item.id, download_queue }
+                TagChips { item_id: item.id, current_view }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn Feed(
+    current_view: Signal<Option<CurrentView>>,
+    download_queue: Signal<DownloadQueue>,
+    show_muted: Signal<bool>,
+    on_retry: EventHandler<Retry>,
+) -> Element {
+    let mut article_list: Signal<Option<Rc<MountedData>>> = use_signal(|| None);
+    let mut window = use_page_window();
+    let notifications = use_notifications();
+    let mute_filter = use_mute_filter();
+    let mute_filter = mute_filter.read();
+    // nothing is muted when the terms can't be loaded
+    let no_mutes = MuteFilter::default();
+    let (mute_filter, mute_error) = match &*mute_filter {
+        Ok(mute_filter) => (mute_filter, None),
+        Err(error) => (&no_mutes, Some(error.clone())),
+    };
+    let show_muted = show_muted();
+    let shown = |channel_id: i32, title: Option<&str>, description: Option<&str>| {
+        show_muted || !mute_filter.is_muted(channel_id, title, description)
+    };
+
+    // the lists are loaded a page at a time, the next one is fetched when
+    // the list is scrolled close to its end
+    let on_mounted = move |element: Event<MountedData>| article_list.set(Some(element.data()));
+    let on_scroll = move |_| async move {
+        let Some(list) = article_list() else {
+            return;
+        };
+        if near_bottom(&list).await {
+            load_next_page(current_view, notifications);
+        }
+    };
+
+    rsx! {
+        if let Some(error) = mute_error {
+            LoadError { action: "Couldn't load the mute terms", error }
+        }
         match &*current_view.read() {
             Some(view) => rsx!{
                 match view {
                     CurrentView::SelectedFeed(channel) => rsx!{
                         h1 { "{channel.name.clone()}" }
-                        ul {
-                            for item in channel.items.iter() {
-                                // li { key: "{item.guid}",
-                                li {
-                                    div { class: "card card-border bg-base-100 min-w-[45ch] max-w-[80ch] shadow-sm",
-                                        div { class: "card-body w-full",
-                                            h2 { class: "card-title",
-                                        a {
-                                            href: item.url.clone().unwrap(),
-                                            {item.title.clone().unwrap()}
-                                        }
-                                    }
-                                    p {
-                                        {item.pub_date.clone().unwrap()}
-                                    }
-                                    p {
-                                        {item.description.clone().unwrap()}
-                                    }
+                        PodcastHeader { channel_id: channel.channel_id }
+                        div { class: "overflow-y-auto max-h-[80vh]",
+                            onmounted: on_mounted,
+                            onscroll: on_scroll,
+                            for (index, page) in channel.pages.pages.iter().enumerate() {
+                                div { key: "{index}",
+                                    style: if !window.shows(index, channel.pages.pages.len()) { window.placeholder(index) },
+                                    onvisible: move |event| window.on_visible(index, event),
+                                    onresize: move |event| window.on_resize(index, event),
+                                    if window.shows(index, channel.pages.pages.len()) {
+                                        ul {
+                                            for item in page.iter().filter(|item| {
+                                                shown(item.channel_id, item.title.as_deref(), item.description.as_deref())
+                                            }) {
+                                                li { key: "{item.id}",
+                                                    ItemCard { item: item.clone(), current_view, download_queue }
+                                                }
+                                            }
                                         }
                                     }
                                 }
                             }
+                            if channel.pages.has_more() {
+                                button { class: "btn btn-ghost",
+                                    onclick: move |_| load_next_page(current_view, notifications),
+                                    "Load more"
+                                }
+                            }
                         }
                     },
-                    CurrentView::AllFeeds(articles)=> rsx!{
+                    CurrentView::AllFeeds(pages) => rsx!{
                         h1 { "All Articles" }
-                        ul {
-                            for item in articles.iter() {
-                                li { key: "{item.id}",
-                                    div { class: "card card-border bg-base-100 min-w-[45ch] max-w-[80ch] shadow-sm",
-                                        div { class: "card-body",
-                                            h2 { class: "card-title",
-                                                a {
-                                                    href: item.link.clone().unwrap(),
-                                                    {item.title.clone().unwrap()}
+                        div { class: "overflow-y-auto max-h-[80vh]",
+                            onmounted: on_mounted,
+                            onscroll: on_scroll,
+                            for (index, page) in pages.pages.iter().enumerate() {
+                                div { key: "{index}",
+                                    style: if !window.shows(index, pages.pages.len()) { window.placeholder(index) },
+                                    onvisible: move |event| window.on_visible(index, event),
+                                    onresize: move |event| window.on_resize(index, event),
+                                    if window.shows(index, pages.pages.len()) {
+                                        ul {
+                                            for cluster in page.iter().filter_map(|cluster| {
+                                                let articles = cluster
+                                                    .articles
+                                                    .iter()
+                                                    .filter(|article| {
+                                                        shown(article.channel_id, article.title.as_deref(), article.description.as_deref())
+                                                    })
+                                                    .cloned()
+                                                    .collect::<Vec<_>>();
+                                                (!articles.is_empty()).then_some(ArticleCluster { articles })
+                                            }) {
+                                                li { key: "{cluster.primary().id}",
+                                                    ClusterCard { cluster, current_view, download_queue }
                                                 }
                                             }
-                                            p {
-                                                {item.pub_date.unwrap().format("%Y-%m-%d").to_string()}
-                                            }
-                                            p {
-                                                {item.description.clone().unwrap()}
-                                            }
                                         }
                                     }
                                 }
                             }
+                            if pages.has_more() {
+                                button { class: "btn btn-ghost",
+                                    onclick: move |_| load_next_page(current_view, notifications),
+                                    "Load more"
+                                }
+                            }
                         }
                     },
+                    CurrentView::Search(search) => rsx!{
+                        h1 { "Results for “{search.query}”" }
+                        SaveSearchForm { query: search.query.clone(), current_view }
+                        SearchResultList { results: search.results.clone() }
+                    },
+                    CurrentView::SavedSearch(saved_search) => rsx!{
+                        h1 { "{saved_search.name}" }
+                        p { class: "text-sm", "{saved_search.search.query}" }
+                        SearchResultList { results: saved_search.search.results.clone() }
+                    },
+                    CurrentView::Settings => rsx!{
+                        RulesSettings {}
+                        MuteSettings {}
+                        RetentionSettings {}
+                        ProfileSettings {}
+                    },
+                    CurrentView::Logs => rsx!{
+                        LogViewer {}
+                    },
+                    CurrentView::ErrorLog(expanded) => rsx!{
+                        ErrorLog { current_view, expanded: *expanded, on_retry }
+                    },
+                    CurrentView::Tagged(tag_feed) => rsx!{
+                        h1 { "Tagged “{tag_feed.name}”" }
+                        ul {
+                            for item in tag_feed.articles.iter() {
+                                li { key: "{item.id}",
+                                    ArticleCard { article: item.clone(), current_view, download_queue }
+                                }
+                            }
+                        }
                     },
-                },
-                None => rsx!{},
+                }
             },
-        }   
+            None => rsx!{},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rss_reader_core::{
+        pagination::ArticlePages,
+        storage::{MemoryStorage, Storage},
+    };
+
+    use super::*;
+    use crate::components::testing::{add_item, rendered_text, subscribe, use_app_contexts};
+
+    fn all_articles() -> Element {
+        use_app_contexts();
+        let current_view = use_signal(|| {
+            let pages = with_storage(|storage| ArticlePages::first(storage)).unwrap();
+            Some(CurrentView::AllFeeds(pages))
+        });
+        let download_queue = use_signal(DownloadQueue::default);
+        let show_muted = use_signal(|| false);
+        rsx! { Feed { current_view, download_queue, show_muted, on_retry: |_| {} } }
+    }
+
+    #[test]
+    fn all_articles_leave_out_muted_ones() {
+        let mut storage = MemoryStorage::new();
+        let feed_id = subscribe(&mut storage, "news");
+        add_item(&mut storage, feed_id, "Regular post");
+        add_item(&mut storage, feed_id, "Sponsored post");
+        storage.add_mute_term(None, "sponsored", false).unwrap();
+
+        let text = rendered_text(storage, all_articles);
+
+        assert!(text.contains(&"Regular post".to_string()));
+        assert!(!text.iter().any(|text| text.contains("Sponsored")));
+    }
 }
@@ -1,126 +1,146 @@
+use std::fmt::Display;
+
 use chrono::Utc;
-use diesel::{result::Error as DieselError, ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper};
 use dioxus::prelude::*;
-use rss::Channel;
-
-use crate::{schema, ChannelFeed, CurrentView, FeedItemRecord, FeedRecord, NewFeedItemRecord, DB};
+use rss_reader_core::{
+    ingest::{fetch_refresh, FeedUpdate, IngestSummary},
+    pagination::ArticlePages,
+    FeedRecord,
+};
 
-#[component]
-pub fn RefreshButton(
-    current_view: Signal<Option<CurrentView>>,
-    stored_feeds: Signal<Vec<FeedRecord>>,
-    selected_feed_index: Memo<Option<usize>>,
-) -> Element {
-    let refresh_button_click_handler = move |_| async move {
-        match &mut *current_view.write() {
-            Some(view) => {
-                match view {
-                    CurrentView::AllFeeds(articles) => todo!(),
-                    CurrentView::SelectedFeed(channel_feed) => {
-                        // TODO store the feed URL in ChannelFeed struct
-                        let url = {
-                            let feeds = stored_feeds.read();
-                            let selected = selected_feed_index.read().unwrap();
-                            feeds[selected].feed_url.clone()
-                        };
+use super::IngestReport;
+use crate::{
+    notifications::{use_notifications, Notice, Notifications, Retry},
+    with_storage, ChannelFeed, CurrentView,
+};
 
-                        let agent = ureq::agent();
-                        let content: Vec<u8> = agent.get(url.clone()).call().unwrap().into_body().read_to_vec().unwrap();
+fn report_failure(
+    mut notifications: Signal<Notifications>,
+    feed_id: Option<i32>,
+    error: impl Display,
+) {
+    let retry = feed_id.map_or(Retry::RefreshAll, Retry::Refresh);
+    notifications
+        .write()
+        .report(Notice::error(format!("Couldn't refresh feeds: {error}")).with_retry(retry));
+}
 
-                        let channel = Channel::read_from(&content[..]);
-                        match channel {
-                            Ok(channel) => {
-                                let (feeds, feed_items) = DB.with_borrow_mut(|conn| {
-                                    {
-                                        use crate::schema::feed_items;
-                                        use diesel::dsl::insert_into;
+/// Fetches `feed_id`, or every feed when there's none, and reloads the view
+/// if it shows what was fetched. Feeds that fail are reported one by one.
+pub async fn refresh(
+    feed_id: Option<i32>,
+    mut current_view: Signal<Option<CurrentView>>,
+    mut stored_feeds: Signal<Vec<FeedRecord>>,
+    mut ingest_report: Signal<Option<IngestReport>>,
+    mut notifications: Signal<Notifications>,
+) {
+    let sources = with_storage(|storage| match feed_id {
+        Some(feed_id) => Ok(vec![storage.refresh_source(feed_id)?]),
+        None => storage.refresh_sources(),
+    });
+    let sources = match sources {
+        Ok(sources) => sources,
+        Err(error) => return report_failure(notifications, feed_id, error),
+    };
 
-                                        // TODO: when inserting, if an item URL is NULL add custom
-                                        // handling that checks if other values are unique
-                                        let now = Utc::now();
-                                        let items = channel
-                                            .items
-                                            .clone()
-                                            .into_iter()
-                                            .map(|item| NewFeedItemRecord {
-                                                channel_id: channel_feed.channel_id,
-                                                title: item.title,
-                                                url: item.link,
-                                                description: item.description,
-                                                author: item.author,
-                                                pub_date: item.pub_date,
-                                                create_date: now,
-                                                update_date: now,
-                                            })
-                                            .collect::<Vec<_>>();
-                                        let items_len = items.len();
+    // feeds are fetched off the UI thread, only storing them blocks
+    let mut results = Vec::new();
+    for source in sources {
+        let fetched = smol::unblock({
+            let source = source.clone();
+            move || fetch_refresh(&source)
+        })
+        .await;
+        let stored = with_storage(|storage| storage.store_refresh(&source, fetched, Utc::now()));
+        results.push((source.feed_id, stored));
+    }
 
-                                        for item in items.into_iter() {
-                                            let row_count_inserted =
-                                                match insert_into(feed_items::table)
-                                                    .values(item)
-                                                    .execute(conn)
-                                                {
-                                                    Ok(record) => record,
-                                                    Err(error) => {
-                                                        match error {
-                                                            // TODO: if insert fails then try an update in future
-                                                            DieselError::DatabaseError(..) => {
-                                                                continue;
-                                                            }
-                                                            _ => todo!(),
-                                                        }
-                                                    }
-                                                };
-                                            dbg!(items_len, row_count_inserted);
-                                        }
-                                    }
+    // a failed fetch is recorded on the feed, so reload either way
+    let feeds = with_storage(|storage| storage.load_feeds());
+    let feeds = match feeds {
+        Ok(feeds) => feeds,
+        Err(error) => return report_failure(notifications, feed_id, error),
+    };
 
-                                    use schema::feed_items::dsl::feed_items;
-                                    use schema::feeds::dsl::feeds;
+    let mut report = feed_id.is_none().then(|| IngestReport {
+        feed_name: "All feeds".to_string(),
+        summary: IngestSummary::default(),
+    });
+    for (feed_id, result) in results {
+        match result {
+            Ok(FeedUpdate {
+                feed_name, summary, ..
+            }) => match &mut report {
+                Some(report) => report.summary.merge(summary),
+                None => report = Some(IngestReport { feed_name, summary }),
+            },
+            Err(error) => {
+                let feed = feeds.iter().find(|feed| feed.id == feed_id);
+                notifications.write().report(Notice::feed_error(
+                    format!(
+                        "Couldn't refresh {}",
+                        feed.map_or(feed_id.to_string(), |feed| feed.name.clone())
+                    ),
+                    &error,
+                    feed.map_or("", |feed| &feed.feed_url),
+                    Some(Retry::Refresh(feed_id)),
+                ));
+            }
+        }
+    }
+    if report.is_some() {
+        ingest_report.set(report);
+    }
 
-                                    (
-                                        feeds
-                                            .select(FeedRecord::as_select())
-                                            .load::<FeedRecord>(conn)
-                                            .unwrap(),
-                                        feed_items
-                                            .filter(
-                                                schema::feed_items::dsl::channel_id
-                                                    .eq(channel_feed.channel_id),
-                                            )
-                                            .select(FeedItemRecord::as_select())
-                                            .load::<FeedItemRecord>(conn)
-                                            .unwrap(),
-                                    )
-                                });
+    let reloaded = with_storage(|storage| match &mut *current_view.write() {
+        Some(CurrentView::AllFeeds(pages)) => {
+            *pages = ArticlePages::first(storage)?;
+            Ok(())
+        }
+        Some(CurrentView::SelectedFeed(channel_feed))
+            if feed_id.is_none_or(|feed_id| feed_id == channel_feed.channel_id) =>
+        {
+            *channel_feed = ChannelFeed::load(storage, &feeds, channel_feed.channel_id)?;
+            Ok(())
+        }
+        _ => Ok::<_, rss_reader_core::Error>(()),
+    });
+    notifications
+        .write()
+        .check("Couldn't reload the articles", reloaded);
+    stored_feeds.set(feeds);
+}
 
-                                // safe to unwrap because if we've added a feed, then we want
-                                // it to be automatically selected and displayed
-                                let selected_index = feeds
-                                    .iter()
-                                    .position(|val| val.name == channel.title)
-                                    .unwrap();
-                                let new_channel_feed = ChannelFeed {
-                                    name: feeds[selected_index].name.clone(),
-                                    channel_id: channel_feed.channel_id,
-                                    items: feed_items,
-                                    selected: selected_index,
-                                };
+#[component]
+pub fn RefreshButton(
+    current_view: Signal<Option<CurrentView>>,
+    stored_feeds: Signal<Vec<FeedRecord>>,
+    ingest_report: Signal<Option<IngestReport>>,
+) -> Element {
+    let notifications = use_notifications();
 
-                                stored_feeds.set(feeds);
-                                *view = CurrentView::SelectedFeed(new_channel_feed);
-                            }
-                            Err(err) => {
-                                dbg!(err);
-                                panic!();
-                            }
-                        };
-                    }
-                }
-            }
-            None => todo!(),
+    let refresh_button_click_handler = move |_| {
+        let feed_id = match &*current_view.read() {
+            Some(CurrentView::AllFeeds(_)) => None,
+            Some(CurrentView::SelectedFeed(channel_feed)) => Some(channel_feed.channel_id),
+            // nothing to fetch for the other views
+            Some(
+                CurrentView::Tagged(_)
+                | CurrentView::Search(_)
+                | CurrentView::SavedSearch(_)
+                | CurrentView::Settings
+                | CurrentView::Logs
+                | CurrentView::ErrorLog(_),
+            )
+            | None => return,
         };
+        spawn(refresh(
+            feed_id,
+            current_view,
+            stored_feeds,
+            ingest_report,
+            notifications,
+        ));
     };
 
     rsx! {
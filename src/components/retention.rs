@@ -0,0 +1,148 @@
+use chrono::Utc;
+use dioxus::prelude::*;
+use rss_reader_core::{retention::RetentionPolicy, Error};
+
+use super::LoadError;
+use crate::{notifications::use_notifications, with_storage};
+
+fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size.abs() >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+/// Reads the keep count and keep days fields of a retention form, treating
+/// empty fields as no limit.
+fn policy_from_form(form: &std::collections::HashMap<String, FormValue>) -> RetentionPolicy {
+    let number = |name: &str| {
+        form.get(name)
+            .and_then(|value| value.as_value().trim().parse().ok())
+            .filter(|number: &i32| *number > 0)
+    };
+    RetentionPolicy {
+        keep_count: number("keep_count"),
+        keep_days: number("keep_days"),
+    }
+}
+
+/// Inputs for a policy. `fallback` is shown as the placeholder of fields
+/// that are left empty.
+#[component]
+fn PolicyFields(policy: RetentionPolicy, fallback: RetentionPolicy) -> Element {
+    let count_placeholder = fallback
+        .keep_count
+        .map(|count| count.to_string())
+        .unwrap_or("all".to_string());
+    let days_placeholder = fallback
+        .keep_days
+        .map(|days| days.to_string())
+        .unwrap_or("ever".to_string());
+
+    rsx! {
+        label { class: "input",
+            span { class: "label", "Keep last" }
+            input { r#type: "number", min: 1, name: "keep_count", placeholder: count_placeholder,
+                value: policy.keep_count.map(|count| count.to_string()).unwrap_or_default()
+            }
+            span { class: "label", "items" }
+        }
+        label { class: "input",
+            span { class: "label", "Keep for" }
+            input { r#type: "number", min: 1, name: "keep_days", placeholder: days_placeholder,
+                value: policy.keep_days.map(|days| days.to_string()).unwrap_or_default()
+            }
+            span { class: "label", "days" }
+        }
+    }
+}
+
+#[component]
+pub fn RetentionSettings() -> Element {
+    let mut revision = use_signal(|| 0);
+    let mut message: Signal<Option<String>> = use_signal(|| None);
+    let mut notifications = use_notifications();
+
+    let _ = revision.read();
+    let loaded = with_storage(|storage| {
+        Ok::<_, Error>((storage.load_global_policy()?, storage.load_feeds()?))
+    });
+    let (global, feeds) = match loaded {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            return rsx! {
+                h1 { "Retention" }
+                LoadError { action: "Couldn't load the retention settings", error: error.to_string() }
+            }
+        }
+    };
+
+    rsx! {
+        h1 { "Retention" }
+        p { class: "text-sm",
+            "Old articles are purged every few hours. Starred articles, articles you or a rule tagged, and downloaded episodes are always kept."
+        }
+        if let Some(message) = &*message.read() {
+            div { class: "alert alert-info", "{message}" }
+        }
+        form { class: "flex flex-wrap items-center gap-2",
+            onsubmit: move |event| {
+                let policy = policy_from_form(&event.data.values());
+                let saved = with_storage(|storage| storage.save_global_policy(policy));
+                if notifications.write().check("Couldn't save the retention policy", saved).is_some() {
+                    revision += 1;
+                }
+            },
+            span { class: "font-bold", "All feeds" }
+            PolicyFields { policy: global, fallback: RetentionPolicy::default() }
+            button { class: "btn", "Save" }
+        }
+        ul { class: "list",
+            for feed in feeds {
+                li { key: "{feed.id}", class: "list-row",
+                    form { class: "flex flex-wrap items-center gap-2",
+                        onsubmit: move |event| {
+                            let policy = policy_from_form(&event.data.values());
+                            let saved = with_storage(|storage| storage.set_feed_policy(feed.id, policy));
+                            if notifications.write().check("Couldn't save the retention policy", saved).is_some() {
+                                revision += 1;
+                            }
+                        },
+                        span { class: "font-bold", "{feed.name}" }
+                        PolicyFields {
+                            policy: RetentionPolicy {
+                                keep_count: feed.retain_count,
+                                keep_days: feed.retain_days,
+                            },
+                            fallback: global,
+                        }
+                        button { class: "btn btn-sm", "Save" }
+                    }
+                }
+            }
+        }
+        button { class: "btn btn-warning",
+            onclick: move |_| {
+                let compacted = with_storage(|storage| storage.compact(Utc::now()));
+                let Some(report) = notifications.write().check("Couldn't compact the database", compacted) else {
+                    return;
+                };
+                message.set(Some(format!(
+                    "Purged {} articles and reclaimed {}",
+                    report.rows_purged,
+                    format_bytes(report.bytes_reclaimed),
+                )));
+            },
+            "Compact Database"
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use chrono::Utc;
+use dioxus::prelude::*;
+use rss_reader_core::{
+    rules::{CompiledRule, NewRuleRecord, RuleRecord},
+    Error, FeedItemRecord, FeedRecord,
+};
+
+use super::LoadError;
+use crate::{notifications::use_notifications, with_storage};
+
+const ACTIONS: [(&str, &str); 5] = [
+    ("mark_read", "Mark as read"),
+    ("star", "Star"),
+    ("tag", "Add tag"),
+    ("hide", "Hide"),
+    ("priority", "Set priority"),
+];
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn describe(rule: &RuleRecord, feeds: &[FeedRecord]) -> String {
+    let mut conditions = Vec::new();
+    if let Some(channel_id) = rule.channel_id {
+        let name = feeds
+            .iter()
+            .find(|feed| feed.id == channel_id)
+            .map(|feed| feed.name.as_str())
+            .unwrap_or("deleted feed");
+        conditions.push(format!("feed is {name}"));
+    }
+    if let Some(pattern) = &rule.title_pattern {
+        conditions.push(format!("title matches /{pattern}/"));
+    }
+    if let Some(pattern) = &rule.description_pattern {
+        conditions.push(format!("description matches /{pattern}/"));
+    }
+    if let Some(author) = &rule.author {
+        conditions.push(format!("author contains {author}"));
+    }
+    if let Some(tag) = &rule.tag {
+        conditions.push(format!("tagged {tag}"));
+    }
+    let conditions = if conditions.is_empty() {
+        "every article".to_string()
+    } else {
+        conditions.join(" and ")
+    };
+
+    let action = ACTIONS
+        .iter()
+        .find(|(name, _)| *name == rule.action)
+        .map(|(_, label)| *label)
+        .unwrap_or(rule.action.as_str());
+    match &rule.action_value {
+        Some(value) => format!("{conditions} → {action} {value}"),
+        None => format!("{conditions} → {action}"),
+    }
+}
+
+/// The articles a rule would act on, shown before applying it.
+#[derive(Clone, PartialEq)]
+struct Preview {
+    rule_id: i32,
+    items: Vec<FeedItemRecord>,
+}
+
+#[component]
+pub fn RulesSettings() -> Element {
+    let mut revision = use_signal(|| 0);
+    let mut editing: Signal<Option<RuleRecord>> = use_signal(|| None);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+    let mut message: Signal<Option<String>> = use_signal(|| None);
+    let mut preview: Signal<Option<Preview>> = use_signal(|| None);
+    let mut notifications = use_notifications();
+
+    let _ = revision.read();
+    let loaded =
+        with_storage(|storage| Ok::<_, Error>((storage.load_rules()?, storage.load_feeds()?)));
+    let (all_rules, feeds) = match loaded {
+        Ok(loaded) => loaded,
+        Err(load_error) => {
+            return rsx! {
+                h1 { "Rules" }
+                LoadError { action: "Couldn't load the rules", error: load_error.to_string() }
+            }
+        }
+    };
+
+    let current = editing.read().clone();
+    let form_key = current.as_ref().map(|rule| rule.id).unwrap_or_default();
+
+    rsx! {
+        h1 { "Rules" }
+        p { class: "text-sm",
+            "Rules run on every new article. An article has to match all of the conditions a rule sets."
+        }
+        if let Some(message) = &*message.read() {
+            div { class: "alert alert-info", "{message}" }
+        }
+        ul { class: "list",
+            for rule in all_rules {
+                li { key: "{rule.id}", class: "list-row items-center",
+                    input { r#type: "checkbox", class: "toggle", checked: rule.enabled,
+                        onchange: move |event| {
+                            let saved = with_storage(|storage| storage.set_rule_enabled(rule.id, event.checked()));
+                            notifications.write().check("Couldn't switch the rule", saved);
+                            revision += 1;
+                        }
+                    }
+                    div {
+                        div { class: "font-bold", "{rule.name}" }
+                        div { class: "text-sm", {describe(&rule, &feeds)} }
+                    }
+                    button { class: "btn btn-xs",
+                        onclick: {
+                            let rule = rule.clone();
+                            move |_| editing.set(Some(rule.clone()))
+                        },
+                        "Edit"
+                    }
+                    button { class: "btn btn-xs",
+                        onclick: {
+                            let rule = rule.clone();
+                            move |_| {
+                                let compiled = CompiledRule::new(rule.clone());
+                                let Some(compiled) = notifications.write().check("Couldn't preview the rule", compiled) else {
+                                    return;
+                                };
+                                let items = with_storage(|storage| storage.dry_run(&compiled));
+                                let Some(items) = notifications.write().check("Couldn't preview the rule", items) else {
+                                    return;
+                                };
+                                preview.set(Some(Preview { rule_id: rule.id, items }));
+                            }
+                        },
+                        "Preview"
+                    }
+                    button { class: "btn btn-xs",
+                        onclick: {
+                            let rule = rule.clone();
+                            move |_| {
+                                let compiled = CompiledRule::new(rule.clone());
+                                let Some(compiled) = notifications.write().check("Couldn't apply the rule", compiled) else {
+                                    return;
+                                };
+                                let applied = with_storage(|storage| storage.apply_retroactively(&compiled));
+                                let Some(applied) = notifications.write().check("Couldn't apply the rule", applied) else {
+                                    return;
+                                };
+                                message.set(Some(format!("Applied “{}” to {applied} existing articles", rule.name)));
+                            }
+                        },
+                        "Apply to existing"
+                    }
+                    button { class: "btn btn-xs btn-error",
+                        onclick: move |_| {
+                            let deleted = with_storage(|storage| storage.delete_rule(rule.id));
+                            if notifications.write().check("Couldn't delete the rule", deleted).is_some() {
+                                revision += 1;
+                            }
+                        },
+                        "Delete"
+                    }
+                }
+            }
+        }
+
+        if let Some(Preview { rule_id, items }) = &*preview.read() {
+            div { class: "card card-border bg-base-100 shadow-sm",
+                div { class: "card-body",
+                    h2 { class: "card-title", "Rule {rule_id} would match {items.len()} articles" }
+                    ul {
+                        for item in items.iter().take(20) {
+                            li { key: "{item.id}", {item.title.clone().unwrap_or_default()} }
+                        }
+                    }
+                    button { class: "btn btn-sm", onclick: move |_| preview.set(None), "Close" }
+                }
+            }
+        }
+
+        h2 { if current.is_some() { "Edit rule" } else { "New rule" } }
+        if let Some(error) = &*error.read() {
+            div { class: "alert alert-error", "{error}" }
+        }
+        form { key: "{form_key}", class: "flex flex-col gap-2",
+            onsubmit: move |event| {
+                let form = event.data.values();
+                let value = |name: &str| form.get(name).map(|value| value.as_value());
+                let now = Utc::now();
+                let rule = NewRuleRecord {
+                    name: value("name").unwrap_or_default(),
+                    enabled: value("enabled").is_some_and(|value| value == "true" || value == "on"),
+                    channel_id: value("channel_id").and_then(|value| value.parse().ok()),
+                    title_pattern: non_empty(value("title_pattern")),
+                    description_pattern: non_empty(value("description_pattern")),
+                    author: non_empty(value("author")),
+                    tag: non_empty(value("tag")),
+                    action: value("action").unwrap_or_default(),
+                    action_value: non_empty(value("action_value")),
+                    create_date: now,
+                    update_date: now,
+                };
+                if let Err(rule_error) = rule.validate() {
+                    error.set(Some(rule_error.to_string()));
+                    return;
+                }
+
+                let rule_id = editing.read().as_ref().map(|rule| rule.id);
+                let saved = with_storage(|storage| storage.save_rule(rule_id, rule));
+                if notifications.write().check("Couldn't save the rule", saved).is_none() {
+                    return;
+                }
+                error.set(None);
+                editing.set(None);
+                revision += 1;
+            },
+            label { class: "input",
+                span { class: "label", "Name" }
+                input { name: "name", required: true,
+                    value: current.as_ref().map(|rule| rule.name.clone()).unwrap_or_default()
+                }
+            }
+            label { class: "select",
+                span { class: "label", "Feed" }
+                select { name: "channel_id",
+                    option { value: "", "Any feed" }
+                    for feed in feeds.iter() {
+                        option { value: "{feed.id}",
+                            selected: current.as_ref().and_then(|rule| rule.channel_id) == Some(feed.id),
+                            "{feed.name}"
+                        }
+                    }
+                }
+            }
+            label { class: "input",
+                span { class: "label", "Title regex" }
+                input { name: "title_pattern",
+                    value: current.as_ref().and_then(|rule| rule.title_pattern.clone()).unwrap_or_default()
+                }
+            }
+            label { class: "input",
+                span { class: "label", "Description regex" }
+                input { name: "description_pattern",
+                    value: current.as_ref().and_then(|rule| rule.description_pattern.clone()).unwrap_or_default()
+                }
+            }
+            label { class: "input",
+                span { class: "label", "Author" }
+                input { name: "author",
+                    value: current.as_ref().and_then(|rule| rule.author.clone()).unwrap_or_default()
+                }
+            }
+            label { class: "input",
+                span { class: "label", "Tag" }
+                input { name: "tag",
+                    value: current.as_ref().and_then(|rule| rule.tag.clone()).unwrap_or_default()
+                }
+            }
+            label { class: "select",
+                span { class: "label", "Action" }
+                select { name: "action",
+                    for (action, label) in ACTIONS {
+                        option { value: action,
+                            selected: current.as_ref().is_some_and(|rule| rule.action == action),
+                            {label}
+                        }
+                    }
+                }
+            }
+            label { class: "input",
+                span { class: "label", "Tag name or priority" }
+                input { name: "action_value",
+                    value: current.as_ref().and_then(|rule| rule.action_value.clone()).unwrap_or_default()
+                }
+            }
+            label { class: "label",
+                input { r#type: "checkbox", class: "checkbox", name: "enabled",
+                    checked: current.as_ref().is_none_or(|rule| rule.enabled)
+                }
+                "Enabled"
+            }
+            div { class: "flex gap-2",
+                button { class: "btn btn-primary", "Save Rule" }
+                if current.is_some() {
+                    button { class: "btn", r#type: "button",
+                        onclick: move |_| editing.set(None),
+                        "Cancel"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rss_reader_core::storage::{MemoryStorage, Storage};
+
+    use super::*;
+    use crate::components::testing::{rendered_text, subscribe, use_app_contexts};
+
+    #[test]
+    fn settings_list_every_rule() {
+        let mut storage = MemoryStorage::new();
+        subscribe(&mut storage, "news");
+        let now = Utc::now();
+        storage
+            .save_rule(
+                None,
+                NewRuleRecord {
+                    name: "Hide ads".to_string(),
+                    enabled: true,
+                    channel_id: None,
+                    title_pattern: Some("sponsored".to_string()),
+                    description_pattern: None,
+                    author: None,
+                    tag: None,
+                    action: "hide".to_string(),
+                    action_value: None,
+                    create_date: now,
+                    update_date: now,
+                },
+            )
+            .unwrap();
+        let rule = storage.load_rules().unwrap().remove(0);
+        let feeds = storage.load_feeds().unwrap();
+
+        let text = rendered_text(storage, || {
+            use_app_contexts();
+            rsx! { RulesSettings {} }
+        });
+
+        assert!(text.contains(&"Hide ads".to_string()));
+        assert!(text.contains(&describe(&rule, &feeds)));
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+use dioxus::prelude::*;
+use rss_reader_core::{
+    search::{SavedSearchFeed, SavedSearchRecord, SearchQuery, SearchResult, SearchResults},
+    Result,
+};
+
+use super::LoadError;
+use crate::{notifications::use_notifications, with_storage, CurrentView};
+
+pub fn run_search(query: String) -> Result<SearchResults> {
+    let parsed = SearchQuery::parse(&query);
+    let results = with_storage(|storage| storage.search(&parsed))?;
+    Ok(SearchResults { query, results })
+}
+
+#[component]
+pub fn SearchBox(mut current_view: Signal<Option<CurrentView>>) -> Element {
+    let mut notifications = use_notifications();
+
+    rsx! {
+        form { onsubmit: move |event| {
+                let form = event.data.values();
+                let query = form.get("query").map(|value| value.as_value()).unwrap_or_default();
+                if query.trim().is_empty() {
+                    return;
+                }
+                if let Some(results) = notifications.write().check("Couldn't search", run_search(query)) {
+                    current_view.set(Some(CurrentView::Search(results)));
+                }
+            },
+            label { class: "input",
+                span { class: "label", "Search"}
+                input { class: "input", name: "query", r#type: "search",
+                    placeholder: "\"exact phrase\" feed: author: is:unread before: after:"
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn SearchResultList(results: Vec<SearchResult>) -> Element {
+    let mut notifications = use_notifications();
+
+    if results.is_empty() {
+        return rsx! {
+            p { "No matching articles" }
+        };
+    }
+
+    rsx! {
+        ul {
+            for result in results {
+                li { key: "{result.id}",
+                    div { class: "card card-border bg-base-100 min-w-[45ch] max-w-[80ch] shadow-sm",
+                        div { class: "card-body",
+                            h2 { class: "card-title",
+                                a {
+                                    class: if !result.is_read {"font-bold"},
+                                    href: result.url.clone().unwrap_or_default(),
+                                    onclick: move |_| {
+                                        let marked = with_storage(|storage| storage.set_item_read(result.id, true));
+                                        notifications.write().check("Couldn't mark the article as read", marked);
+                                    },
+                                    {result.title.clone().unwrap_or_default()}
+                                }
+                            }
+                            p { class: "text-sm",
+                                "{result.feed_name}"
+                                if let Some(pub_date) = &result.pub_date {
+                                    " · {pub_date}"
+                                }
+                            }
+                            p {
+                                for (text, highlighted) in result.snippet_segments() {
+                                    if highlighted {
+                                        mark { "{text}" }
+                                    } else {
+                                        span { "{text}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Pins the current query to the sidebar.
+#[component]
+pub fn SaveSearchForm(query: String, mut current_view: Signal<Option<CurrentView>>) -> Element {
+    let mut notifications = use_notifications();
+
+    rsx! {
+        form { onsubmit: move |event| {
+                let form = event.data.values();
+                let name = form.get("name").map(|value| value.as_value()).unwrap_or_default();
+                let saved = with_storage(|storage| storage.save_search(&name, &query))
+                    .and_then(|saved_search_id| {
+                        Ok(SavedSearchFeed {
+                            saved_search_id,
+                            name: name.trim().to_string(),
+                            search: run_search(query.clone())?,
+                        })
+                    });
+                if let Some(saved_search) = notifications.write().check("Couldn't save the search", saved) {
+                    current_view.set(Some(CurrentView::SavedSearch(saved_search)));
+                }
+            },
+            label { class: "input",
+                span { class: "label", "Name"}
+                input { class: "input", name: "name", required: true }
+            }
+            button { class: "btn", "Save Search" }
+        }
+    }
+}
+
+fn open_saved_search(record: &SavedSearchRecord) -> Result<SavedSearchFeed> {
+    Ok(SavedSearchFeed {
+        saved_search_id: record.id,
+        name: record.name.clone(),
+        search: run_search(record.query.clone())?,
+    })
+}
+
+/// Saved searches listed under the subscriptions as virtual feeds.
+#[component]
+pub fn SavedSearchNav(mut current_view: Signal<Option<CurrentView>>) -> Element {
+    let mut notifications = use_notifications();
+    let selected = match &*current_view.read() {
+        Some(CurrentView::SavedSearch(saved_search)) => Some(saved_search.saved_search_id),
+        _ => None,
+    };
+    let saved_searches = with_storage(|storage| storage.load_saved_searches());
+    // counted outside of rendering, again whenever the view changes since
+    // anything that changes read state goes through it
+    let unread_counts = use_resource(move || async move {
+        let _ = current_view.read();
+        let counted = with_storage(|storage| {
+            storage
+                .load_saved_searches()?
+                .into_iter()
+                .map(|record| Ok((record.id, storage.count_unread(&record.query)?)))
+                .collect::<Result<HashMap<_, _>>>()
+        });
+        notifications
+            .write()
+            .check(
+                "Couldn't count the unread articles of the saved searches",
+                counted,
+            )
+            .unwrap_or_default()
+    });
+    let unread_counts = unread_counts.read().clone().unwrap_or_default();
+    let saved_searches = match saved_searches {
+        Ok(saved_searches) => saved_searches,
+        Err(error) => {
+            return rsx! {
+                li { LoadError { action: "Couldn't load the saved searches", error: error.to_string() } }
+            }
+        }
+    };
+
+    rsx! {
+        if !saved_searches.is_empty() {
+            li { class: "menu-title", "Saved Searches" }
+        }
+        for record in saved_searches {
+            li { key: "saved-search-{record.id}",
+                class: if selected == Some(record.id) {"active-feed"},
+                a { onclick: {
+                        let record = record.clone();
+                        move |_| {
+                            let opened = open_saved_search(&record);
+                            if let Some(saved_search) = notifications.write().check("Couldn't open the saved search", opened) {
+                                current_view.set(Some(CurrentView::SavedSearch(saved_search)));
+                            }
+                        }
+                    },
+                    "{record.name}"
+                    if let Some(unread) = unread_counts.get(&record.id).filter(|unread| **unread > 0) {
+                        span { class: "badge badge-sm", "{unread}" }
+                    }
+                    button { class: "cursor-pointer", aria_label: "Delete saved search",
+                        onclick: move |event| {
+                            event.stop_propagation();
+                            let deleted = with_storage(|storage| storage.delete_saved_search(record.id));
+                            if notifications.write().check("Couldn't delete the saved search", deleted).is_none() {
+                                return;
+                            }
+                            if selected == Some(record.id) {
+                                current_view.set(None);
+                            } else {
+                                // re-render so the deleted search disappears
+                                current_view.write();
+                            }
+                        },
+                        "×"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rss_reader_core::storage::{MemoryStorage, Storage};
+
+    use super::*;
+    use crate::components::testing::{add_item, rendered_text, subscribe, use_app_contexts};
+
+    #[test]
+    fn saved_searches_show_how_many_matches_are_unread() {
+        let mut storage = MemoryStorage::new();
+        let feed_id = subscribe(&mut storage, "news");
+        let read = add_item(&mut storage, feed_id, "rust release");
+        add_item(&mut storage, feed_id, "another rust release");
+        storage.set_item_read(read, true).unwrap();
+        storage.save_search("Rust", "rust").unwrap();
+
+        let mut text = rendered_text(storage, || {
+            use_app_contexts();
+            let current_view = use_signal(|| None);
+            rsx! { SavedSearchNav { current_view } }
+        });
+
+        text.sort();
+        assert_eq!(text, ["1", "Rust"]);
+    }
+}
@@ -1,7 +1,15 @@
-use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper};
+use std::str::FromStr;
+
 use dioxus::prelude::*;
+use rss_reader_core::{fetcher::discover_feed, FeedRecord};
+use url::Url;
 
-use crate::{schema, ChannelFeed, CurrentView, FeedItemRecord, DB};
+use super::{FeedIcon, FeedProperties};
+use crate::{
+    load_all_feeds,
+    notifications::{use_notifications, Notice, Notifications},
+    with_storage, ChannelFeed, CurrentView,
+};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct FeedNameId {
@@ -9,13 +17,48 @@ pub struct FeedNameId {
     pub channel_name: String,
 }
 
+/// What the context menu of a feed is showing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FeedMenu {
+    Actions,
+    Properties,
+    Rename,
+    ChangeUrl,
+    Delete,
+}
+
+fn reload_feeds(
+    mut stored_feeds: Signal<Vec<FeedRecord>>,
+    mut notifications: Signal<Notifications>,
+) {
+    let feeds = with_storage(|storage| storage.load_feeds());
+    if let Some(feeds) = notifications
+        .write()
+        .check("Couldn't reload your feeds", feeds)
+    {
+        stored_feeds.set(feeds);
+    }
+}
+
 #[component]
 pub fn SideNavItem(
     mut current_view: Signal<Option<CurrentView>>,
+    mut stored_feeds: Signal<Vec<FeedRecord>>,
     selected_feed_index: Memo<Option<usize>>,
     feed_metadata: FeedNameId,
     index: usize,
 ) -> Element {
+    let mut menu: Signal<Option<FeedMenu>> = use_signal(|| None);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+    let mut notifications = use_notifications();
+    let feed_id = feed_metadata.id;
+    let feed_url = stored_feeds
+        .read()
+        .iter()
+        .find(|feed| feed.id == feed_id)
+        .map(|feed| feed.feed_url.clone())
+        .unwrap_or_default();
+
     rsx! {
 
         li { key: "{feed_metadata.id}", onmounted: move |element| async move {
@@ -26,34 +69,147 @@ pub fn SideNavItem(
                 }
             }
         },
+        oncontextmenu: move |event| {
+            event.prevent_default();
+            error.set(None);
+            menu.set(Some(FeedMenu::Actions));
+        },
         class: if selected_feed_index.read().is_some() && selected_feed_index.read().unwrap() == index {"active-feed"},
             {
                 rsx!{
-                    a { onclick: move |_| {
-                    let items = DB.with_borrow_mut(move |conn| {
-                        use schema::feed_items::dsl::*;
-
-                        // select by channel id
-                        feed_items
-                            .filter(channel_id.eq(feed_metadata.id))
-                            .select(FeedItemRecord::as_select())
-                            .load::<FeedItemRecord>(conn)
-                            .unwrap()
+                    a { onclick: {
+                        let feed_metadata = feed_metadata.clone();
+                        move |_| {
+                    let loaded = with_storage(|storage| {
+                        ChannelFeed::load(storage, &stored_feeds.read(), feed_metadata.id)
                     });
-                    let channel_feed = ChannelFeed {
-                        name: feed_metadata.channel_name.clone(),
-                        channel_id: feed_metadata.id,
-                        items,
-                        selected: index,
-                    };
-
-                    current_view.set(Some(CurrentView::SelectedFeed(channel_feed)));
+                    if let Some(channel_feed) = notifications.write().check("Couldn't open the feed", loaded) {
+                        current_view.set(Some(CurrentView::SelectedFeed(channel_feed)));
+                    }
 
-                    },
+                    }},
+                        FeedIcon { feed_id: feed_metadata.id }
                         {feed_metadata.channel_name.clone()}
                     }
                 }
             }
+            if let Some(error) = &*error.read() {
+                div { class: "alert alert-error", "{error}" }
+            }
+            match menu() {
+                Some(FeedMenu::Actions) => rsx! {
+                    ul { class: "menu bg-base-100 rounded-box shadow-sm",
+                        li { a { onclick: move |_| menu.set(Some(FeedMenu::Properties)), "Properties" } }
+                        li { a { onclick: move |_| menu.set(Some(FeedMenu::Rename)), "Rename" } }
+                        li { a { onclick: move |_| menu.set(Some(FeedMenu::ChangeUrl)), "Change Feed URL" } }
+                        li { a { class: "text-error", onclick: move |_| menu.set(Some(FeedMenu::Delete)), "Unsubscribe" } }
+                        li { a { onclick: move |_| menu.set(None), "Cancel" } }
+                    }
+                },
+                Some(FeedMenu::Properties) => rsx! {
+                    FeedProperties { feed_id, on_close: move |_| menu.set(None) }
+                },
+                Some(FeedMenu::Rename) => rsx! {
+                    form { class: "flex flex-col gap-2",
+                        onsubmit: move |event| {
+                            let form = event.data.values();
+                            let new_name = form.get("name").map(|value| value.as_value()).unwrap_or_default();
+                            if let Err(rename_error) = with_storage(|storage| storage.rename_feed(feed_id, &new_name)) {
+                                error.set(Some(rename_error.to_string()));
+                                return;
+                            }
+
+                            if let Some(CurrentView::SelectedFeed(channel)) = &mut *current_view.write() {
+                                if channel.channel_id == feed_id {
+                                    channel.name = new_name.trim().to_string();
+                                }
+                            }
+                            reload_feeds(stored_feeds, notifications);
+                            error.set(None);
+                            menu.set(None);
+                        },
+                        input { class: "input input-sm", name: "name", required: true,
+                            value: feed_metadata.channel_name.clone()
+                        }
+                        div { class: "flex gap-2",
+                            button { class: "btn btn-sm btn-primary", "Rename" }
+                            button { class: "btn btn-sm", r#type: "button", onclick: move |_| menu.set(None), "Cancel" }
+                        }
+                    }
+                },
+                Some(FeedMenu::ChangeUrl) => rsx! {
+                    form { class: "flex flex-col gap-2",
+                        onsubmit: move |event| {
+                            let form = event.data.values();
+                            let new_url = form.get("feed_url").map(|value| value.as_value()).unwrap_or_default();
+                            spawn(async move {
+                                let Ok(mut new_url) = Url::from_str(new_url.trim()) else {
+                                    error.set(Some(format!("{new_url} isn't a valid URL")));
+                                    return;
+                                };
+                                // only switch once the new address actually serves a feed
+                                if let Err(fetch_error) = discover_feed(&mut new_url) {
+                                    notifications.write().report(Notice::feed_error(
+                                        format!("Couldn't switch to {new_url}"),
+                                        &fetch_error,
+                                        new_url.as_str(),
+                                        None,
+                                    ));
+                                    return;
+                                }
+                                if let Err(change_error) = with_storage(|storage| storage.change_feed_url(feed_id, &new_url)) {
+                                    error.set(Some(change_error.to_string()));
+                                    return;
+                                }
+
+                                reload_feeds(stored_feeds, notifications);
+                                error.set(None);
+                                menu.set(None);
+                            });
+                        },
+                        input { class: "input input-sm", name: "feed_url", r#type: "url", required: true,
+                            value: feed_url
+                        }
+                        div { class: "flex gap-2",
+                            button { class: "btn btn-sm btn-primary", "Save" }
+                            button { class: "btn btn-sm", r#type: "button", onclick: move |_| menu.set(None), "Cancel" }
+                        }
+                    }
+                },
+                Some(FeedMenu::Delete) => rsx! {
+                    form { class: "flex flex-col gap-2",
+                        onsubmit: move |event| {
+                            let form = event.data.values();
+                            let keep_starred = form
+                                .get("keep_starred")
+                                .is_some_and(|value| matches!(value.as_value().as_str(), "true" | "on"));
+                            spawn(async move {
+                                let deleted = with_storage(|storage| storage.delete_feed(feed_id, keep_starred));
+                                if notifications.write().check("Couldn't unsubscribe", deleted).is_none() {
+                                    return;
+                                }
+                                menu.set(None);
+
+                                let loaded = load_all_feeds().await;
+                                if let Some((feeds, pages)) = notifications.write().check("Couldn't load your feeds", loaded) {
+                                    stored_feeds.set(feeds);
+                                    current_view.set(Some(CurrentView::AllFeeds(pages)));
+                                }
+                            });
+                        },
+                        p { "Unsubscribe from {feed_metadata.channel_name} and delete its articles?" }
+                        label { class: "label",
+                            input { r#type: "checkbox", class: "checkbox", name: "keep_starred", checked: true }
+                            "Keep starred articles"
+                        }
+                        div { class: "flex gap-2",
+                            button { class: "btn btn-sm btn-error", "Unsubscribe" }
+                            button { class: "btn btn-sm", r#type: "button", onclick: move |_| menu.set(None), "Cancel" }
+                        }
+                    }
+                },
+                None => rsx! {},
+            }
         }
     }
 }
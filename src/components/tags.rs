@@ -0,0 +1,137 @@
+use dioxus::prelude::*;
+use rss_reader_core::tags::USER_SOURCE;
+
+use super::LoadError;
+use crate::{
+    notifications::{use_notifications, Notifications},
+    with_storage, CurrentView,
+};
+
+fn open_tag(
+    mut current_view: Signal<Option<CurrentView>>,
+    mut notifications: Signal<Notifications>,
+    tag_id: i32,
+) {
+    let loaded = with_storage(|storage| storage.load_tag_feed(tag_id));
+    if let Some(feed) = notifications.write().check("Couldn't open the tag", loaded) {
+        current_view.set(Some(CurrentView::Tagged(feed)));
+    }
+}
+
+/// Tags on an article, with a small form for adding tags of your own.
+#[component]
+pub fn TagChips(item_id: i32, current_view: Signal<Option<CurrentView>>) -> Element {
+    // bumped whenever tags change so the chips are re-read from the database
+    let mut revision = use_signal(|| 0);
+    let mut notifications = use_notifications();
+    let _ = revision.read();
+    let item_tags = match with_storage(|storage| storage.load_item_tags(item_id)) {
+        Ok(item_tags) => item_tags,
+        Err(error) => {
+            return rsx! {
+                LoadError { action: "Couldn't load the article's tags", error: error.to_string() }
+            }
+        }
+    };
+
+    rsx! {
+        div { class: "flex flex-wrap items-center gap-1",
+            for tag in item_tags {
+                div { key: "{tag.tag_id}", class: "badge badge-outline gap-1",
+                    a { class: "cursor-pointer",
+                        onclick: move |_| open_tag(current_view, notifications, tag.tag_id),
+                        "{tag.name}"
+                    }
+                    if tag.source == USER_SOURCE {
+                        button { class: "cursor-pointer", aria_label: "Remove tag",
+                            onclick: move |_| {
+                                let removed = with_storage(|storage| storage.untag_item(item_id, tag.tag_id));
+                                if notifications.write().check("Couldn't remove the tag", removed).is_some() {
+                                    revision += 1;
+                                }
+                            },
+                            "×"
+                        }
+                    }
+                }
+            }
+            form { class: "inline-flex",
+                onsubmit: move |event| {
+                    let form = event.data.values();
+                    let Some(name) = form.get("tag").map(|value| value.as_value()) else {
+                        return;
+                    };
+                    let tagged = with_storage(|storage| storage.tag_item(item_id, &name, USER_SOURCE));
+                    if notifications.write().check("Couldn't add the tag", tagged).is_some() {
+                        revision += 1;
+                    }
+                },
+                input { class: "input input-xs w-24", name: "tag", placeholder: "Add tag", required: true }
+            }
+        }
+    }
+}
+
+/// Sidebar list of every tag for browsing articles across feeds.
+#[component]
+pub fn TagNav(current_view: Signal<Option<CurrentView>>) -> Element {
+    let notifications = use_notifications();
+    let selected_tag = match &*current_view.read() {
+        Some(CurrentView::Tagged(tag_feed)) => Some(tag_feed.tag_id),
+        _ => None,
+    };
+    let all_tags = match with_storage(|storage| storage.load_tags()) {
+        Ok(all_tags) => all_tags,
+        Err(error) => {
+            return rsx! {
+                li { LoadError { action: "Couldn't load the tags", error: error.to_string() } }
+            }
+        }
+    };
+
+    rsx! {
+        if !all_tags.is_empty() {
+            li { class: "menu-title", "Tags" }
+        }
+        for (tag, count) in all_tags {
+            li { key: "tag-{tag.id}",
+                class: if selected_tag == Some(tag.id) {"active-feed"},
+                a { onclick: move |_| open_tag(current_view, notifications, tag.id),
+                    "{tag.name}"
+                    span { class: "badge badge-sm", "{count}" }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rss_reader_core::storage::{MemoryStorage, Storage};
+
+    use super::*;
+    use crate::components::testing::{add_item, rendered_text, subscribe, use_app_contexts};
+
+    #[test]
+    fn the_nav_lists_tags_in_use_with_their_counts() {
+        let mut storage = MemoryStorage::new();
+        let feed_id = subscribe(&mut storage, "news");
+        for title in ["first", "second"] {
+            let item_id = add_item(&mut storage, feed_id, title);
+            storage.tag_item(item_id, "rust", USER_SOURCE).unwrap();
+        }
+        let item_id = add_item(&mut storage, feed_id, "third");
+        storage.tag_item(item_id, "go", USER_SOURCE).unwrap();
+        let tag_id = storage.load_item_tags(item_id).unwrap()[0].tag_id;
+        storage.untag_item(item_id, tag_id).unwrap();
+
+        let mut text = rendered_text(storage, || {
+            use_app_contexts();
+            let current_view = use_signal(|| None);
+            rsx! { TagNav { current_view } }
+        });
+
+        text.sort();
+        assert_eq!(text, ["2", "rust"]);
+    }
+}
@@ -0,0 +1,86 @@
+//! Renders components against [`MemoryStorage`] without a window, for
+//! checking what they show.
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use chrono::Utc;
+use dioxus::{
+    dioxus_core::{Mutation, Mutations},
+    prelude::*,
+};
+use rss_reader_core::{
+    storage::{MemoryStorage, Storage},
+    NewFeedItemRecord, NewFeedRecord,
+};
+use smol::{future, Timer};
+
+use super::{feed_icon::FeedIcons, MuteRevision};
+use crate::{notifications::Notifications, SharedStorage};
+
+pub fn subscribe(storage: &mut MemoryStorage, name: &str) -> i32 {
+    storage
+        .insert_feed(NewFeedRecord::new(
+            "example.com".to_string(),
+            format!("https://example.com/{name}.xml"),
+            name.to_string(),
+            Utc::now(),
+        ))
+        .unwrap()
+}
+
+pub fn add_item(storage: &mut MemoryStorage, channel_id: i32, title: &str) -> i32 {
+    storage
+        .insert_item(NewFeedItemRecord {
+            title: Some(title.to_string()),
+            ..NewFeedItemRecord::new(channel_id, Utc::now())
+        })
+        .unwrap()
+}
+
+/// Provides the contexts `App` gives every component, other than the
+/// storage. Call it first thing in a test's root component.
+pub fn use_app_contexts() {
+    use_context_provider(|| Signal::new(Notifications::default()));
+    use_context_provider(|| Signal::new(MuteRevision::default()));
+    let icons = use_memo(FeedIcons::new);
+    use_context_provider(|| icons);
+}
+
+/// The text `app` shows once it has rendered with `storage` and finished
+/// whatever it loads in the background, in no particular order. Only text
+/// that's worked out while rendering is seen, fixed labels are part of the
+/// templates.
+pub fn rendered_text(storage: MemoryStorage, app: fn() -> Element) -> Vec<String> {
+    let storage: SharedStorage = Rc::new(RefCell::new(storage));
+    let mut dom = VirtualDom::new(app).with_root_context(storage);
+    let mut text = text_of(dom.rebuild_to_vec());
+    // resources finish right away on memory storage, give up once nothing
+    // else happens
+    loop {
+        let worked = smol::block_on(future::or(
+            async {
+                dom.wait_for_work().await;
+                true
+            },
+            async {
+                Timer::after(Duration::from_millis(50)).await;
+                false
+            },
+        ));
+        if !worked {
+            return text;
+        }
+        text.extend(text_of(dom.render_immediate_to_vec()));
+    }
+}
+
+fn text_of(mutations: Mutations) -> Vec<String> {
+    mutations
+        .edits
+        .into_iter()
+        .filter_map(|edit| match edit {
+            Mutation::CreateTextNode { value, .. } | Mutation::SetText { value, .. } => Some(value),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,84 @@
+use std::{collections::VecDeque, time::Duration};
+
+use dioxus::prelude::*;
+use rss_reader_core::{fetcher::download_enclosure, profiles};
+use smol::Timer;
+
+use crate::{
+    notifications::{Notice, Notifications, Retry},
+    with_storage,
+};
+
+/// Enclosures waiting to be cached on disk, processed one at a time.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DownloadQueue {
+    pub pending: VecDeque<i32>,
+    pub active: Option<i32>,
+}
+
+impl DownloadQueue {
+    pub fn contains(&self, enclosure_id: i32) -> bool {
+        self.active == Some(enclosure_id) || self.pending.contains(&enclosure_id)
+    }
+
+    pub fn push(&mut self, enclosure_id: i32) {
+        if !self.contains(enclosure_id) {
+            self.pending.push_back(enclosure_id);
+        }
+    }
+}
+
+/// Downloads queued enclosures in the background for as long as the app runs.
+pub async fn run_download_queue(
+    mut queue: Signal<DownloadQueue>,
+    mut notifications: Signal<Notifications>,
+) {
+    loop {
+        let next = queue.write().pending.pop_front();
+        let Some(enclosure_id) = next else {
+            Timer::after(Duration::from_secs(1)).await;
+            continue;
+        };
+        queue.write().active = Some(enclosure_id);
+
+        let url = with_storage(|storage| storage.enclosure_url(enclosure_id));
+        let url = notifications
+            .write()
+            .check("Couldn't look up the episode to download", url)
+            .flatten();
+
+        let profile = notifications
+            .write()
+            .check("Couldn't find the download folder", profiles::current());
+        if let (Some(url), Some(profile)) = (url, profile) {
+            let episodes_dir = profile.episodes_dir();
+            let download_url = url.clone();
+            let result = smol::unblock(move || {
+                download_enclosure(enclosure_id, &download_url, &episodes_dir)
+            })
+            .await;
+            match result {
+                Ok(path) => {
+                    let saved = with_storage(|storage| {
+                        storage.set_enclosure_path(enclosure_id, &path.to_string_lossy())
+                    });
+                    let mut notifications = notifications.write();
+                    if notifications
+                        .check("Couldn't save the download", saved)
+                        .is_some()
+                    {
+                        notifications.report(Notice::info(format!("Downloaded {url}")));
+                    }
+                }
+                Err(error) => {
+                    notifications.write().report(
+                        Notice::error(format!("Couldn't download {url}: {error}"))
+                            .with_retry(Retry::Download(enclosure_id)),
+                    );
+                }
+            }
+        }
+
+        queue.write().active = None;
+    }
+}
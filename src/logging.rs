@@ -0,0 +1,45 @@
+use std::{fs, io, path::Path};
+
+use rss_reader_core::diagnostics::{LOG_FILES_KEPT, LOG_FILE_PREFIX};
+use tracing::Level;
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{RollingFileAppender, Rotation},
+};
+use tracing_subscriber::{filter::Targets, fmt, prelude::*};
+
+fn targets(level: Level) -> Targets {
+    Targets::new()
+        .with_target("rss_reader", level)
+        .with_target("rss_reader_core", level)
+        .with_default(Level::WARN)
+}
+
+/// Writes the app's events to a log file in `log_dir` that's started afresh
+/// every day, and the more important ones to stderr. The guard has to be
+/// kept until the app exits, or the last lines are lost.
+pub fn init(log_dir: &Path) -> io::Result<WorkerGuard> {
+    fs::create_dir_all(log_dir)?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .max_log_files(LOG_FILES_KEPT)
+        .build(log_dir)
+        .map_err(io::Error::other)?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    tracing_subscriber::registry()
+        .with(
+            fmt::layer()
+                .with_ansi(false)
+                .with_writer(writer)
+                .with_filter(targets(Level::DEBUG)),
+        )
+        .with(
+            fmt::layer()
+                .with_writer(io::stderr)
+                .with_filter(targets(Level::INFO)),
+        )
+        .init();
+    Ok(guard)
+}
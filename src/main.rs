@@ -1,240 +1,202 @@
-use std::{cell::RefCell, str::FromStr};
+use std::{cell::RefCell, rc::Rc, sync::Mutex};
 
-use chrono::{DateTime, Utc};
-use components::{AddFeed, Feed, FeedNameId, RefreshButton, SideNavItem};
-use diesel::{
-    backend::Backend, deserialize::FromSql, expression::AsExpression, serialize::ToSql, sql_types,
-    Connection, SqliteConnection,
+use chrono::Utc;
+use components::{
+    add_feed, feed_icons, refresh, AddFeed, ErrorLogNav, Feed, FeedNameId, IngestReport,
+    IngestReportAlert, MuteRevision, RefreshButton, SavedSearchNav, SearchBox, SideNavItem, TagNav,
+    ToastStack,
 };
 use dioxus::prelude::*;
-
-use rss::{Channel, Guid};
-use url::Url;
+use downloads::{run_download_queue, DownloadQueue};
+use notifications::{Notice, Notifications, Retry};
+use rss_reader_core::{
+    database,
+    pagination::{ArticlePages, ChannelPages},
+    profiles, retention,
+    search::{SavedSearchFeed, SearchResults},
+    storage::Storage,
+    tags::TagFeed,
+    Error, FeedRecord,
+};
+use smol::Timer;
+use tracing::error;
 
 mod components;
-mod models;
-mod schema;
-
-use diesel::prelude::*;
-
-thread_local! {
-    pub static DB: RefCell<SqliteConnection> = {
-        let database_url = "./testdb.db";
-        // Return the connection
-        RefCell::new(SqliteConnection::establish(database_url).unwrap())
-    };
-}
-
-#[derive(AsExpression, Debug, Clone)]
-#[diesel(sql_type = sql_types::Text)]
-pub struct StringTime(String);
-
-impl From<DateTime<Utc>> for StringTime {
-    fn from(val: DateTime<Utc>) -> Self {
-        Self(val.to_rfc2822())
-    }
-}
-
-impl From<StringTime> for DateTime<Utc> {
-    fn from(value: StringTime) -> Self {
-        DateTime::parse_from_rfc2822(&value.0).unwrap().into()
-    }
-}
-
-impl<Text, DB> Queryable<Text, DB> for StringTime
-where
-    DB: Backend,
-    Text: diesel::sql_types::SingleValue,
-    String: FromSql<Text, DB>,
-{
-    type Row = String;
-
-    fn build(row: Self::Row) -> diesel::deserialize::Result<Self> {
-        Ok(StringTime(row))
-    }
-}
-
-impl<DB> ToSql<sql_types::Text, DB> for StringTime
-where
-    DB: Backend,
-    String: ToSql<sql_types::Text, DB>,
-{
-    fn to_sql<'b>(
-        &'b self,
-        out: &mut diesel::serialize::Output<'b, '_, DB>,
-    ) -> diesel::serialize::Result {
-        self.0.to_sql(out)
-    }
-}
-
-#[derive(Queryable, Selectable, Identifiable, Clone, Debug, PartialEq)]
-#[diesel(table_name = schema::feeds)]
-#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
-pub struct FeedRecord {
-    pub id: i32,
-    pub url: String,
-    pub feed_url: String,
-    pub name: String,
-    #[diesel(deserialize_as = StringTime)]
-    pub create_date: DateTime<Utc>,
-    #[diesel(deserialize_as = StringTime)]
-    pub update_date: DateTime<Utc>,
-}
-
-#[derive(Insertable, Clone, Debug)]
-#[diesel(table_name = schema::feeds)]
-pub struct NewFeedRecord {
-    pub url: String,
-    pub feed_url: String,
-    pub name: String,
-    #[diesel(serialize_as = StringTime)]
-    pub create_date: DateTime<Utc>,
-    #[diesel(serialize_as = StringTime)]
-    pub update_date: DateTime<Utc>,
-}
-
-#[derive(Selectable, Queryable, Clone, Debug)]
-#[diesel(table_name = schema::feeds)]
-pub struct FeedTitleUrl {
-    pub feed_url: String,
-    pub name: String,
-}
-
-#[derive(Queryable, Selectable, Identifiable, Associations, Clone, Debug, PartialEq)]
-#[diesel(belongs_to(FeedRecord, foreign_key = channel_id))]
-#[diesel(table_name = schema::feed_items)]
-#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
-pub struct FeedItemRecord {
-    pub id: i32,
-    pub channel_id: i32,
-    pub title: Option<String>,
-    pub url: Option<String>,
-    pub description: Option<String>,
-    pub author: Option<String>,
-    pub pub_date: Option<String>,
-    #[diesel(deserialize_as = StringTime)]
-    pub create_date: DateTime<Utc>,
-    #[diesel(deserialize_as = StringTime)]
-    pub update_date: DateTime<Utc>,
-}
-
-#[derive(Insertable, Clone, Debug)]
-#[diesel(table_name = schema::feed_items)]
-pub struct NewFeedItemRecord {
-    pub channel_id: i32,
-    pub title: Option<String>,
-    pub url: Option<String>,
-    pub description: Option<String>,
-    pub author: Option<String>,
-    pub pub_date: Option<String>,
-    #[diesel(serialize_as = StringTime)]
-    pub create_date: DateTime<Utc>,
-    #[diesel(serialize_as = StringTime)]
-    pub update_date: DateTime<Utc>,
+mod downloads;
+mod logging;
+mod notifications;
+
+/// Where every view reads and changes feeds and articles, provided to the
+/// whole app as context.
+pub type SharedStorage = Rc<RefCell<dyn Storage>>;
+
+/// Runs `f` on the app's storage. Only works while a component runs, which
+/// includes its event handlers and the tasks it spawns.
+pub fn with_storage<T>(f: impl FnOnce(&mut dyn Storage) -> T) -> T {
+    let storage = consume_context::<SharedStorage>();
+    let mut storage = storage.borrow_mut();
+    f(&mut *storage)
 }
 
 const FAVICON: Asset = asset!("/assets/favicon.ico");
 const MAIN_CSS: Asset = asset!("/assets/styling/main.css");
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Article {
-    id: i32,
-    title: Option<String>,
-    link: Option<String>,
-    description: Option<String>,
-    author: Option<String>,
-    // guid: Option<Guid>,
-    pub_date: Option<DateTime<Utc>>,
-    channel_title: String,
-    channel_link: Url,
+/// The stored feeds and the first page of the All Articles view.
+async fn load_all_feeds() -> rss_reader_core::Result<(Vec<FeedRecord>, ArticlePages)> {
+    with_storage(|storage| {
+        let feed_urls = storage.load_feeds()?;
+        Ok((feed_urls, ArticlePages::first(storage)?))
+    })
 }
 
-async fn load_all_feeds() -> (Vec<FeedRecord>, Vec<Article>) {
-    let (feed_urls, all_items) = DB.with_borrow_mut(|conn| {
-        let feed_urls = {
-            use schema::feeds::dsl::*;
-            feeds
-                .select(FeedRecord::as_select())
-                .load::<FeedRecord>(conn)
-                .unwrap()
-        };
-
-        let all_items = {
-            use schema::feed_items;
-
-            feed_items::table
-                .left_join(schema::feeds::table)
-                .select((
-                    FeedItemRecord::as_select(),
-                    Option::<FeedTitleUrl>::as_select(),
-                ))
-                .load::<(FeedItemRecord, Option<FeedTitleUrl>)>(conn)
-                .unwrap()
-        };
-        (feed_urls, all_items)
-    });
-
-    let mut articles = Vec::new();
-    for (item, feed_data) in all_items {
-        let pub_date = if let Some(date) = item.pub_date {
-            if let Ok(time) = DateTime::parse_from_rfc2822(&date) {
-                Some(time.into())
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        let Some(FeedTitleUrl { feed_url, name }) = feed_data else {
-            continue;
-        };
-
-        let article = Article {
-            id: item.id,
-            title: item.title,
-            link: item.url,
-            description: item.description,
-            author: item.author,
-            pub_date,
-            channel_title: name,
-            channel_link: Url::from_str(&feed_url).unwrap(),
-        };
-        articles.push(article);
+/// Applies the retention policies in the background for as long as the app
+/// runs.
+async fn run_purge_job(mut notifications: Signal<Notifications>) {
+    loop {
+        // purging reads every item of the feeds with a policy, so it runs off
+        // the UI thread on a connection of its own
+        let purged = smol::unblock(|| {
+            let mut conn = database::establish(&profiles::current()?.database_path)?;
+            Ok::<_, Error>(retention::purge(&mut conn, Utc::now())?)
+        })
+        .await;
+        notifications
+            .write()
+            .check("Couldn't remove old articles", purged);
+        Timer::after(retention::PURGE_INTERVAL).await;
     }
-
-    articles.sort_by(|a, b| b.pub_date.cmp(&a.pub_date));
-    articles.dedup_by(|a, b| {
-        if let (Some(a), Some(b)) = (&a.link, &b.link) {
-            return a.eq_ignore_ascii_case(b);
-        }
-
-        false
-    });
-    (feed_urls, articles)
 }
 
 fn main() {
-    dioxus::launch(App);
+    // nothing is logged yet, so this can only go to the terminal
+    let profile = match profiles::current() {
+        Ok(profile) => profile,
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+    };
+    // the app works without its log file
+    let log_guard = logging::init(&profile.log_dir())
+        .inspect_err(|error| eprintln!("couldn't open the log file: {error}"))
+        .ok();
+
+    // open the database up front so a broken or too new one is reported
+    // before any window appears
+    let conn = match database::establish(&profile.database_path) {
+        Ok(conn) => conn,
+        Err(error) => {
+            error!("{error}");
+            if log_guard.is_none() {
+                eprintln!("{error}");
+            }
+            // exiting skips destructors, so flush the log first
+            drop(log_guard);
+            std::process::exit(1);
+        }
+    };
+
+    // the provider may run again for another window, which gets a connection
+    // of its own
+    let conn = Mutex::new(Some(conn));
+    let database_path = profile.database_path.clone();
+    dioxus::LaunchBuilder::new()
+        .with_context_provider(move || {
+            let conn = conn.lock().unwrap().take().unwrap_or_else(|| {
+                database::establish(&database_path).unwrap_or_else(|error| panic!("{error}"))
+            });
+            Box::new(Rc::new(RefCell::new(conn)) as SharedStorage)
+        })
+        .launch(App);
 }
 
 enum CurrentView {
-    AllFeeds(Vec<Article>),
+    AllFeeds(ArticlePages),
     // SelectedFeed(Channel, usize),
     SelectedFeed(ChannelFeed),
+    Tagged(TagFeed),
+    Search(SearchResults),
+    SavedSearch(SavedSearchFeed),
+    Settings,
+    Logs,
+    /// The errors of this session, with the raw response of one of them
+    /// showing.
+    ErrorLog(Option<usize>),
 }
 
 pub struct ChannelFeed {
     name: String,
     channel_id: i32,
-    items: Vec<FeedItemRecord>,
+    pages: ChannelPages,
     selected: usize,
 }
 
+impl ChannelFeed {
+    /// Loads a feed for display. `feeds` is the side nav list it's selected
+    /// from, as stored in `stored_feeds`.
+    fn load(
+        storage: &mut (impl Storage + ?Sized),
+        feeds: &[FeedRecord],
+        feed_id: i32,
+    ) -> rss_reader_core::Result<Self> {
+        let selected = feeds
+            .iter()
+            .position(|feed| feed.id == feed_id)
+            .ok_or(Error::FeedNotFound(feed_id))?;
+        Ok(ChannelFeed {
+            name: feeds[selected].name.clone(),
+            channel_id: feed_id,
+            pages: ChannelPages::first(storage, feed_id)?,
+            selected,
+        })
+    }
+}
+
 #[component]
 fn App() -> Element {
     let mut current_view: Signal<Option<CurrentView>> = use_signal(|| None);
     let mut stored_feeds: Signal<Vec<FeedRecord>> = use_signal(Vec::new);
+    let mut download_queue: Signal<DownloadQueue> = use_signal(DownloadQueue::default);
+    let mut show_muted = use_signal(|| false);
+    let ingest_report: Signal<Option<IngestReport>> = use_signal(|| None);
+    let mut notifications = use_context_provider(|| Signal::new(Notifications::default()));
+    let icons = use_memo(move || feed_icons(&stored_feeds.read()));
+    use_context_provider(|| icons);
+    use_context_provider(|| Signal::new(MuteRevision::default()));
+
+    use_future(move || run_download_queue(download_queue, notifications));
+    use_future(move || run_purge_job(notifications));
+
+    let on_retry = move |retry: Retry| match retry {
+        Retry::Subscribe(address) => {
+            spawn(add_feed(
+                address,
+                current_view,
+                stored_feeds,
+                ingest_report,
+                notifications,
+            ));
+        }
+        Retry::Refresh(feed_id) => {
+            spawn(refresh(
+                Some(feed_id),
+                current_view,
+                stored_feeds,
+                ingest_report,
+                notifications,
+            ));
+        }
+        Retry::RefreshAll => {
+            spawn(refresh(
+                None,
+                current_view,
+                stored_feeds,
+                ingest_report,
+                notifications,
+            ));
+        }
+        Retry::Download(enclosure_id) => download_queue.write().push(enclosure_id),
+    };
 
     let selected_feed_index = use_memo(move || match &*current_view.read() {
         Some(CurrentView::SelectedFeed(ChannelFeed { selected, .. })) => Some(*selected),
@@ -246,9 +208,17 @@ fn App() -> Element {
     // will probably need use resource
     use_effect(move || {
         spawn(async move {
-            let (feed_urls, feeds) = load_all_feeds().await;
-            current_view.set(Some(CurrentView::AllFeeds(feeds)));
-            stored_feeds.set(feed_urls);
+            match load_all_feeds().await {
+                Ok((feed_urls, feeds)) => {
+                    current_view.set(Some(CurrentView::AllFeeds(feeds)));
+                    stored_feeds.set(feed_urls);
+                }
+                Err(error) => {
+                    notifications
+                        .write()
+                        .report(Notice::error(format!("Couldn't load your feeds: {error}")));
+                }
+            }
         });
     });
 
@@ -267,17 +237,39 @@ fn App() -> Element {
                 label { for: "my-drawer-2", class: "btn btn-primary drawer-button md:hidden",
                     "Open drawer"
                 }
-                AddFeed { current_view, stored_feeds }
-                RefreshButton { current_view, stored_feeds, selected_feed_index }
-                Feed{ current_view }
+                AddFeed { current_view, stored_feeds, ingest_report }
+                SearchBox { current_view }
+                RefreshButton { current_view, stored_feeds, ingest_report }
+                IngestReportAlert { report: ingest_report }
+                label { class: "label",
+                    input { r#type: "checkbox", class: "toggle", checked: show_muted(),
+                        onchange: move |event| show_muted.set(event.checked())
+                    }
+                    "Show muted"
+                }
+                Feed{ current_view, download_queue, show_muted, on_retry }
+                ToastStack { current_view, on_retry }
             }
             div { class: "drawer-side",
                 label { for: "my-drawer-2", aria_label: "close sidebar", class: "drawer-overlay",
                 }
                 ul { class: "menu bg-base-200 text-base-content min-h-full w-80",
-                    for (index, record) in stored_feeds.read().iter().enumerate() {
-                        SideNavItem { 
+                    li {
+                        a { onclick: move |_| current_view.set(Some(CurrentView::Settings)),
+                            "Settings"
+                        }
+                    }
+                    ErrorLogNav { current_view }
+                    li {
+                        a { onclick: move |_| current_view.set(Some(CurrentView::Logs)),
+                            "Logs"
+                        }
+                    }
+                    // archived feeds only exist to hold on to starred articles
+                    for (index, record) in stored_feeds.read().iter().enumerate().filter(|(_, record)| !record.archived) {
+                        SideNavItem {
                             current_view,
+                            stored_feeds,
                             selected_feed_index,
                             feed_metadata: FeedNameId {
                                 id: record.id,
@@ -286,8 +278,48 @@ fn App() -> Element {
                             index
                         }
                     }
+                    SavedSearchNav { current_view }
+                    TagNav { current_view }
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rss_reader_core::storage::MemoryStorage;
+
+    use super::*;
+    use crate::components::testing::{add_item, subscribe};
+
+    #[test]
+    fn selecting_a_feed_loads_its_items_and_side_nav_position() {
+        let mut storage = MemoryStorage::new();
+        subscribe(&mut storage, "first");
+        let feed_id = subscribe(&mut storage, "second");
+        let item_id = add_item(&mut storage, feed_id, "Hello");
+        storage.set_item_read(item_id, true).unwrap();
+        let feeds = storage.load_feeds().unwrap();
+
+        let channel_feed = ChannelFeed::load(&mut storage, &feeds, feed_id).unwrap();
+
+        assert_eq!(channel_feed.name, "second");
+        assert_eq!(channel_feed.selected, 1);
+        assert_eq!(
+            channel_feed.pages.pages,
+            [storage.load_channel_items(feed_id).unwrap()]
+        );
+        assert!(channel_feed.pages.pages[0][0].is_read);
+    }
+
+    #[test]
+    fn selecting_a_feed_missing_from_the_side_nav_fails() {
+        let mut storage = MemoryStorage::new();
+        let feed_id = subscribe(&mut storage, "unlisted");
+
+        let loaded = ChannelFeed::load(&mut storage, &[], feed_id);
+
+        assert!(matches!(loaded, Err(Error::FeedNotFound(id)) if id == feed_id));
+    }
+}
@@ -1,8 +0,0 @@
-
-// CREATE TABLE IF NOT EXISTS feeds (
-//     id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
-//     url TEXT NOT NULL,
-//     feed_url TEXT NOT NULL,
-//     create_date INTEGER NOT NULL,
-//     update_date INTEGER NOT NULL
-// )
@@ -0,0 +1,233 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+use dioxus::prelude::*;
+use rss_reader_core::Error;
+use tracing::{info, warn};
+
+/// How many toasts are shown at once. Older ones are still in the log.
+pub const TOAST_LIMIT: usize = 4;
+
+/// Something that failed and can be tried again from its notice.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Retry {
+    Subscribe(String),
+    Refresh(i32),
+    RefreshAll,
+    Download(i32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    /// Goes away by itself after a few seconds.
+    Info,
+    /// Stays until dismissed and is kept in the error log.
+    Error,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Notice {
+    pub id: usize,
+    pub severity: Severity,
+    pub message: String,
+    pub date: DateTime<Utc>,
+    pub retry: Option<Retry>,
+    /// The address whose response couldn't be read as a feed, so it can be
+    /// looked at.
+    pub raw_response_url: Option<String>,
+}
+
+impl Notice {
+    pub fn info(message: impl Into<String>) -> Self {
+        Notice {
+            id: 0,
+            severity: Severity::Info,
+            message: message.into(),
+            date: Utc::now(),
+            retry: None,
+            raw_response_url: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Notice {
+            severity: Severity::Error,
+            ..Notice::info(message)
+        }
+    }
+
+    /// A feed that couldn't be fetched or read. `retry` is only offered when
+    /// trying again could turn out differently.
+    pub fn feed_error(
+        context: impl Display,
+        error: &Error,
+        feed_url: &str,
+        retry: Option<Retry>,
+    ) -> Self {
+        let mut notice = Notice::error(format!("{context}: {error}"));
+        if !matches!(
+            error,
+            Error::AlreadySubscribed(_) | Error::InvalidUrl(_) | Error::Invalid(_)
+        ) {
+            notice.retry = retry;
+        }
+        match error {
+            Error::Parse(_) | Error::NoFeed(_) => notice.with_raw_response(feed_url),
+            _ => notice,
+        }
+    }
+
+    pub fn with_retry(mut self, retry: Retry) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    pub fn with_raw_response(mut self, url: &str) -> Self {
+        self.raw_response_url = Some(url.to_string());
+        self
+    }
+}
+
+/// Everything reported this session, and which of it is showing as a toast.
+/// Provided to the whole app as a context, see [`use_notifications`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Notifications {
+    notices: Vec<Notice>,
+    toasts: Vec<usize>,
+    last_id: usize,
+}
+
+impl Notifications {
+    /// Shows `notice` as a toast and, if it's an error, adds it to the log.
+    /// Returns the id it was given.
+    pub fn report(&mut self, mut notice: Notice) -> usize {
+        match notice.severity {
+            Severity::Info => info!("{}", notice.message),
+            Severity::Error => warn!("{}", notice.message),
+        }
+        self.last_id += 1;
+        notice.id = self.last_id;
+        self.notices.push(notice);
+        self.toasts.push(self.last_id);
+        if self.toasts.len() > TOAST_LIMIT {
+            let hidden = self.toasts.remove(0);
+            self.forget_info(hidden);
+        }
+        self.last_id
+    }
+
+    /// Hands back the value, or reports the error as "`action`: error".
+    pub fn check<T, E: Display>(&mut self, action: &str, result: Result<T, E>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.report(Notice::error(format!("{action}: {error}")));
+                None
+            }
+        }
+    }
+
+    pub fn dismiss(&mut self, id: usize) {
+        self.toasts.retain(|toast| *toast != id);
+        self.forget_info(id);
+    }
+
+    /// Empties the error log, toasts of the errors in it included.
+    pub fn clear_errors(&mut self) {
+        self.notices
+            .retain(|notice| notice.severity != Severity::Error);
+        let notices = &self.notices;
+        self.toasts
+            .retain(|id| notices.iter().any(|notice| notice.id == *id));
+    }
+
+    pub fn toasts(&self) -> impl Iterator<Item = &Notice> {
+        self.toasts
+            .iter()
+            .filter_map(|id| self.notices.iter().find(|notice| notice.id == *id))
+    }
+
+    /// The errors of this session, newest first.
+    pub fn errors(&self) -> impl Iterator<Item = &Notice> {
+        self.notices
+            .iter()
+            .rev()
+            .filter(|notice| notice.severity == Severity::Error)
+    }
+
+    // info notices only live as long as their toast
+    fn forget_info(&mut self, id: usize) {
+        self.notices
+            .retain(|notice| notice.id != id || notice.severity == Severity::Error);
+    }
+}
+
+/// The app's [`Notifications`], as provided by `App`.
+pub fn use_notifications() -> Signal<Notifications> {
+    use_context()
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::*;
+
+    #[test]
+    fn dismissed_errors_stay_in_the_log() {
+        let mut notifications = Notifications::default();
+        let info = notifications.report(Notice::info("Downloaded"));
+        let error = notifications.report(Notice::error("Couldn't download"));
+
+        notifications.dismiss(info);
+        notifications.dismiss(error);
+
+        assert_eq!(notifications.toasts().count(), 0);
+        let logged = notifications
+            .errors()
+            .map(|notice| notice.id)
+            .collect::<Vec<_>>();
+        assert_eq!(logged, [error]);
+    }
+
+    #[test]
+    fn only_the_newest_toasts_are_shown() {
+        let mut notifications = Notifications::default();
+        for number in 0..TOAST_LIMIT + 2 {
+            notifications.report(Notice::error(format!("error {number}")));
+        }
+
+        let shown = notifications
+            .toasts()
+            .map(|notice| notice.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(shown.len(), TOAST_LIMIT);
+        assert_eq!(shown.first(), Some(&"error 2"));
+        assert_eq!(notifications.errors().count(), TOAST_LIMIT + 2);
+
+        notifications.clear_errors();
+        assert_eq!(notifications.toasts().count(), 0);
+    }
+
+    #[test]
+    fn feed_errors_offer_what_can_help() {
+        let url = "https://example.com/feed.xml";
+        let missing = Notice::feed_error(
+            "Couldn't subscribe",
+            &Error::NoFeed(Url::parse(url).unwrap()),
+            url,
+            Some(Retry::Subscribe(url.to_string())),
+        );
+        assert_eq!(missing.retry, Some(Retry::Subscribe(url.to_string())));
+        assert_eq!(missing.raw_response_url.as_deref(), Some(url));
+
+        let duplicate = Notice::feed_error(
+            "Couldn't subscribe",
+            &Error::AlreadySubscribed(Url::parse(url).unwrap()),
+            url,
+            Some(Retry::Subscribe(url.to_string())),
+        );
+        assert_eq!(duplicate.retry, None);
+        assert_eq!(duplicate.raw_response_url, None);
+    }
+}